@@ -0,0 +1,58 @@
+//! The commands of the `connectorx` command line tool, to use ConnectorX without writing Rust or
+//! Python.
+
+pub mod bench;
+pub mod copy;
+pub mod export;
+pub mod list_tables;
+pub mod output;
+pub mod sample;
+pub mod schema;
+pub mod sink;
+pub mod source;
+pub mod stream;
+pub mod validate;
+
+use anyhow::Error;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[clap(name = "connectorx", version, about)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Load a query several times and report the latency, throughput and peak memory
+    Bench(bench::BenchArgs),
+    /// Copy the result of a query into a Postgres table
+    Copy(copy::CopyArgs),
+    /// Load a query into a Parquet, CSV, Arrow IPC or JSON file
+    Export(export::ExportArgs),
+    /// List the tables of a database with their row and column counts
+    ListTables(list_tables::ListTablesArgs),
+    /// Print the first rows of a query, or a random sample of a table
+    Sample(sample::SampleArgs),
+    /// Show the schema of a table as Arrow, JSON or DDL, or compare it across two databases
+    Schema(schema::SchemaArgs),
+    /// Write the result of a query to stdout in the Arrow IPC stream format
+    Stream(stream::StreamArgs),
+    /// Check a connection and a query without loading it: columns, plan and partition queries
+    Validate(validate::ValidateArgs),
+}
+
+/// Run `command`.
+pub fn run(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Bench(args) => bench::run(args),
+        Command::Copy(args) => copy::run(args),
+        Command::Export(args) => export::run(args),
+        Command::ListTables(args) => list_tables::run(args),
+        Command::Sample(args) => sample::run(args),
+        Command::Schema(args) => schema::run(args),
+        Command::Stream(args) => stream::run(args),
+        Command::Validate(args) => validate::run(args),
+    }
+}
@@ -0,0 +1,13 @@
+//! The `connectorx` command line tool, to use ConnectorX without writing Rust or Python.
+
+use clap::Parser;
+use connectorx_cli::Cli;
+
+fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+    if let Err(e) = connectorx_cli::run(cli.command) {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
+}
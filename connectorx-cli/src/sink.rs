@@ -0,0 +1,120 @@
+//! Writing record batches to files: `parquet://`, `csv://`, `arrow-ipc://` and `json://`.
+
+use anyhow::{anyhow, Error};
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use arrow::{csv, ipc, json};
+use clap::ArgEnum;
+use fehler::{throw, throws};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression as ParquetCompression;
+use parquet::file::properties::WriterProperties;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Parquet,
+    Csv,
+    ArrowIpc,
+    Json,
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Snappy,
+    Gzip,
+    Lz4,
+    Zstd,
+}
+
+impl From<Compression> for ParquetCompression {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::Snappy => ParquetCompression::SNAPPY,
+            Compression::Gzip => ParquetCompression::GZIP,
+            Compression::Lz4 => ParquetCompression::LZ4,
+            Compression::Zstd => ParquetCompression::ZSTD,
+        }
+    }
+}
+
+/// A file to write, e.g. `parquet://out/lineitem.parquet`. The path is taken as is after the
+/// scheme, so that it can be relative.
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    pub format: FileFormat,
+    pub path: PathBuf,
+}
+
+impl TryFrom<&str> for FileSink {
+    type Error = Error;
+
+    #[throws(Error)]
+    fn try_from(dst: &str) -> FileSink {
+        let (scheme, path) = dst
+            .split_once("://")
+            .ok_or_else(|| anyhow!("{} is not a <format>://<path> destination", dst))?;
+        let format = match scheme {
+            "parquet" => FileFormat::Parquet,
+            "csv" => FileFormat::Csv,
+            "arrow-ipc" => FileFormat::ArrowIpc,
+            "json" => FileFormat::Json,
+            _ => throw!(anyhow!("unsupported destination format: {}", scheme)),
+        };
+        FileSink {
+            format,
+            path: PathBuf::from(path),
+        }
+    }
+}
+
+impl FileSink {
+    /// Write `batches` of `schema` to the file, replacing it if it exists. The compression is only
+    /// supported by Parquet.
+    #[throws(Error)]
+    pub fn write(
+        &self,
+        schema: SchemaRef,
+        batches: &[RecordBatch],
+        compression: Option<Compression>,
+    ) {
+        if compression.is_some() && self.format != FileFormat::Parquet {
+            throw!(anyhow!("compression is only supported for parquet"));
+        }
+        let file = File::create(&self.path)?;
+        match self.format {
+            FileFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(
+                        compression.map_or(ParquetCompression::UNCOMPRESSED, Into::into),
+                    )
+                    .build();
+                let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+                for batch in batches {
+                    writer.write(batch)?;
+                }
+                writer.close()?;
+            }
+            FileFormat::Csv => {
+                let mut writer = csv::Writer::new(file);
+                for batch in batches {
+                    writer.write(batch)?;
+                }
+            }
+            FileFormat::ArrowIpc => {
+                let mut writer = ipc::writer::FileWriter::try_new(file, &schema)?;
+                for batch in batches {
+                    writer.write(batch)?;
+                }
+                writer.finish()?;
+            }
+            FileFormat::Json => {
+                let mut writer = json::LineDelimitedWriter::new(file);
+                writer.write_batches(batches)?;
+                writer.finish()?;
+            }
+        }
+    }
+}
@@ -0,0 +1,581 @@
+//! Routing a connection string to the matching source, and reading queries into Arrow.
+
+use anyhow::{anyhow, Error};
+use arrow::array::Array;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use connectorx::{
+    prelude::*,
+    sources::{
+        mysql::BinaryProtocol as MySQLBinaryProtocol,
+        oracle::{connect_oracle, OracleDialect},
+        postgres::{rewrite_tls_args, BinaryProtocol as PgBinaryProtocol},
+    },
+    sql::{
+        get_partition_range_query, limit_query, limit_query_oracle, null_partition,
+        single_col_partition_query, single_col_partition_query_oracle, CXQuery,
+    },
+};
+use fehler::{throw, throws};
+use log::debug;
+use postgres::{Client, NoTls};
+use postgres_openssl::MakeTlsConnector;
+use sqlparser::dialect::{MsSqlDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::thread;
+use url::Url;
+
+/// A table of the database, from its catalog.
+#[derive(Debug, Clone)]
+pub struct TableInfo {
+    pub schema: String,
+    pub name: String,
+    /// The number of rows from the statistics of the database, if it keeps them.
+    pub rows: Option<u64>,
+    pub columns: u64,
+}
+
+/// The plan of a query, from `EXPLAIN`.
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    pub lines: Vec<String>,
+    pub estimated_rows: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceType {
+    Postgres,
+    SQLite,
+    MySQL,
+    MsSQL,
+    Oracle,
+}
+
+/// A connection string, in the same format as the one of the library.
+#[derive(Debug, Clone)]
+pub struct SourceConn {
+    pub ty: SourceType,
+    pub conn: Url,
+}
+
+impl TryFrom<&str> for SourceConn {
+    type Error = Error;
+
+    #[throws(Error)]
+    fn try_from(conn: &str) -> SourceConn {
+        let url = Url::parse(conn).map_err(|e| anyhow!("cannot parse {}: {}", conn, e))?;
+        // users from sqlalchemy may set engine in connection url (e.g. mssql+pymssql://...)
+        let ty = match url.scheme().split('+').next().unwrap_or_default() {
+            "postgres" | "postgresql" => SourceType::Postgres,
+            "sqlite" => SourceType::SQLite,
+            "mysql" => SourceType::MySQL,
+            "mssql" => SourceType::MsSQL,
+            "oracle" => SourceType::Oracle,
+            scheme => throw!(anyhow!("unsupported source: {}", scheme)),
+        };
+        SourceConn { ty, conn: url }
+    }
+}
+
+impl SourceConn {
+    /// Read `queries` as partitions of `origin_query` into record batches, along with their
+    /// schema (there may be no batch if there is no row).
+    #[throws(Error)]
+    pub fn read_arrow(
+        &self,
+        queries: &[CXQuery<String>],
+        origin_query: Option<String>,
+    ) -> (SchemaRef, Vec<RecordBatch>) {
+        let mut destination = ArrowDestination::new();
+        self.run(&mut destination, queries, origin_query)?;
+        (destination.arrow_schema(), destination.arrow()?)
+    }
+
+    /// Read `queries` like `read_arrow`, but pass the record batches of each partition to `write`
+    /// as soon as the partition is finished instead of keeping all of them in memory. The
+    /// partitions are read on another thread while `write` runs. Returns the schema.
+    #[throws(Error)]
+    pub fn stream_arrow<F>(
+        &self,
+        queries: &[CXQuery<String>],
+        origin_query: Option<String>,
+        mut write: F,
+    ) -> SchemaRef
+    where
+        F: FnMut(&RecordBatch) -> Result<(), Error>,
+    {
+        let (mut destination, rx) = ArrowDestination::new_streaming();
+        let source = self.clone();
+        let queries = queries.to_vec();
+        let dispatcher = thread::spawn(move || -> Result<SchemaRef, Error> {
+            source.run(&mut destination, &queries, origin_query)?;
+            Ok(destination.arrow_schema())
+        });
+        for partition in rx {
+            for batch in &partition.batches {
+                write(batch)?;
+            }
+        }
+        dispatcher
+            .join()
+            .map_err(|_| anyhow!("the thread reading the partitions panicked"))??
+    }
+
+    /// Run the dispatcher of the source loading `queries` into `destination`.
+    #[throws(Error)]
+    fn run(
+        &self,
+        destination: &mut ArrowDestination,
+        queries: &[CXQuery<String>],
+        origin_query: Option<String>,
+    ) {
+        match self.ty {
+            SourceType::Postgres => {
+                let (config, tls) = rewrite_tls_args(&self.conn)?;
+                match tls {
+                    Some(tls_conn) => {
+                        let source = PostgresSource::<PgBinaryProtocol, MakeTlsConnector>::new(
+                            config,
+                            tls_conn,
+                            queries.len(),
+                        )?;
+                        let dispatcher = Dispatcher::<
+                            _,
+                            _,
+                            PostgresArrowTransport<PgBinaryProtocol, MakeTlsConnector>,
+                        >::new(
+                            source, destination, queries, origin_query
+                        );
+                        debug!("Running dispatcher");
+                        dispatcher.run()?;
+                    }
+                    None => {
+                        let source = PostgresSource::<PgBinaryProtocol, NoTls>::new(
+                            config,
+                            NoTls,
+                            queries.len(),
+                        )?;
+                        let dispatcher = Dispatcher::<
+                            _,
+                            _,
+                            PostgresArrowTransport<PgBinaryProtocol, NoTls>,
+                        >::new(
+                            source, destination, queries, origin_query
+                        );
+                        debug!("Running dispatcher");
+                        dispatcher.run()?;
+                    }
+                }
+            }
+            SourceType::SQLite => {
+                // remove the first "sqlite://" manually since url.path is not correct for windows
+                let path = &self.conn.as_str()[9..];
+                let source = SQLiteSource::new(path, queries.len())?;
+                let dispatcher = Dispatcher::<_, _, SQLiteArrowTransport>::new(
+                    source,
+                    destination,
+                    queries,
+                    origin_query,
+                );
+                debug!("Running dispatcher");
+                dispatcher.run()?;
+            }
+            SourceType::MySQL => {
+                let source =
+                    MySQLSource::<MySQLBinaryProtocol>::new(&self.conn[..], queries.len())?;
+                let dispatcher = Dispatcher::<_, _, MySQLArrowTransport<MySQLBinaryProtocol>>::new(
+                    source,
+                    destination,
+                    queries,
+                    origin_query,
+                );
+                debug!("Running dispatcher");
+                dispatcher.run()?;
+            }
+            SourceType::MsSQL => {
+                let rt = Arc::new(tokio::runtime::Runtime::new()?);
+                let source = MsSQLSource::new(rt, &self.conn[..], queries.len())?;
+                let dispatcher = Dispatcher::<_, _, MsSQLArrowTransport>::new(
+                    source,
+                    destination,
+                    queries,
+                    origin_query,
+                );
+                debug!("Running dispatcher");
+                dispatcher.run()?;
+            }
+            SourceType::Oracle => {
+                let source = OracleSource::new(&self.conn[..], queries.len())?;
+                let dispatcher = Dispatcher::<_, _, OracleArrowTransport>::new(
+                    source,
+                    destination,
+                    queries,
+                    origin_query,
+                );
+                debug!("Running dispatcher");
+                dispatcher.run()?;
+            }
+        }
+    }
+
+    /// The names and the types of the columns of `query`, from the metadata of the source, without
+    /// reading any row.
+    #[throws(Error)]
+    pub fn describe(&self, query: &str) -> Vec<(String, String)> {
+        let queries = [CXQuery::naked(query)];
+        match self.ty {
+            SourceType::Postgres => {
+                let (config, tls) = rewrite_tls_args(&self.conn)?;
+                match tls {
+                    Some(tls_conn) => describe_source(
+                        PostgresSource::<PgBinaryProtocol, MakeTlsConnector>::new(
+                            config, tls_conn, 1,
+                        )?,
+                        &queries,
+                    )?,
+                    None => describe_source(
+                        PostgresSource::<PgBinaryProtocol, NoTls>::new(config, NoTls, 1)?,
+                        &queries,
+                    )?,
+                }
+            }
+            SourceType::SQLite => {
+                // remove the first "sqlite://" manually since url.path is not correct for windows
+                let path = &self.conn.as_str()[9..];
+                describe_source(SQLiteSource::new(path, 1)?, &queries)?
+            }
+            SourceType::MySQL => describe_source(
+                MySQLSource::<MySQLBinaryProtocol>::new(&self.conn[..], 1)?,
+                &queries,
+            )?,
+            SourceType::MsSQL => {
+                let rt = Arc::new(tokio::runtime::Runtime::new()?);
+                describe_source(MsSQLSource::new(rt, &self.conn[..], 1)?, &queries)?
+            }
+            SourceType::Oracle => describe_source(OracleSource::new(&self.conn[..], 1)?, &queries)?,
+        }
+    }
+
+    /// The plan of `query`, `None` for the sources whose plan is not supported (only Postgres and
+    /// Oracle are).
+    #[throws(Error)]
+    pub fn explain(&self, query: &str) -> Option<QueryPlan> {
+        match self.ty {
+            SourceType::Postgres => {
+                let mut client = connect_postgres(&self.conn)?;
+                let lines = client
+                    .query(format!("EXPLAIN {}", query).as_str(), &[])?
+                    .iter()
+                    .map(|row| row.try_get::<_, String>(0))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // the root node, e.g. `Seq Scan on t  (cost=0.00..35.50 rows=2550 width=4)`
+                let estimated_rows = lines.first().and_then(|line| {
+                    let rows = line.split("rows=").nth(1)?;
+                    rows.split(|c: char| !c.is_ascii_digit())
+                        .next()?
+                        .parse()
+                        .ok()
+                });
+                Some(QueryPlan {
+                    lines,
+                    estimated_rows,
+                })
+            }
+            SourceType::Oracle => {
+                const STATEMENT_ID: &str = "CX_VALIDATE";
+                let conn = connect_oracle(&self.conn)?.connect()?;
+                conn.execute(
+                    &format!(
+                        "EXPLAIN PLAN SET STATEMENT_ID = '{}' FOR {}",
+                        STATEMENT_ID, query
+                    ),
+                    &[],
+                )?;
+                let lines = conn
+                    .query_as::<Option<String>>(
+                        "SELECT plan_table_output \
+                         FROM TABLE(DBMS_XPLAN.DISPLAY('PLAN_TABLE', :1, 'TYPICAL'))",
+                        &[&STATEMENT_ID],
+                    )?
+                    .map(|line| line.map(Option::unwrap_or_default))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let estimated_rows = conn.query_row_as::<Option<u64>>(
+                    "SELECT cardinality FROM plan_table WHERE statement_id = :1 AND id = 0",
+                    &[&STATEMENT_ID],
+                )?;
+                conn.execute(
+                    "DELETE FROM plan_table WHERE statement_id = :1",
+                    &[&STATEMENT_ID],
+                )?;
+                Some(QueryPlan {
+                    lines,
+                    estimated_rows,
+                })
+            }
+            SourceType::SQLite | SourceType::MySQL | SourceType::MsSQL => None,
+        }
+    }
+
+    /// The tables of `schema` (the current schema by default) whose name is `LIKE` `pattern`, from
+    /// the catalog of the database.
+    #[throws(Error)]
+    pub fn list_tables(&self, schema: Option<&str>, pattern: Option<&str>) -> Vec<TableInfo> {
+        let literal = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let pattern = literal(pattern.unwrap_or("%"));
+        // the columns are the schema, the name, the number of columns, and the number of rows
+        // when the database keeps it
+        let query = match self.ty {
+            SourceType::Postgres => format!(
+                "SELECT t.table_schema::text, t.table_name::text, \
+                 (SELECT COUNT(*) FROM information_schema.columns c \
+                  WHERE c.table_schema = t.table_schema AND c.table_name = t.table_name), \
+                 CASE WHEN cl.reltuples < 0 THEN NULL ELSE cl.reltuples::bigint END \
+                 FROM information_schema.tables t \
+                 LEFT JOIN pg_namespace n ON n.nspname = t.table_schema \
+                 LEFT JOIN pg_class cl ON cl.relnamespace = n.oid AND cl.relname = t.table_name \
+                 WHERE t.table_schema = {} AND t.table_name LIKE {} ORDER BY 1, 2",
+                schema.map_or("current_schema()".to_string(), literal),
+                pattern
+            ),
+            SourceType::SQLite => format!(
+                "SELECT 'main', m.name, (SELECT COUNT(*) FROM pragma_table_info(m.name)) \
+                 FROM sqlite_master m WHERE m.type = 'table' AND m.name LIKE {} ORDER BY 2",
+                pattern
+            ),
+            SourceType::MySQL => format!(
+                "SELECT t.table_schema, t.table_name, \
+                 (SELECT COUNT(*) FROM information_schema.columns c \
+                  WHERE c.table_schema = t.table_schema AND c.table_name = t.table_name), \
+                 t.table_rows \
+                 FROM information_schema.tables t \
+                 WHERE t.table_schema = {} AND t.table_name LIKE {} ORDER BY 1, 2",
+                schema.map_or("DATABASE()".to_string(), literal),
+                pattern
+            ),
+            SourceType::MsSQL => format!(
+                "SELECT t.TABLE_SCHEMA, t.TABLE_NAME, \
+                 (SELECT COUNT(*) FROM INFORMATION_SCHEMA.COLUMNS c \
+                  WHERE c.TABLE_SCHEMA = t.TABLE_SCHEMA AND c.TABLE_NAME = t.TABLE_NAME), \
+                 (SELECT SUM(p.rows) FROM sys.partitions p \
+                  WHERE p.object_id = OBJECT_ID(QUOTENAME(t.TABLE_SCHEMA) + '.' + \
+                  QUOTENAME(t.TABLE_NAME)) AND p.index_id IN (0, 1)) \
+                 FROM INFORMATION_SCHEMA.TABLES t \
+                 WHERE t.TABLE_SCHEMA = {} AND t.TABLE_NAME LIKE {} ORDER BY 1, 2",
+                schema.map_or("SCHEMA_NAME()".to_string(), literal),
+                pattern
+            ),
+            // the unquoted names are stored in uppercase
+            SourceType::Oracle => format!(
+                "SELECT t.owner, t.table_name, \
+                 CAST((SELECT COUNT(*) FROM all_tab_columns c \
+                  WHERE c.owner = t.owner AND c.table_name = t.table_name) AS NUMBER(19)), \
+                 CAST(t.num_rows AS NUMBER(19)) \
+                 FROM all_tables t \
+                 WHERE t.owner = {} AND t.table_name LIKE UPPER({}) ORDER BY 1, 2",
+                schema.map_or(
+                    "SYS_CONTEXT('USERENV', 'CURRENT_SCHEMA')".to_string(),
+                    |s| { format!("UPPER({})", literal(s)) }
+                ),
+                pattern
+            ),
+        };
+        let (_, batches) = self.read_arrow(&[CXQuery::naked(query)], None)?;
+        let mut tables = vec![];
+        for batch in &batches {
+            let value = |col: usize, row: usize| -> Result<Option<String>, Error> {
+                if col >= batch.num_columns() || batch.column(col).is_null(row) {
+                    return Ok(None);
+                }
+                Ok(Some(array_value_to_string(batch.column(col), row)?))
+            };
+            let number = |col: usize, row: usize| -> Result<Option<u64>, Error> {
+                value(col, row)?
+                    .map(|s| {
+                        s.parse::<u64>()
+                            .or_else(|_| s.parse::<f64>().map(|v| v as u64))
+                            .map_err(|_| anyhow!("cannot parse {} as a count", s))
+                    })
+                    .transpose()
+            };
+            for row in 0..batch.num_rows() {
+                tables.push(TableInfo {
+                    schema: value(0, row)?.unwrap_or_default(),
+                    name: value(1, row)?.unwrap_or_default(),
+                    columns: number(2, row)?.unwrap_or_default(),
+                    rows: number(3, row)?,
+                });
+            }
+        }
+        tables
+    }
+
+    /// The first `n` rows of `query`.
+    #[throws(Error)]
+    pub fn limit_query(&self, query: &str, n: usize) -> CXQuery<String> {
+        let query = CXQuery::naked(query);
+        match self.ty {
+            SourceType::Postgres => limit_query(&query, n, &PostgreSqlDialect {})?,
+            SourceType::SQLite => limit_query(&query, n, &SQLiteDialect {})?,
+            SourceType::MySQL => limit_query(&query, n, &MySqlDialect {})?,
+            SourceType::MsSQL => CXQuery::Wrapped(format!(
+                "SELECT TOP {} * FROM ({}) CXTMPTAB_LIMIT",
+                n, query
+            )),
+            SourceType::Oracle => limit_query_oracle(&query, n),
+        }
+    }
+
+    /// At most `n` rows of a random sample of about `percent` % of the rows of `table`, the same
+    /// sample for the same `seed` as long as the table does not change.
+    #[throws(Error)]
+    pub fn table_sample_query(
+        &self,
+        table: &str,
+        n: usize,
+        percent: f64,
+        seed: u64,
+    ) -> CXQuery<String> {
+        let query = match self.ty {
+            SourceType::Postgres => format!(
+                "SELECT * FROM {} TABLESAMPLE BERNOULLI ({}) REPEATABLE ({}) LIMIT {}",
+                table, percent, seed, n
+            ),
+            SourceType::MsSQL => format!(
+                "SELECT TOP {} * FROM {} TABLESAMPLE ({} PERCENT) REPEATABLE ({})",
+                n, table, percent, seed
+            ),
+            SourceType::Oracle => format!(
+                "SELECT * FROM {} SAMPLE ({}) SEED ({}) WHERE rownum <= {}",
+                table, percent, seed, n
+            ),
+            SourceType::SQLite | SourceType::MySQL => {
+                throw!(anyhow!("{:?} does not support TABLESAMPLE", self.ty))
+            }
+        };
+        CXQuery::Wrapped(query)
+    }
+
+    /// Split `query` into `num` partitions on the integer column `col`, from its min and max. On
+    /// Oracle, the rows whose `col` is `NULL` are read by the partition of [`null_partition`].
+    #[throws(Error)]
+    pub fn partition(&self, query: &str, col: &str, num: usize) -> Vec<CXQuery<String>> {
+        let (min, max) = self.col_range(query, col)?;
+        let num = num.max(1) as i64;
+        let partition_size = (max - min + 1) / num;
+        let nulls = null_partition(None, num as usize) as i64;
+        let mut queries = vec![];
+        for i in 0..num {
+            let lower = min + i * partition_size;
+            let upper = match i == num - 1 {
+                true => max + 1,
+                false => min + (i + 1) * partition_size,
+            };
+            queries.push(self.part_query(query, col, lower, upper, i == nulls)?);
+        }
+        queries
+    }
+
+    /// The min and max of `col` in the result of `query`.
+    #[throws(Error)]
+    pub fn col_range(&self, query: &str, col: &str) -> (i64, i64) {
+        let range_query = match self.ty {
+            SourceType::Postgres => get_partition_range_query(query, col, &PostgreSqlDialect {})?,
+            SourceType::SQLite => get_partition_range_query(query, col, &SQLiteDialect {})?,
+            SourceType::MySQL => get_partition_range_query(query, col, &MySqlDialect {})?,
+            SourceType::MsSQL => get_partition_range_query(query, col, &MsSqlDialect {})?,
+            SourceType::Oracle => get_partition_range_query(query, col, &OracleDialect {})?,
+        };
+        let (_, batches) = self.read_arrow(&[CXQuery::Wrapped(range_query)], None)?;
+        let batch = batches
+            .iter()
+            .find(|b| b.num_rows() > 0)
+            .ok_or_else(|| anyhow!("no result for the range of {}", col))?;
+        let value = |idx: usize| -> Result<i64, Error> {
+            let col = batch.column(idx);
+            if col.is_null(0) {
+                return Ok(0);
+            }
+            let s = array_value_to_string(col, 0)?;
+            s.parse::<i64>()
+                .or_else(|_| s.parse::<f64>().map(|v| v as i64))
+                .map_err(|_| anyhow!("partition can only be done on int or float columns"))
+        };
+        (value(0)?, value(1)?)
+    }
+
+    #[throws(Error)]
+    fn part_query(
+        &self,
+        query: &str,
+        col: &str,
+        lower: i64,
+        upper: i64,
+        nulls: bool,
+    ) -> CXQuery<String> {
+        let query = match self.ty {
+            SourceType::Postgres => {
+                single_col_partition_query(query, col, lower, upper, &PostgreSqlDialect {})?
+            }
+            SourceType::SQLite => {
+                single_col_partition_query(query, col, lower, upper, &SQLiteDialect {})?
+            }
+            SourceType::MySQL => {
+                single_col_partition_query(query, col, lower, upper, &MySqlDialect {})?
+            }
+            SourceType::MsSQL => {
+                single_col_partition_query(query, col, lower, upper, &MsSqlDialect {})?
+            }
+            SourceType::Oracle => {
+                single_col_partition_query_oracle(query, col, lower, upper, nulls)
+            }
+        };
+        CXQuery::Wrapped(query)
+    }
+
+    /// The partition queries of `query`: split on `partition_on` if given, `query` as is
+    /// otherwise. The second element is the origin query, if it is split.
+    #[throws(Error)]
+    pub fn queries(
+        &self,
+        query: &str,
+        partition_on: Option<&str>,
+        partitions: usize,
+    ) -> (Vec<CXQuery<String>>, Option<String>) {
+        match partition_on {
+            Some(col) if partitions > 1 => (
+                self.partition(query, col, partitions)?,
+                Some(query.to_string()),
+            ),
+            _ => (vec![CXQuery::naked(query)], None),
+        }
+    }
+}
+
+#[throws(Error)]
+fn describe_source<S>(mut source: S, queries: &[CXQuery<String>]) -> Vec<(String, String)>
+where
+    S: Source,
+    S::TypeSystem: Debug,
+    S::Error: std::error::Error + Send + Sync + 'static,
+{
+    source.set_queries(queries);
+    source.fetch_metadata()?;
+    source
+        .names()
+        .into_iter()
+        .zip(source.schema().iter().map(|ty| format!("{:?}", ty)))
+        .collect()
+}
+
+/// A client of the Postgres database of `conn`, with the TLS options of the connection string.
+#[throws(Error)]
+pub fn connect_postgres(conn: &Url) -> Client {
+    let (config, tls) = rewrite_tls_args(conn)?;
+    match tls {
+        Some(tls_conn) => config.connect(tls_conn)?,
+        None => config.connect(NoTls)?,
+    }
+}
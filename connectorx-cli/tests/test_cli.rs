@@ -0,0 +1,157 @@
+use clap::{ErrorKind, Parser};
+use connectorx::sql::CXQuery;
+use connectorx_cli::bench::percentile;
+use connectorx_cli::copy::WriteMode;
+use connectorx_cli::output::format_table;
+use connectorx_cli::source::{SourceConn, SourceType};
+use connectorx_cli::{Cli, Command};
+use std::convert::TryFrom;
+use std::time::Duration;
+
+fn copy_mode(flags: &[&str]) -> Result<WriteMode, clap::Error> {
+    let mut args = vec![
+        "connectorx",
+        "copy",
+        "--from",
+        "postgres://localhost/src",
+        "--from-query",
+        "select * from t",
+        "--to",
+        "postgres://localhost/dst",
+        "--to-table",
+        "public.t",
+    ];
+    args.extend(flags);
+    match Cli::try_parse_from(args)?.command {
+        Command::Copy(args) => Ok(args.mode()),
+        command => panic!("parsed as {:?}", command),
+    }
+}
+
+#[test]
+fn test_percentile() {
+    let latencies: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+    assert_eq!(Duration::from_millis(5), percentile(&latencies, 50.));
+    assert_eq!(Duration::from_millis(10), percentile(&latencies, 95.));
+    assert_eq!(Duration::from_millis(10), percentile(&latencies, 100.));
+    assert_eq!(Duration::from_millis(1), percentile(&latencies, 0.));
+
+    let single = [Duration::from_millis(7)];
+    assert_eq!(Duration::from_millis(7), percentile(&single, 50.));
+}
+
+#[test]
+fn test_format_table() {
+    let rows = vec![
+        vec!["rows".to_string(), "10".to_string()],
+        vec!["throughput".to_string(), "1.5".to_string()],
+        vec!["名前".to_string(), "".to_string()],
+    ];
+    assert_eq!(
+        "metric     | value\n\
+         -----------+------\n\
+         rows       | 10\n\
+         throughput | 1.5\n\
+         名前         |",
+        format_table(&["metric", "value"], &rows)
+    );
+    assert_eq!("a | b\n--+--", format_table(&["a", "b"], &[]));
+}
+
+#[test]
+fn test_source_conn() {
+    let ty = |conn: &str| SourceConn::try_from(conn).unwrap().ty;
+    assert_eq!(
+        SourceType::Postgres,
+        ty("postgres://user:pw@localhost:5432/db")
+    );
+    assert_eq!(SourceType::Postgres, ty("postgresql://localhost/db"));
+    assert_eq!(SourceType::SQLite, ty("sqlite:///tmp/test.db"));
+    assert_eq!(SourceType::MySQL, ty("mysql://localhost:3306/db"));
+    assert_eq!(SourceType::MsSQL, ty("mssql+pymssql://localhost:1433/db"));
+    assert_eq!(SourceType::Oracle, ty("oracle://user:pw@localhost:1521/db"));
+
+    let err = SourceConn::try_from("redis://localhost").unwrap_err();
+    assert_eq!("unsupported source: redis", err.to_string());
+    let err = SourceConn::try_from("/tmp/test.db").unwrap_err();
+    assert!(err.to_string().starts_with("cannot parse /tmp/test.db"));
+}
+
+#[test]
+fn test_write_mode() {
+    assert_eq!(WriteMode::Append, copy_mode(&[]).unwrap());
+    assert_eq!(WriteMode::Append, copy_mode(&["--append"]).unwrap());
+    assert_eq!(WriteMode::Truncate, copy_mode(&["--truncate"]).unwrap());
+    assert_eq!(
+        WriteMode::Upsert(vec!["id".to_string(), "day".to_string()]),
+        copy_mode(&["--upsert-on", "id,day"]).unwrap()
+    );
+
+    for flags in &[
+        &["--truncate", "--append"][..],
+        &["--truncate", "--upsert-on", "id"],
+        &["--append", "--upsert-on", "id"],
+    ] {
+        assert_eq!(
+            ErrorKind::ArgumentConflict,
+            copy_mode(flags).unwrap_err().kind()
+        );
+    }
+}
+
+#[test]
+fn test_partitions_require_partition_on() {
+    let parse = |command: &str, flags: &[&str]| {
+        let mut args = vec!["connectorx", command, "--query", "select * from t"];
+        args.extend(match command {
+            "export" => &["--from", "postgres://localhost/db", "--to", "csv://t.csv"][..],
+            _ => &["--conn", "postgres://localhost/db"],
+        });
+        args.extend(flags);
+        Cli::try_parse_from(args).map(|_| ())
+    };
+    for command in &["bench", "export", "stream", "validate"] {
+        parse(command, &[]).unwrap();
+        parse(command, &["--partition-on", "id", "--partitions", "4"]).unwrap();
+        assert_eq!(
+            ErrorKind::MissingRequiredArgument,
+            parse(command, &["--partitions", "4"]).unwrap_err().kind()
+        );
+    }
+    assert_eq!(
+        ErrorKind::MissingRequiredArgument,
+        copy_mode(&["--partitions", "4"]).unwrap_err().kind()
+    );
+    copy_mode(&["--partition-on", "id", "--partitions", "4"]).unwrap();
+}
+
+#[test]
+fn test_table_sample_query() {
+    let conn = |conn: &str| SourceConn::try_from(conn).unwrap();
+    assert_eq!(
+        CXQuery::Wrapped(
+            "SELECT * FROM t TABLESAMPLE BERNOULLI (10) REPEATABLE (42) LIMIT 5".to_string()
+        ),
+        conn("postgres://localhost/db")
+            .table_sample_query("t", 5, 10., 42)
+            .unwrap()
+    );
+    assert_eq!(
+        CXQuery::Wrapped(
+            "SELECT TOP 5 * FROM t TABLESAMPLE (10 PERCENT) REPEATABLE (42)".to_string()
+        ),
+        conn("mssql://localhost/db")
+            .table_sample_query("t", 5, 10., 42)
+            .unwrap()
+    );
+    assert_eq!(
+        CXQuery::Wrapped("SELECT * FROM t SAMPLE (0.5) SEED (42) WHERE rownum <= 5".to_string()),
+        conn("oracle://localhost/db")
+            .table_sample_query("t", 5, 0.5, 42)
+            .unwrap()
+    );
+    let err = conn("mysql://localhost/db")
+        .table_sample_query("t", 5, 10., 42)
+        .unwrap_err();
+    assert_eq!("MySQL does not support TABLESAMPLE", err.to_string());
+}
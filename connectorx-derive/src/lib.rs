@@ -0,0 +1,204 @@
+//! Derive macros for ConnectorX.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta};
+
+/// Derive `connectorx::sources::ProduceRow` for a struct whose fields correspond to the columns of
+/// a result, in order. The generated `produce_row` calls `produce` once per field with the type of the
+/// field, so a parser producing all the field types can read the struct directly:
+///
+/// ```ignore
+/// #[derive(SerializeToArrow)]
+/// struct User {
+///     id: i64,
+///     name: String,
+///     age: Option<i32>,
+/// }
+///
+/// let user = User::produce_row(&mut parser)?;
+/// ```
+#[proc_macro_derive(SerializeToArrow)]
+pub fn derive_serialize_to_arrow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "SerializeToArrow does not support generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "SerializeToArrow can only be derived for structs",
+            ))
+        }
+    };
+
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            name,
+            "SerializeToArrow requires at least one field",
+        ));
+    }
+
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let values = types.iter().map(|ty| {
+        quote! {
+            <__P as ::connectorx::sources::Produce<'_, #ty>>::produce(&mut *parser)?
+        }
+    });
+    let body = match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|f| &f.ident);
+            quote! { #name { #(#idents: #values,)* } }
+        }
+        _ => quote! { #name ( #(#values,)* ) },
+    };
+
+    Ok(quote! {
+        impl<__P, __E> ::connectorx::sources::ProduceRow<__P> for #name
+        where
+            #(__P: for<'__r> ::connectorx::sources::Produce<'__r, #types, Error = __E>,)*
+        {
+            type Error = __E;
+
+            fn produce_row(parser: &mut __P) -> ::std::result::Result<Self, __E> {
+                ::std::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+/// Derive `connectorx::destinations::arrow::FromArrow` for a struct with named fields, reading
+/// each field from the column of the same name. Every field type must implement
+/// `connectorx::destinations::arrow::FromArrowValue`, use `Option<T>` for nullable columns.
+///
+/// * `#[arrow(rename = "col_name")]` reads the field from the column `col_name` instead.
+/// * `#[arrow(default)]` fills the field with `Default::default()` if the column is absent.
+///
+/// ```ignore
+/// #[derive(FromArrow)]
+/// struct User {
+///     id: i64,
+///     #[arrow(rename = "user_name")]
+///     name: String,
+///     #[arrow(default)]
+///     age: Option<i32>,
+/// }
+///
+/// let users = User::from_record_batch(&batch)?;
+/// ```
+#[proc_macro_derive(FromArrow, attributes(arrow))]
+pub fn derive_from_arrow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_from_arrow(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_from_arrow(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "FromArrow does not support generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "FromArrow requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "FromArrow can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut columns = vec![];
+    let mut values = vec![];
+    for (i, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().expect("named field");
+        let (rename, default) = arrow_attrs(&field.attrs)?;
+        let col_name = rename.unwrap_or_else(|| ident.to_string());
+        let col = quote::format_ident!("__col{}", i);
+        if default {
+            columns.push(quote! {
+                let #col = ::connectorx::destinations::arrow::from_arrow::optional_column(batch, #col_name);
+            });
+            values.push(quote! {
+                #ident: match &#col {
+                    ::std::option::Option::Some(col) => {
+                        ::connectorx::destinations::arrow::FromArrowValue::from_arrow(col.as_ref(), row)?
+                    }
+                    ::std::option::Option::None => ::std::default::Default::default(),
+                }
+            });
+        } else {
+            columns.push(quote! {
+                let #col = ::connectorx::destinations::arrow::from_arrow::column(batch, #col_name)?;
+            });
+            values.push(quote! {
+                #ident: ::connectorx::destinations::arrow::FromArrowValue::from_arrow(#col.as_ref(), row)?
+            });
+        }
+    }
+
+    Ok(quote! {
+        impl ::connectorx::destinations::arrow::FromArrow for #name {
+            fn from_record_batch(
+                batch: &::connectorx::destinations::arrow::from_arrow::RecordBatch,
+            ) -> ::connectorx::destinations::arrow::Result<::std::vec::Vec<Self>> {
+                #(#columns)*
+                (0..batch.num_rows())
+                    .map(|row| ::std::result::Result::Ok(#name { #(#values,)* }))
+                    .collect()
+            }
+        }
+    })
+}
+
+/// Parse the `#[arrow(...)]` attributes of a field into its column rename and default flag.
+fn arrow_attrs(attrs: &[syn::Attribute]) -> syn::Result<(Option<String>, bool)> {
+    let mut rename = None;
+    let mut default = false;
+    for attr in attrs.iter().filter(|a| a.path.is_ident("arrow")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new_spanned(meta, "expected #[arrow(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                    match nv.lit {
+                        Lit::Str(s) => rename = Some(s.value()),
+                        lit => return Err(Error::new_spanned(lit, "rename expects a string")),
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => default = true,
+                other => return Err(Error::new_spanned(other, "unknown arrow attribute")),
+            }
+        }
+    }
+    Ok((rename, default))
+}
@@ -4,7 +4,7 @@ import pandas as pd
 import pytest
 from pandas.testing import assert_frame_equal
 
-from .. import read_sql
+from .. import count, read_sql
 
 
 @pytest.fixture(scope="module")  # type: ignore
@@ -37,6 +37,12 @@ def postgres_sslkey() -> str:
     return key
 
 
+def test_count(postgres_url: str) -> None:
+    assert count(postgres_url, "SELECT * FROM test_table") == 6
+    assert count(postgres_url, "SELECT * FROM test_table WHERE test_int > 1 LIMIT 2") == 2
+    assert count(postgres_url, "SELECT * FROM test_table WHERE test_int > 1000 LIMIT 10") == 1
+
+
 @pytest.mark.xfail
 def test_on_non_select(postgres_url: str) -> None:
     query = "CREATE TABLE non_select(id INTEGER NOT NULL)"
@@ -12,6 +12,7 @@ mod source_router;
 
 use crate::constants::J4RS_BASE_PATH;
 use connectorx::fed_dispatcher::run;
+use connectorx::sources::oracle::OracleSource;
 use pyo3::prelude::*;
 use pyo3::{wrap_pyfunction, PyResult};
 use std::collections::HashMap;
@@ -40,6 +41,8 @@ fn connectorx(_: Python, m: &PyModule) -> PyResult<()> {
     m.add_wrapped(wrap_pyfunction!(read_sql2))?;
     m.add_wrapped(wrap_pyfunction!(partition_sql))?;
     m.add_wrapped(wrap_pyfunction!(get_meta))?;
+    m.add_wrapped(wrap_pyfunction!(test_connection))?;
+    m.add_wrapped(wrap_pyfunction!(count))?;
     m.add_class::<pandas::PandasBlockInfo>()?;
     Ok(())
 }
@@ -97,3 +100,18 @@ pub fn get_meta<'a>(
     pandas::get_meta::get_meta(py, conn, protocol.unwrap_or("binary"), query)
         .map_err(|e| From::from(e))
 }
+
+/// The round trip time of a query on a new connection to the Oracle database `conn`, in seconds.
+#[pyfunction]
+pub fn test_connection(conn: &str) -> PyResult<f64> {
+    let elapsed =
+        OracleSource::test_connection(conn).map_err(errors::ConnectorXPythonError::from)?;
+    Ok(elapsed.as_secs_f64())
+}
+
+/// The number of rows of `query` on `conn`, from a `COUNT(*)` of it.
+#[pyfunction]
+pub fn count(conn: &str, query: &str) -> PyResult<u64> {
+    let source_conn = source_router::SourceConn::try_from(conn)?;
+    Ok(source_conn.count(query)?)
+}
@@ -48,6 +48,7 @@ pub struct PandasDestination<'py> {
     names: Vec<String>,
     block_datas: Vec<&'py PyAny>, // either 2d array for normal blocks, or two 1d arrays for extension blocks
     block_infos: Vec<PandasBlockInfo>,
+    rows: Arc<AtomicUsize>,
 }
 
 impl<'a> PandasDestination<'a> {
@@ -59,6 +60,7 @@ impl<'a> PandasDestination<'a> {
             names: vec![],
             block_datas: vec![],
             block_infos: vec![],
+            rows: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -304,6 +306,7 @@ impl<'a> Destination for PandasDestination<'a> {
                 columns,
                 &self.schema[..],
                 Arc::clone(&glob_row),
+                Arc::clone(&self.rows),
             ));
         }
 
@@ -313,6 +316,10 @@ impl<'a> Destination for PandasDestination<'a> {
     fn schema(&self) -> &[Self::TypeSystem] {
         self.schema.as_ref()
     }
+
+    fn row_count(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
 }
 pub struct PandasPartitionDestination<'a> {
     columns: Vec<Box<dyn PandasColumnObject + 'a>>,
@@ -320,6 +327,8 @@ pub struct PandasPartitionDestination<'a> {
     seq: usize,
     glob_row: Arc<AtomicUsize>,
     cur_row: usize,
+    /// The rows written by all the partitions of the destination
+    rows: Arc<AtomicUsize>,
 }
 
 impl<'a> PandasPartitionDestination<'a> {
@@ -327,6 +336,7 @@ impl<'a> PandasPartitionDestination<'a> {
         columns: Vec<Box<dyn PandasColumnObject + 'a>>,
         schema: &'a [PandasTypeSystem],
         glob_row: Arc<AtomicUsize>,
+        rows: Arc<AtomicUsize>,
     ) -> Self {
         Self {
             columns,
@@ -334,6 +344,7 @@ impl<'a> PandasPartitionDestination<'a> {
             seq: 0,
             glob_row,
             cur_row: 0,
+            rows,
         }
     }
 
@@ -388,6 +399,10 @@ where
 
         let (column, _): (&mut T::PandasColumn<'a>, *const ()) =
             unsafe { transmute(&*self.columns[col]) };
-        column.write(value, row)
+        column.write(value, row)?;
+        if col + 1 == self.ncols() {
+            self.rows.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
     }
 }
@@ -30,6 +30,7 @@ impl_transport!(
         { Date[NaiveDate]            => DateTime[DateTime<Utc>] | conversion option }
         { Timestamp[NaiveDateTime]   => DateTime[DateTime<Utc>] | conversion option }
         { TimestampTz[DateTime<Utc>] => DateTime[DateTime<Utc>] | conversion auto }
+        { Decimal[(i128, i8)]        => F64[f64]                | conversion option }
     }
 );
 
@@ -44,3 +45,12 @@ impl<'py> TypeConversion<NaiveDateTime, DateTime<Utc>> for OraclePandasTransport
         DateTime::from_utc(val, Utc)
     }
 }
+
+impl<'py> TypeConversion<(i128, i8), f64> for OraclePandasTransport<'py> {
+    fn convert((mantissa, scale): (i128, i8)) -> f64 {
+        // parsed rather than divided, to round the value once
+        let text = format!("{}E{}", mantissa, -(scale as i32));
+        text.parse()
+            .unwrap_or_else(|_| panic!("cannot convert decimal {} to float64", text))
+    }
+}
@@ -1,6 +1,10 @@
 // Unfortunately, due to the orphan rule, typesystem implementation should be in this crate.
 use chrono::{DateTime, Utc};
+use connectorx::errors::ConnectorXError;
 use connectorx::impl_typesystem;
+use connectorx::typesystem::{parse_type, TypeName};
+use fehler::{throw, throws};
+use std::convert::TryFrom;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PandasTypeSystem {
@@ -84,6 +88,49 @@ impl_typesystem! {
     }
 }
 
+impl std::fmt::Display for PandasTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use PandasTypeSystem::*;
+        let (name, nullable) = match *self {
+            F64(n) => ("float64", n),
+            I64(n) => ("int64", n),
+            F64Array(n) => ("float64[]", n),
+            I64Array(n) => ("int64[]", n),
+            Bool(n) => ("bool", n),
+            Char(n) => ("char", n),
+            Str(n) | BoxStr(n) | String(n) => ("str", n),
+            Bytes(n) | ByteSlice(n) => ("bytes", n),
+            DateTime(n) => ("datetime64[ns]", n),
+        };
+        connectorx::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of a pandas type as displayed by [`PandasTypeSystem`], e.g. `int64(nullable)`.
+/// The strings and bytes are parsed into their owned variants.
+impl<'a> TryFrom<&'a str> for PandasTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use PandasTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "FLOAT64" => F64,
+            "INT64" => I64,
+            "FLOAT64[]" => F64Array,
+            "INT64[]" => I64Array,
+            "BOOL" => Bool,
+            "CHAR" => Char,
+            "STR" => String,
+            "BYTES" => Bytes,
+            "DATETIME64[NS]" => DateTime,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
+
 pub trait PandasDType: Sized {
     // For initialize a pandas array when creating the pandas dataframe
     fn is_masked(&self) -> bool;
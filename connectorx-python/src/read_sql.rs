@@ -1,5 +1,5 @@
 use crate::source_router::SourceConn;
-use connectorx::sql::CXQuery;
+use connectorx::sql::{null_partition, CXQuery};
 use dict_derive::FromPyObject;
 use fehler::throw;
 use pyo3::prelude::*;
@@ -39,6 +39,7 @@ pub fn partition(part: &PartitionQuery, source_conn: &SourceConn) -> PyResult<Ve
     };
 
     let partition_size = (max - min + 1) / num;
+    let nulls = null_partition(None, part.num) as i64;
 
     for i in 0..num {
         let lower = min + i * partition_size;
@@ -47,7 +48,7 @@ pub fn partition(part: &PartitionQuery, source_conn: &SourceConn) -> PyResult<Ve
             false => min + (i + 1) * partition_size,
         };
         let partition_query =
-            source_conn.get_part_query(&part.query, &part.column, lower, upper)?;
+            source_conn.get_part_query(&part.query, &part.column, lower, upper, i == nulls)?;
         queries.push(partition_query);
     }
     Ok(queries)
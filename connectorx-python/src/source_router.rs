@@ -1,26 +1,36 @@
 use crate::errors::{ConnectorXPythonError, Result};
 use anyhow::anyhow;
 use connectorx::{
+    prelude::Source,
     sources::{
-        bigquery::BigQueryDialect,
-        mssql::{mssql_config, FloatN, IntN, MsSQLTypeSystem},
-        mysql::{MySQLSourceError, MySQLTypeSystem},
-        oracle::{connect_oracle, OracleDialect},
-        postgres::{rewrite_tls_args, PostgresTypeSystem},
+        bigquery::{BigQueryDialect, BigQuerySource},
+        mssql::{mssql_config, FloatN, IntN, MsSQLSource, MsSQLTypeSystem},
+        mysql::{
+            BinaryProtocol as MySQLBinaryProtocol, MySQLSource, MySQLSourceError, MySQLTypeSystem,
+        },
+        oracle::{connect_oracle, OracleDialect, OracleSource},
+        postgres::{
+            rewrite_tls_args, BinaryProtocol as PgBinaryProtocol, PostgresSource,
+            PostgresTypeSystem,
+        },
+        sqlite::SQLiteSource,
     },
     sql::{
         get_partition_range_query, get_partition_range_query_sep, single_col_partition_query,
-        CXQuery,
+        single_col_partition_query_oracle, CXQuery,
     },
 };
 use fehler::{throw, throws};
 use gcp_bigquery_client;
+use postgres::NoTls;
+use postgres_openssl::MakeTlsConnector;
 use r2d2_mysql::mysql::{prelude::Queryable, Opts, Pool, Row};
 use rusqlite::{types::Type, Connection};
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
 use sqlparser::dialect::{MsSqlDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
 use std::convert::TryFrom;
+use std::sync::Arc;
 use tiberius::Client;
 use tokio::net::TcpStream;
 use tokio::runtime::Runtime;
@@ -90,6 +100,43 @@ impl SourceConn {
         }
     }
 
+    /// The number of rows of `query`, see [`Source::count`].
+    #[throws(ConnectorXPythonError)]
+    pub fn count(&self, query: &str) -> u64 {
+        match self.ty {
+            SourceType::Postgres => {
+                let (config, tls) = rewrite_tls_args(&self.conn)?;
+                match tls {
+                    Some(tls_conn) => PostgresSource::<PgBinaryProtocol, MakeTlsConnector>::new(
+                        config, tls_conn, 1,
+                    )?
+                    .count(query)?,
+                    None => PostgresSource::<PgBinaryProtocol, NoTls>::new(config, NoTls, 1)?
+                        .count(query)?,
+                }
+            }
+            SourceType::SQLite => {
+                // remove the first "sqlite://" manually since url.path is not correct for windows
+                SQLiteSource::new(&self.conn.as_str()[9..], 1)?.count(query)?
+            }
+            SourceType::MySQL => {
+                MySQLSource::<MySQLBinaryProtocol>::new(&self.conn[..], 1)?.count(query)?
+            }
+            SourceType::MsSQL => {
+                let rt = Arc::new(Runtime::new().expect("Failed to create runtime"));
+                MsSQLSource::new(rt, &self.conn[..], 1)?.count(query)?
+            }
+            SourceType::Oracle => OracleSource::new(&self.conn[..], 1)?.count(query)?,
+            SourceType::BigQuery => {
+                let rt = Arc::new(Runtime::new().expect("Failed to create runtime"));
+                BigQuerySource::new(rt, &self.conn[..])?.count(query)?
+            }
+        }
+    }
+
+    /// The partition of `query` whose `col` is in `[lower, upper)`. On Oracle, it also takes the
+    /// rows whose `col` is `NULL` in if `nulls`, see [`connectorx::sql::null_partition`]; the
+    /// other sources do not read them.
     #[throws(ConnectorXPythonError)]
     pub fn get_part_query(
         &self,
@@ -97,6 +144,7 @@ impl SourceConn {
         col: &str,
         lower: i64,
         upper: i64,
+        nulls: bool,
     ) -> CXQuery<String> {
         let query = match self.ty {
             SourceType::Postgres => {
@@ -112,7 +160,7 @@ impl SourceConn {
                 single_col_partition_query(query, col, lower, upper, &MsSqlDialect {})?
             }
             SourceType::Oracle => {
-                single_col_partition_query(query, col, lower, upper, &OracleDialect {})?
+                single_col_partition_query_oracle(query, col, lower, upper, nulls)
             }
             SourceType::BigQuery => {
                 single_col_partition_query(query, col, lower, upper, &BigQueryDialect {})?
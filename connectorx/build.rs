@@ -0,0 +1,12 @@
+// Remind the users of the `unstable` feature that its APIs may break, there is no
+// `compile_warning!`. Unlike a lint, the warning of a build script does not fail the builds
+// denying the warnings.
+
+fn main() {
+    if std::env::var_os("CARGO_FEATURE_UNSTABLE").is_some() {
+        println!(
+            "cargo:warning=the `unstable` feature of connectorx is enabled: \
+             its APIs may break between minor versions"
+        );
+    }
+}
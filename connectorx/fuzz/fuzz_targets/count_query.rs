@@ -0,0 +1,51 @@
+#![no_main]
+use connectorx::sql::{count_query, CXQuery};
+use connectorx_fuzz::single_query;
+use libfuzzer_sys::fuzz_target;
+use sqlparser::ast::{SelectItem, SetExpr, TableFactor};
+use sqlparser::dialect::{GenericDialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect};
+
+fn check<D: sqlparser::dialect::Dialect>(sql: &str, dialect: &D) {
+    // an error is a fine answer to a query the rewrite does not support, a panic is not
+    let counted = match count_query(&CXQuery::naked(sql), dialect) {
+        Ok(counted) => counted,
+        Err(_) => return,
+    };
+    if single_query(sql, dialect).is_none() {
+        return;
+    }
+    let query = single_query(counted.as_str(), dialect)
+        .unwrap_or_else(|| panic!("count query of {:?} does not parse: {}", sql, counted));
+    let select = match &query.body {
+        SetExpr::Select(select) => select,
+        _ => panic!("count query of {:?} is not a select: {}", sql, counted),
+    };
+    let count = match &select.projection[..] {
+        [SelectItem::UnnamedExpr(e)] => e.to_string(),
+        _ => String::new(),
+    };
+    assert!(
+        count.eq_ignore_ascii_case("count(*)"),
+        "count query of {:?} does not select count(*): {}",
+        sql,
+        counted
+    );
+    assert!(
+        matches!(
+            &select.from[..],
+            [from] if from.joins.is_empty() && matches!(from.relation, TableFactor::Derived { .. })
+        ),
+        "count query of {:?} is not over a subquery: {}",
+        sql,
+        counted
+    );
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(sql) = std::str::from_utf8(data) {
+        check(sql, &GenericDialect {});
+        check(sql, &PostgreSqlDialect {});
+        check(sql, &MySqlDialect {});
+        check(sql, &MsSqlDialect {});
+    }
+});
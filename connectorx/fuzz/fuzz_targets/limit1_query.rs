@@ -0,0 +1,34 @@
+#![no_main]
+use connectorx::sql::{limit1_query, CXQuery};
+use connectorx_fuzz::single_query;
+use libfuzzer_sys::fuzz_target;
+use sqlparser::ast::{Expr, Value};
+use sqlparser::dialect::{GenericDialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect};
+
+fn check<D: sqlparser::dialect::Dialect>(sql: &str, dialect: &D) {
+    let limited = match limit1_query(&CXQuery::naked(sql), dialect) {
+        Ok(limited) => limited,
+        Err(_) => return,
+    };
+    if single_query(sql, dialect).is_none() {
+        return;
+    }
+    let query = single_query(limited.as_str(), dialect)
+        .unwrap_or_else(|| panic!("limit query of {:?} does not parse: {}", sql, limited));
+    assert_eq!(
+        Some(Expr::Value(Value::Number("1".to_string(), false))),
+        query.limit,
+        "limit query of {:?} is not limited to 1 row: {}",
+        sql,
+        limited
+    );
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(sql) = std::str::from_utf8(data) {
+        check(sql, &GenericDialect {});
+        check(sql, &PostgreSqlDialect {});
+        check(sql, &MySqlDialect {});
+        check(sql, &MsSqlDialect {});
+    }
+});
@@ -0,0 +1,32 @@
+#![no_main]
+use connectorx::sources::oracle::OracleDialect;
+use connectorx::sql::{limit1_query_oracle, CXQuery};
+use connectorx_fuzz::single_query;
+use libfuzzer_sys::fuzz_target;
+use sqlparser::ast::SetExpr;
+
+fuzz_target!(|data: &[u8]| {
+    let sql = match std::str::from_utf8(data) {
+        Ok(sql) => sql,
+        Err(_) => return,
+    };
+    let limited = match limit1_query_oracle(&CXQuery::naked(sql)) {
+        Ok(limited) => limited,
+        Err(_) => return,
+    };
+    if single_query(sql, &OracleDialect {}).is_none() {
+        return;
+    }
+    let query = single_query(limited.as_str(), &OracleDialect {})
+        .unwrap_or_else(|| panic!("limit query of {:?} does not parse: {}", sql, limited));
+    let filter = match &query.body {
+        SetExpr::Select(select) => select.selection.as_ref(),
+        _ => None,
+    };
+    assert!(
+        filter.map_or(false, |f| f.to_string().eq_ignore_ascii_case("rownum <= 1")),
+        "limit query of {:?} is not limited to 1 row: {}",
+        sql,
+        limited
+    );
+});
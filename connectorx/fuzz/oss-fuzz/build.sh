@@ -0,0 +1,8 @@
+#!/bin/bash -eu
+# Build the fuzz targets of the SQL rewrites (see connectorx/fuzz) for OSS-Fuzz.
+
+cd $SRC/connector-x/connectorx
+cargo fuzz build -O
+for target in $(cargo fuzz list); do
+    cp fuzz/target/x86_64-unknown-linux-gnu/release/$target $OUT/
+done
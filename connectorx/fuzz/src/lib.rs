@@ -0,0 +1,15 @@
+//! Helpers shared by the fuzz targets of the SQL rewrites, see `fuzz_targets/`.
+
+use sqlparser::ast::{Query, Statement};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::Parser;
+
+/// The query of `sql` if it is made of a single query statement. The rewrites only promise a valid
+/// output for those, the others are passed through as they are for the database to reject.
+pub fn single_query<D: Dialect>(sql: &str, dialect: &D) -> Option<Query> {
+    let mut ast = Parser::parse_sql(dialect, sql).ok()?;
+    match (ast.pop(), ast.is_empty()) {
+        (Some(Statement::Query(query)), true) => Some(*query),
+        _ => None,
+    }
+}
@@ -7,6 +7,14 @@ const KILO: usize = 1 << 10;
 #[cfg(any(feature = "dst_arrow", feature = "dst_arrow2"))]
 pub const RECORD_BATCH_SIZE: usize = 64 * KILO;
 
+/// The precision and the scale of the Arrow `Decimal128` columns, whatever the scale of the source
+/// column: the values are rescaled to it, so a column of a scale up to `DECIMAL_SCALE` and of up
+/// to `DECIMAL_PRECISION - DECIMAL_SCALE` digits before the decimal point fits.
+#[cfg(any(feature = "dst_arrow", feature = "src_oracle"))]
+pub const DECIMAL_PRECISION: usize = 38;
+#[cfg(any(feature = "dst_arrow", feature = "src_oracle"))]
+pub const DECIMAL_SCALE: usize = 10;
+
 #[cfg(any(
     feature = "src_postgres",
     feature = "src_mysql",
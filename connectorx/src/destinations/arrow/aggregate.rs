@@ -0,0 +1,266 @@
+//! Post-fetch aggregation over the record batches produced by [`super::ArrowDestination`].
+
+use super::{ArrowDestinationError, Result};
+use anyhow::anyhow;
+use arrow::{
+    array::{
+        as_primitive_array, new_null_array, Array, ArrayRef, Float64Array, Int64Array, StringArray,
+        UInt32Array, UInt64Array,
+    },
+    compute::{self, cast, concat, sort_to_indices, take, SortOptions},
+    datatypes::{DataType, Field, Float64Type, Int64Type, Schema, UInt64Type},
+    record_batch::RecordBatch,
+};
+use fehler::{throw, throws};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// An aggregation to apply on a fetched `RecordBatch`.
+#[derive(Debug, Clone)]
+pub enum Agg<'a> {
+    /// In `Int64`, `UInt64` or `Float64`, after the signedness of the column.
+    Sum {
+        col: &'a str,
+    },
+    Mean {
+        col: &'a str,
+    },
+    /// In the type of the column, of any type that can be sorted (numbers, strings, dates...).
+    Min {
+        col: &'a str,
+    },
+    /// In the type of the column, like `Min`.
+    Max {
+        col: &'a str,
+    },
+    Count {
+        col: &'a str,
+    },
+    CountDistinct {
+        col: &'a str,
+    },
+    GroupBy {
+        keys: Vec<&'a str>,
+        aggs: Vec<Agg<'a>>,
+    },
+}
+
+impl<'a> Agg<'a> {
+    #[throws(ArrowDestinationError)]
+    fn field(&self, schema: &Schema) -> Field {
+        let col_type = |col: &str| -> Result<DataType> {
+            Ok(schema.field_with_name(col)?.data_type().clone())
+        };
+        match self {
+            Agg::Sum { col } => {
+                Field::new(&format!("sum({})", col), sum_type(&col_type(col)?)?, true)
+            }
+            Agg::Mean { col } => Field::new(&format!("mean({})", col), DataType::Float64, true),
+            Agg::Min { col } => Field::new(&format!("min({})", col), col_type(col)?, true),
+            Agg::Max { col } => Field::new(&format!("max({})", col), col_type(col)?, true),
+            Agg::Count { col } => Field::new(&format!("count({})", col), DataType::Int64, false),
+            Agg::CountDistinct { col } => {
+                Field::new(&format!("count_distinct({})", col), DataType::Int64, false)
+            }
+            Agg::GroupBy { .. } => throw!(anyhow!("GroupBy cannot be nested in an aggregation")),
+        }
+    }
+}
+
+/// Aggregate `batch` using `aggs`. Without a `GroupBy` the result has exactly one row with one
+/// column per aggregation. With a `GroupBy` (at most one), the result has one row per group,
+/// with the key columns followed by the aggregations of each group: the other aggregations of
+/// `aggs`, with the nested ones of the `GroupBy` in its place.
+#[throws(ArrowDestinationError)]
+pub fn aggregate(batch: &RecordBatch, aggs: &[Agg]) -> RecordBatch {
+    let mut group_keys = None;
+    let mut flat = vec![];
+    for agg in aggs {
+        match agg {
+            Agg::GroupBy { .. } if group_keys.is_some() => {
+                throw!(anyhow!("at most one GroupBy can be applied"))
+            }
+            Agg::GroupBy { keys, aggs } => {
+                group_keys = Some(keys);
+                flat.extend(aggs.iter().cloned());
+            }
+            agg => flat.push(agg.clone()),
+        }
+    }
+    match group_keys {
+        Some(keys) => group_by(batch, keys, &flat)?,
+        None => {
+            let schema = batch.schema();
+            let mut fields = vec![];
+            let mut columns = vec![];
+            for agg in &flat {
+                fields.push(agg.field(&schema)?);
+                columns.push(evaluate(batch, agg)?);
+            }
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?
+        }
+    }
+}
+
+#[throws(ArrowDestinationError)]
+fn column<'b>(batch: &'b RecordBatch, col: &str) -> &'b ArrayRef {
+    batch.column(batch.schema().index_of(col)?)
+}
+
+/// The type the values of a column of type `ty` are summed in, wide enough not to lose the
+/// precision of an integer column.
+#[throws(ArrowDestinationError)]
+fn sum_type(ty: &DataType) -> DataType {
+    match ty {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => DataType::Int64,
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            DataType::UInt64
+        }
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => DataType::Float64,
+        _ => throw!(anyhow!("cannot sum a column of type {:?}", ty)),
+    }
+}
+
+#[throws(ArrowDestinationError)]
+fn sum(array: &ArrayRef) -> ArrayRef {
+    let ty = sum_type(array.data_type())?;
+    let casted = cast(array, &ty)?;
+    let result: ArrayRef = match ty {
+        DataType::Int64 => Arc::new(Int64Array::from(vec![compute::sum(as_primitive_array::<
+            Int64Type,
+        >(&casted))])),
+        DataType::UInt64 => Arc::new(UInt64Array::from(vec![compute::sum(as_primitive_array::<
+            UInt64Type,
+        >(&casted))])),
+        _ => Arc::new(Float64Array::from(vec![compute::sum(
+            as_primitive_array::<Float64Type>(&casted),
+        )])),
+    };
+    result
+}
+
+/// The smallest (or largest if `max`) non-null value of `array`, in its own type.
+#[throws(ArrowDestinationError)]
+fn extreme(array: &ArrayRef, max: bool) -> ArrayRef {
+    if array.null_count() == array.len() {
+        return new_null_array(array.data_type(), 1);
+    }
+    let options = SortOptions {
+        descending: max,
+        nulls_first: false,
+    };
+    let indices = sort_to_indices(array, Some(options), Some(1))?;
+    take(array.as_ref(), &indices, None)?
+}
+
+#[throws(ArrowDestinationError)]
+fn evaluate(batch: &RecordBatch, agg: &Agg) -> ArrayRef {
+    let result: ArrayRef = match agg {
+        Agg::Sum { col } => sum(column(batch, col)?)?,
+        Agg::Mean { col } => {
+            let array = column(batch, col)?;
+            let valid = array.len() - array.null_count();
+            let total = cast(&sum(array)?, &DataType::Float64)?;
+            let total = as_primitive_array::<Float64Type>(&total);
+            let mean = Some(total)
+                .filter(|_| valid > 0 && total.is_valid(0))
+                .map(|total| total.value(0) / valid as f64);
+            Arc::new(Float64Array::from(vec![mean]))
+        }
+        Agg::Min { col } => extreme(column(batch, col)?, false)?,
+        Agg::Max { col } => extreme(column(batch, col)?, true)?,
+        Agg::Count { col } => {
+            let array = column(batch, col)?;
+            Arc::new(Int64Array::from(vec![
+                (array.len() - array.null_count()) as i64,
+            ]))
+        }
+        Agg::CountDistinct { col } => {
+            let casted = cast(column(batch, col)?, &DataType::Utf8)?;
+            let values = casted
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| anyhow!("cannot cast column {} to Utf8", col))?;
+            let distinct: HashSet<&str> = values.iter().flatten().collect();
+            Arc::new(Int64Array::from(vec![distinct.len() as i64]))
+        }
+        Agg::GroupBy { .. } => throw!(anyhow!("GroupBy cannot be nested in an aggregation")),
+    };
+    result
+}
+
+#[throws(ArrowDestinationError)]
+fn group_by(batch: &RecordBatch, keys: &[&str], aggs: &[Agg]) -> RecordBatch {
+    let key_columns = keys
+        .iter()
+        .map(|k| Ok(cast(column(batch, k)?, &DataType::Utf8)?))
+        .collect::<Result<Vec<_>>>()?;
+    let key_values = key_columns
+        .iter()
+        .map(|c| {
+            c.as_any().downcast_ref::<StringArray>().ok_or_else(|| {
+                ArrowDestinationError::from(anyhow!("cannot cast group key to Utf8"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // group row indices by key, keeping groups in the order they first appear
+    let mut group_ids: HashMap<Vec<Option<&str>>, usize> = HashMap::new();
+    let mut groups: Vec<Vec<u32>> = vec![];
+    for row in 0..batch.num_rows() {
+        let key: Vec<Option<&str>> = key_values
+            .iter()
+            .map(|a| {
+                if a.is_null(row) {
+                    None
+                } else {
+                    Some(a.value(row))
+                }
+            })
+            .collect();
+        let gid = *group_ids.entry(key).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[gid].push(row as u32);
+    }
+
+    let schema = batch.schema();
+    let mut fields = vec![];
+    let mut columns = vec![];
+
+    let first_rows = UInt32Array::from(groups.iter().map(|g| g[0]).collect::<Vec<_>>());
+    for key in keys {
+        let idx = schema.index_of(key)?;
+        fields.push(schema.field(idx).clone());
+        columns.push(take(batch.column(idx).as_ref(), &first_rows, None)?);
+    }
+
+    let mut partials: Vec<Vec<ArrayRef>> = vec![vec![]; aggs.len()];
+    for rows in groups {
+        let indices = UInt32Array::from(rows);
+        let group = RecordBatch::try_new(
+            schema.clone(),
+            batch
+                .columns()
+                .iter()
+                .map(|c| take(c.as_ref(), &indices, None))
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+        )?;
+        for (i, agg) in aggs.iter().enumerate() {
+            partials[i].push(evaluate(&group, agg)?);
+        }
+    }
+
+    for (agg, partial) in aggs.iter().zip(partials) {
+        let field = agg.field(&schema)?;
+        columns.push(if partial.is_empty() {
+            arrow::array::new_empty_array(field.data_type())
+        } else {
+            concat(&partial.iter().map(|a| a.as_ref()).collect::<Vec<_>>())?
+        });
+        fields.push(field);
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?
+}
@@ -1,9 +1,10 @@
 use super::errors::{ArrowDestinationError, Result};
-use crate::constants::SECONDS_IN_DAY;
+use crate::constants::{DECIMAL_PRECISION, DECIMAL_SCALE, SECONDS_IN_DAY};
+use anyhow::anyhow;
 use arrow::array::{
-    ArrayBuilder, BooleanBuilder, Date32Builder, Date64Builder, Float32Builder, Float64Builder,
-    Int32Builder, Int64Builder, LargeBinaryBuilder, StringBuilder, Time64NanosecondBuilder,
-    UInt32Builder, UInt64Builder,
+    ArrayBuilder, BooleanBuilder, Date32Builder, Date64Builder, DecimalBuilder, Float32Builder,
+    Float64Builder, Int32Builder, Int64Builder, LargeBinaryBuilder, StringBuilder,
+    Time64NanosecondBuilder, UInt32Builder, UInt64Builder,
 };
 use arrow::datatypes::Field;
 use arrow::datatypes::{DataType as ArrowDataType, TimeUnit};
@@ -353,3 +354,69 @@ impl ArrowAssoc for Vec<u8> {
         Field::new(header, ArrowDataType::LargeBinary, false)
     }
 }
+
+/// Rescale the mantissa of a decimal of scale `scale` to [`DECIMAL_SCALE`], e.g. `(425, 1)` to
+/// `42500000000`. Dropping a non-zero digit, or overflowing, is an error.
+#[throws(ArrowDestinationError)]
+fn rescale_decimal((mantissa, scale): (i128, i8)) -> i128 {
+    let diff = DECIMAL_SCALE as i32 - scale as i32;
+    let rescaled = match 10i128.checked_pow(diff.unsigned_abs()) {
+        Some(factor) if diff >= 0 => mantissa.checked_mul(factor),
+        Some(factor) if mantissa % factor == 0 => Some(mantissa / factor),
+        _ => None,
+    };
+    rescaled.ok_or_else(|| {
+        anyhow!(
+            "{}E{} does not fit in a Decimal({}, {})",
+            mantissa,
+            -(scale as i32),
+            DECIMAL_PRECISION,
+            DECIMAL_SCALE
+        )
+    })?
+}
+
+impl ArrowAssoc for (i128, i8) {
+    type Builder = DecimalBuilder;
+
+    fn builder(nrows: usize) -> Self::Builder {
+        DecimalBuilder::new(nrows, DECIMAL_PRECISION, DECIMAL_SCALE)
+    }
+
+    #[throws(ArrowDestinationError)]
+    fn append(builder: &mut Self::Builder, value: Self) {
+        builder.append_value(rescale_decimal(value)?)?;
+    }
+
+    fn field(header: &str) -> Field {
+        Field::new(
+            header,
+            ArrowDataType::Decimal(DECIMAL_PRECISION, DECIMAL_SCALE),
+            false,
+        )
+    }
+}
+
+impl ArrowAssoc for Option<(i128, i8)> {
+    type Builder = DecimalBuilder;
+
+    fn builder(nrows: usize) -> Self::Builder {
+        DecimalBuilder::new(nrows, DECIMAL_PRECISION, DECIMAL_SCALE)
+    }
+
+    #[throws(ArrowDestinationError)]
+    fn append(builder: &mut Self::Builder, value: Self) {
+        match value {
+            Some(v) => builder.append_value(rescale_decimal(v)?)?,
+            None => builder.append_null()?,
+        }
+    }
+
+    fn field(header: &str) -> Field {
+        Field::new(
+            header,
+            ArrowDataType::Decimal(DECIMAL_PRECISION, DECIMAL_SCALE),
+            true,
+        )
+    }
+}
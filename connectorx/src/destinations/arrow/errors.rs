@@ -7,6 +7,9 @@ pub enum ArrowDestinationError {
     #[error(transparent)]
     ArrowError(#[from] arrow::error::ArrowError),
 
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
     #[error(transparent)]
     ConnectorXError(#[from] crate::errors::ConnectorXError),
 
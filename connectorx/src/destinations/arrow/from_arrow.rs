@@ -0,0 +1,135 @@
+//! Converting record batches back into row structs, usually through `#[derive(FromArrow)]`
+//! (feature `derive`).
+
+use super::{ArrowDestinationError, Result};
+use anyhow::anyhow;
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Date32Array, Date64Array, Float32Array, Float64Array,
+    Int32Array, Int64Array, LargeBinaryArray, LargeStringArray, StringArray, Time64NanosecondArray,
+    UInt32Array, UInt64Array,
+};
+pub use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use fehler::{throw, throws};
+
+/// A row type that can be read from the rows of a record batch.
+pub trait FromArrow: Sized {
+    fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Self>>;
+}
+
+/// A value that can be read from a single slot of an Arrow array.
+pub trait FromArrowValue: Sized {
+    fn from_arrow(array: &dyn Array, row: usize) -> Result<Self>;
+}
+
+/// Get the column `name` of `batch`.
+#[throws(ArrowDestinationError)]
+pub fn column(batch: &RecordBatch, name: &str) -> ArrayRef {
+    match optional_column(batch, name) {
+        Some(col) => col,
+        None => throw!(anyhow!("column {} is not in the record batch", name)),
+    }
+}
+
+/// Get the column `name` of `batch` if it exists.
+pub fn optional_column(batch: &RecordBatch, name: &str) -> Option<ArrayRef> {
+    batch
+        .schema()
+        .column_with_name(name)
+        .map(|(idx, _)| batch.column(idx).clone())
+}
+
+#[throws(ArrowDestinationError)]
+fn downcast<A: 'static>(array: &dyn Array) -> &A {
+    array.as_any().downcast_ref::<A>().ok_or_else(|| {
+        anyhow!(
+            "cannot read {:?} array as {}",
+            array.data_type(),
+            std::any::type_name::<A>()
+        )
+    })?
+}
+
+macro_rules! impl_from_arrow_value {
+    ($($t: ty => $A: ty),+) => {
+        $(
+            impl FromArrowValue for $t {
+                #[throws(ArrowDestinationError)]
+                fn from_arrow(array: &dyn Array, row: usize) -> $t {
+                    if array.is_null(row) {
+                        throw!(anyhow!("unexpected null value at row {}", row));
+                    }
+                    downcast::<$A>(array)?.value(row)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_arrow_value!(
+    i32 => Int32Array,
+    i64 => Int64Array,
+    u32 => UInt32Array,
+    u64 => UInt64Array,
+    f32 => Float32Array,
+    f64 => Float64Array,
+    bool => BooleanArray
+);
+
+impl FromArrowValue for String {
+    #[throws(ArrowDestinationError)]
+    fn from_arrow(array: &dyn Array, row: usize) -> String {
+        if array.is_null(row) {
+            throw!(anyhow!("unexpected null value at row {}", row));
+        }
+        match array.as_any().downcast_ref::<StringArray>() {
+            Some(array) => array.value(row).to_string(),
+            None => downcast::<LargeStringArray>(array)?.value(row).to_string(),
+        }
+    }
+}
+
+impl FromArrowValue for Vec<u8> {
+    #[throws(ArrowDestinationError)]
+    fn from_arrow(array: &dyn Array, row: usize) -> Vec<u8> {
+        if array.is_null(row) {
+            throw!(anyhow!("unexpected null value at row {}", row));
+        }
+        downcast::<LargeBinaryArray>(array)?.value(row).to_vec()
+    }
+}
+
+macro_rules! impl_from_arrow_temporal {
+    ($($t: ty => $A: ty, $f: ident),+) => {
+        $(
+            impl FromArrowValue for $t {
+                #[throws(ArrowDestinationError)]
+                fn from_arrow(array: &dyn Array, row: usize) -> $t {
+                    if array.is_null(row) {
+                        throw!(anyhow!("unexpected null value at row {}", row));
+                    }
+                    downcast::<$A>(array)?
+                        .$f(row)
+                        .ok_or_else(|| anyhow!("value at row {} is out of range", row))?
+                }
+            }
+        )+
+    };
+}
+
+impl_from_arrow_temporal!(
+    NaiveDate => Date32Array, value_as_date,
+    NaiveDateTime => Date64Array, value_as_datetime,
+    NaiveTime => Time64NanosecondArray, value_as_time
+);
+
+impl<T: FromArrowValue> FromArrowValue for Option<T> {
+    #[throws(ArrowDestinationError)]
+    fn from_arrow(array: &dyn Array, row: usize) -> Option<T> {
+        if array.is_null(row) {
+            None
+        } else {
+            Some(T::from_arrow(array, row)?)
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! A destination writing the fetched record batches as an Arrow IPC stream into a memory-mapped file,
+//! so that result sets larger than RAM can be produced and then opened zero-copy (e.g. `pyarrow.memory_map`).
+
+use super::{ArrowDestination, ArrowDestinationError, Sink};
+use anyhow::anyhow;
+use arrow::{datatypes::Schema, ipc::writer::StreamWriter, record_batch::RecordBatch};
+use fehler::{throw, throws};
+use memmap2::MmapMut;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tempfile::{NamedTempFile, TempPath};
+
+/// Initial size of the mapped file, it is doubled whenever it runs out of space.
+const INITIAL_MMAP_SIZE: usize = 1 << 20;
+
+/// A `Write` over a memory-mapped file. The file is grown with `File::set_len`
+/// (`ftruncate` on unix, `SetEndOfFile` on windows) and remapped as data arrives.
+pub struct MmapFile {
+    file: File,
+    mmap: Option<MmapMut>,
+    len: usize,
+}
+
+impl MmapFile {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self {
+            file,
+            mmap: None,
+            len: 0,
+        })
+    }
+
+    fn reserve(&mut self, additional: usize) -> io::Result<()> {
+        let capacity = self.mmap.as_ref().map(|m| m.len()).unwrap_or(0);
+        if self.len + additional <= capacity {
+            return Ok(());
+        }
+        let capacity = (capacity * 2)
+            .max(self.len + additional)
+            .max(INITIAL_MMAP_SIZE);
+        // unmap before resizing the file
+        self.mmap = None;
+        self.file.set_len(capacity as u64)?;
+        self.mmap = Some(unsafe { MmapMut::map_mut(&self.file)? });
+        Ok(())
+    }
+
+    /// Flush the mapped pages and cut the file to the written length.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(mmap) = self.mmap.take() {
+            mmap.flush()?;
+        }
+        self.file.set_len(self.len as u64)?;
+        self.file.sync_all()
+    }
+}
+
+impl Write for MmapFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.reserve(buf.len())?;
+        if let Some(mmap) = self.mmap.as_mut() {
+            mmap[self.len..self.len + buf.len()].copy_from_slice(buf);
+        }
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &self.mmap {
+            Some(mmap) => mmap.flush_async(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The IPC stream writer shared by the partition writers of an [`ArrowDestination`].
+pub struct MmapSink {
+    file: Option<MmapFile>,
+    writer: Option<StreamWriter<MmapFile>>,
+}
+
+impl MmapSink {
+    fn new(file: MmapFile) -> Self {
+        Self {
+            file: Some(file),
+            writer: None,
+        }
+    }
+
+    /// Write the stream header, called once the schema of the result is known.
+    #[throws(ArrowDestinationError)]
+    pub fn start(&mut self, schema: &Schema) {
+        let file = self
+            .file
+            .take()
+            .ok_or_else(|| anyhow!("mmap destination is already started"))?;
+        self.writer = Some(StreamWriter::try_new(file, schema)?);
+    }
+
+    #[throws(ArrowDestinationError)]
+    pub fn write(&mut self, rb: &RecordBatch) {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| anyhow!("mmap destination is not started"))?
+            .write(rb)?;
+    }
+
+    #[throws(ArrowDestinationError)]
+    fn finish(&mut self) {
+        let mut writer = self
+            .writer
+            .take()
+            .ok_or_else(|| anyhow!("mmap destination is not started"))?;
+        writer.finish()?;
+        writer.into_inner()?.finish()?;
+    }
+}
+
+/// The outcome of a [`MmapDestination`]: an Arrow IPC stream file and the schema of its batches.
+#[derive(Debug, Clone)]
+pub struct MmapArrowResult {
+    pub path: PathBuf,
+    pub schema: Schema,
+}
+
+/// A destination writing the record batches sequentially into a memory-mapped Arrow IPC stream file
+/// instead of keeping them in memory. Pass [`MmapDestination::destination`] to the dispatcher, then
+/// call [`MmapDestination::finish`].
+pub struct MmapDestination {
+    inner: ArrowDestination,
+    path: PathBuf,
+}
+
+impl MmapDestination {
+    #[throws(ArrowDestinationError)]
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let sink = MmapSink::new(MmapFile::create(&path)?);
+        Self {
+            inner: ArrowDestination {
+                sink: Sink::Mmap(Arc::new(Mutex::new(sink))),
+                ..ArrowDestination::default()
+            },
+            path,
+        }
+    }
+
+    /// The underlying `ArrowDestination` to hand to the dispatcher.
+    pub fn destination(&mut self) -> &mut ArrowDestination {
+        &mut self.inner
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Finish the IPC stream and truncate the file to its final size.
+    #[throws(ArrowDestinationError)]
+    pub fn finish(self) -> MmapArrowResult {
+        let schema = (*self.inner.arrow_schema()).clone();
+        let sink = match self.inner.sink {
+            Sink::Mmap(sink) => sink,
+            _ => throw!(anyhow!("destination is not created with mmap")),
+        };
+        let sink = Arc::try_unwrap(sink).map_err(|_| anyhow!("Partitions are not freed"))?;
+        sink.into_inner()
+            .map_err(|e| anyhow!("mutex poisoned {}", e))?
+            .finish()?;
+        MmapArrowResult {
+            path: self.path,
+            schema,
+        }
+    }
+}
+
+/// A [`MmapDestination`] backed by a file in the system temp directory, which is deleted when the
+/// `TempFileDestination` is dropped. Use [`TempFileDestination::keep`] to move the file to a
+/// permanent path instead.
+pub struct TempFileDestination {
+    inner: Option<MmapDestination>,
+    result: Option<MmapArrowResult>,
+    path: TempPath,
+}
+
+impl TempFileDestination {
+    #[throws(ArrowDestinationError)]
+    pub fn new() -> Self {
+        let path = NamedTempFile::new()?.into_temp_path();
+        Self {
+            inner: Some(MmapDestination::new(&path)?),
+            result: None,
+            path,
+        }
+    }
+
+    /// The underlying `ArrowDestination` to hand to the dispatcher.
+    #[throws(ArrowDestinationError)]
+    pub fn destination(&mut self) -> &mut ArrowDestination {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| anyhow!("temp file destination is already finished"))?
+            .destination()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Finish the IPC stream. The file stays valid until the destination is dropped.
+    #[throws(ArrowDestinationError)]
+    pub fn finish(&mut self) -> MmapArrowResult {
+        if let Some(inner) = self.inner.take() {
+            self.result = Some(inner.finish()?);
+        }
+        self.result
+            .clone()
+            .ok_or_else(|| anyhow!("temp file destination is not finished"))?
+    }
+
+    /// Finish the IPC stream if needed and move the file to `path`, which will not be deleted.
+    /// Moving across file systems is not supported.
+    #[throws(ArrowDestinationError)]
+    pub fn keep<P: AsRef<Path>>(mut self, path: P) -> MmapArrowResult {
+        let mut result = self.finish()?;
+        self.path.persist(path.as_ref()).map_err(|e| e.error)?;
+        result.path = path.as_ref().to_path_buf();
+        result
+    }
+}
@@ -1,11 +1,19 @@
 //! Destination implementation for Arrow and Polars.
 
+pub mod aggregate;
 mod arrow_assoc;
 mod errors;
+pub mod from_arrow;
 mod funcs;
+pub mod mmap;
+pub mod stream;
 pub mod typesystem;
 
+pub use self::aggregate::{aggregate, Agg};
 pub use self::errors::{ArrowDestinationError, Result};
+pub use self::from_arrow::{FromArrow, FromArrowValue};
+pub use self::mmap::{MmapArrowResult, MmapDestination, TempFileDestination};
+pub use self::stream::ConnectorXArrowStream;
 pub use self::typesystem::ArrowTypeSystem;
 use super::{Consume, Destination, DestinationPartition};
 use crate::constants::RECORD_BATCH_SIZE;
@@ -17,19 +25,49 @@ use arrow_assoc::ArrowAssoc;
 use fehler::{throw, throws};
 use funcs::{FFinishBuilder, FNewBuilder, FNewField};
 use itertools::Itertools;
+use mmap::MmapSink;
 use std::{
     any::Any,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
 };
 
 type Builder = Box<dyn Any + Send>;
 type Builders = Vec<Builder>;
 
+/// The record batches of one partition, sent by a streaming destination as soon as the
+/// partition is finished. See [`ArrowDestination::new_streaming`].
+pub struct PartitionBatches {
+    pub partition: usize,
+    pub batches: Vec<RecordBatch>,
+}
+
+/// Where the partition writers put the record batches they fill.
+#[derive(Clone)]
+enum Sink {
+    /// Kept until all the partitions are done, see [`ArrowDestination::arrow`].
+    Memory(Arc<Mutex<Vec<RecordBatch>>>),
+    /// Written to a memory-mapped file, see [`MmapDestination`].
+    Mmap(Arc<Mutex<MmapSink>>),
+    /// Sent by partition, see [`ArrowDestination::new_streaming`].
+    Stream(Sender<PartitionBatches>),
+}
+
+impl Default for Sink {
+    fn default() -> Self {
+        Sink::Memory(Arc::new(Mutex::new(vec![])))
+    }
+}
+
 pub struct ArrowDestination {
     schema: Vec<ArrowTypeSystem>,
     names: Vec<String>,
-    data: Arc<Mutex<Vec<RecordBatch>>>,
+    sink: Sink,
     arrow_schema: Arc<Schema>,
+    rows: Arc<AtomicUsize>,
 }
 
 impl Default for ArrowDestination {
@@ -37,8 +75,9 @@ impl Default for ArrowDestination {
         ArrowDestination {
             schema: vec![],
             names: vec![],
-            data: Arc::new(Mutex::new(vec![])),
+            sink: Sink::default(),
             arrow_schema: Arc::new(Schema::empty()),
+            rows: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -47,6 +86,19 @@ impl ArrowDestination {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Create a destination that sends the batches of each partition to the returned receiver as
+    /// soon as the partition is finished, instead of keeping them until all the partitions are
+    /// done. Partitions arrive in the order they finish, and the receiver is disconnected once all
+    /// of them are sent. Run the dispatcher on another thread to consume them while it runs.
+    pub fn new_streaming() -> (Self, Receiver<PartitionBatches>) {
+        let (tx, rx) = channel();
+        let destination = ArrowDestination {
+            sink: Sink::Stream(tx),
+            ..Self::default()
+        };
+        (destination, rx)
+    }
 }
 
 impl Destination for ArrowDestination {
@@ -84,17 +136,30 @@ impl Destination for ArrowDestination {
             .map(|(&dt, h)| Ok(Realize::<FNewField>::realize(dt)?(h.as_str())))
             .collect::<Result<Vec<_>>>()?;
         self.arrow_schema = Arc::new(Schema::new(fields));
+        if let Sink::Mmap(mmap) = &self.sink {
+            mmap.lock()
+                .map_err(|e| anyhow!("mutex poisoned {}", e))?
+                .start(&self.arrow_schema)?;
+        }
     }
 
     #[throws(ArrowDestinationError)]
     fn partition(&mut self, counts: usize) -> Vec<Self::Partition<'_>> {
         let mut partitions = vec![];
-        for _ in 0..counts {
-            partitions.push(ArrowPartitionWriter::new(
+        // the partitions hold the only senders, so the receiver disconnects when they are done
+        let sink = match self.sink {
+            Sink::Stream(_) => std::mem::take(&mut self.sink),
+            _ => self.sink.clone(),
+        };
+        for i in 0..counts {
+            let mut partition = ArrowPartitionWriter::new(
                 self.schema.clone(),
-                Arc::clone(&self.data),
+                sink.clone(),
                 Arc::clone(&self.arrow_schema),
-            )?);
+            )?;
+            partition.rows = Arc::clone(&self.rows);
+            partition.partition = i;
+            partitions.push(partition);
         }
         partitions
     }
@@ -102,19 +167,65 @@ impl Destination for ArrowDestination {
     fn schema(&self) -> &[ArrowTypeSystem] {
         self.schema.as_slice()
     }
+
+    fn row_count(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
 }
 
 impl ArrowDestination {
+    /// The record batches kept in memory, none if they were written to a file or streamed.
     #[throws(ArrowDestinationError)]
     pub fn arrow(self) -> Vec<RecordBatch> {
-        let lock = Arc::try_unwrap(self.data).map_err(|_| anyhow!("Partitions are not freed"))?;
-        lock.into_inner()
-            .map_err(|e| anyhow!("mutex poisoned {}", e))?
+        match self.sink {
+            Sink::Memory(data) => {
+                let lock =
+                    Arc::try_unwrap(data).map_err(|_| anyhow!("Partitions are not freed"))?;
+                lock.into_inner()
+                    .map_err(|e| anyhow!("mutex poisoned {}", e))?
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Take the record batches fetched so far, leaving the destination empty.
+    #[throws(ArrowDestinationError)]
+    pub(crate) fn take_batches(&mut self) -> Vec<RecordBatch> {
+        match &self.sink {
+            Sink::Memory(data) => {
+                let mut guard = data.lock().map_err(|e| anyhow!("mutex poisoned {}", e))?;
+                std::mem::take(&mut *guard)
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Get the fetched record batches as a stream, which can be exported through the Arrow
+    /// C Stream Interface.
+    #[throws(ArrowDestinationError)]
+    pub fn arrow_stream(self) -> ConnectorXArrowStream {
+        let schema = self.arrow_schema();
+        ConnectorXArrowStream::new(schema, self.arrow()?)
     }
 
     pub fn arrow_schema(&self) -> Arc<Schema> {
         self.arrow_schema.clone()
     }
+
+    /// The counter of the rows written by the partitions, see [`Destination::row_count`], to
+    /// report the progress of a load from another thread while the dispatcher holds the
+    /// destination.
+    pub fn row_counter(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.rows)
+    }
+
+    /// Concatenate all the fetched batches and aggregate them using `aggs`.
+    #[throws(ArrowDestinationError)]
+    pub fn aggregate(self, aggs: &[Agg]) -> RecordBatch {
+        let schema = self.arrow_schema();
+        let batches = self.arrow()?;
+        aggregate(&RecordBatch::concat(&schema, &batches)?, aggs)?
+    }
 }
 
 pub struct ArrowPartitionWriter {
@@ -122,24 +233,28 @@ pub struct ArrowPartitionWriter {
     builders: Option<Builders>,
     current_row: usize,
     current_col: usize,
-    data: Arc<Mutex<Vec<RecordBatch>>>,
+    sink: Sink,
+    /// The index of the partition, and its batches until it is finished, for a `Sink::Stream`
+    partition: usize,
+    batches: Vec<RecordBatch>,
     arrow_schema: Arc<Schema>,
+    /// The rows written by all the partitions of the destination
+    rows: Arc<AtomicUsize>,
 }
 
 impl ArrowPartitionWriter {
     #[throws(ArrowDestinationError)]
-    fn new(
-        schema: Vec<ArrowTypeSystem>,
-        data: Arc<Mutex<Vec<RecordBatch>>>,
-        arrow_schema: Arc<Schema>,
-    ) -> Self {
+    fn new(schema: Vec<ArrowTypeSystem>, sink: Sink, arrow_schema: Arc<Schema>) -> Self {
         let mut pw = ArrowPartitionWriter {
             schema,
             builders: None,
             current_row: 0,
             current_col: 0,
-            data,
+            sink,
+            partition: 0,
+            batches: vec![],
             arrow_schema,
+            rows: Arc::new(AtomicUsize::new(0)),
         };
         pw.allocate()?;
         pw
@@ -167,13 +282,17 @@ impl ArrowPartitionWriter {
             .map(|(builder, &dt)| Realize::<FFinishBuilder>::realize(dt)?(builder))
             .collect::<std::result::Result<Vec<_>, crate::errors::ConnectorXError>>()?;
         let rb = RecordBatch::try_new(Arc::clone(&self.arrow_schema), columns)?;
-        {
-            let mut guard = self
-                .data
+        match &self.sink {
+            Sink::Memory(data) => {
+                let mut guard = data.lock().map_err(|e| anyhow!("mutex poisoned {}", e))?;
+                let inner_data = &mut *guard;
+                inner_data.push(rb);
+            }
+            Sink::Mmap(mmap) => mmap
                 .lock()
-                .map_err(|e| anyhow!("mutex poisoned {}", e))?;
-            let inner_data = &mut *guard;
-            inner_data.push(rb);
+                .map_err(|e| anyhow!("mutex poisoned {}", e))?
+                .write(&rb)?,
+            Sink::Stream(_) => self.batches.push(rb),
         }
 
         self.current_row = 0;
@@ -190,6 +309,13 @@ impl<'a> DestinationPartition<'a> for ArrowPartitionWriter {
         if self.builders.is_some() {
             self.flush()?;
         }
+        if let Sink::Stream(tx) = &self.sink {
+            // the receiver may be gone if the consumer is no longer interested
+            let _ = tx.send(PartitionBatches {
+                partition: self.partition,
+                batches: std::mem::take(&mut self.batches),
+            });
+        }
     }
 
     #[throws(ArrowDestinationError)]
@@ -229,6 +355,7 @@ where
         // flush if exceed batch_size
         if self.current_col == 0 {
             self.current_row += 1;
+            self.rows.fetch_add(1, Ordering::Relaxed);
             if self.current_row >= RECORD_BATCH_SIZE {
                 self.flush()?;
                 self.allocate()?;
@@ -0,0 +1,51 @@
+//! Export of the fetched record batches through the Arrow C Stream Interface (`ArrowArrayStream`),
+//! so that a consumer such as `pyarrow.RecordBatchReader._import_from_c` can pull the batches one
+//! by one without copying them.
+
+use arrow::{
+    datatypes::SchemaRef,
+    error::Result as ArrowResult,
+    ffi_stream::{export_reader_into_raw, FFI_ArrowArrayStream},
+    record_batch::{RecordBatch, RecordBatchReader},
+};
+use std::vec::IntoIter;
+
+/// A `RecordBatchReader` over the batches of an [`super::ArrowDestination`]. Batches are handed
+/// out (and released by the consumer) one at a time instead of as a whole.
+pub struct ConnectorXArrowStream {
+    schema: SchemaRef,
+    batches: IntoIter<RecordBatch>,
+}
+
+impl ConnectorXArrowStream {
+    pub fn new(schema: SchemaRef, batches: Vec<RecordBatch>) -> Self {
+        Self {
+            schema,
+            batches: batches.into_iter(),
+        }
+    }
+
+    /// Move the stream into `out`, which afterwards owns it and releases it through its
+    /// `release` callback.
+    ///
+    /// # Safety
+    /// `out` must be a valid pointer to an `ArrowArrayStream` struct, e.g. one allocated by
+    /// `FFI_ArrowArrayStream::empty()` or by the consumer.
+    pub unsafe fn export(self, out: *mut FFI_ArrowArrayStream) {
+        export_reader_into_raw(Box::new(self), out)
+    }
+}
+
+impl Iterator for ConnectorXArrowStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.batches.next().map(Ok)
+    }
+}
+
+impl RecordBatchReader for ConnectorXArrowStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
@@ -1,5 +1,9 @@
+use crate::errors::ConnectorXError;
 use crate::impl_typesystem;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use fehler::{throw, throws};
+use std::convert::TryFrom;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ArrowTypeSystem {
@@ -16,6 +20,10 @@ pub enum ArrowTypeSystem {
     Date64(bool),
     Time64(bool),
     DateTimeTz(bool),
+    /// A `Decimal128` of precision [`crate::constants::DECIMAL_PRECISION`] and scale
+    /// [`crate::constants::DECIMAL_SCALE`], produced as the mantissa and the scale of the value,
+    /// which is rescaled.
+    Decimal(bool),
 }
 
 impl_typesystem! {
@@ -34,5 +42,59 @@ impl_typesystem! {
         { Date64     => NaiveDateTime }
         { Time64     => NaiveTime     }
         { DateTimeTz => DateTime<Utc> }
+        { Decimal    => (i128, i8)    }
+    }
+}
+
+impl std::fmt::Display for ArrowTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use ArrowTypeSystem::*;
+        let (name, nullable) = match *self {
+            Int32(n) => ("Int32", n),
+            Int64(n) => ("Int64", n),
+            UInt32(n) => ("UInt32", n),
+            UInt64(n) => ("UInt64", n),
+            Float32(n) => ("Float32", n),
+            Float64(n) => ("Float64", n),
+            Boolean(n) => ("Boolean", n),
+            LargeUtf8(n) => ("LargeUtf8", n),
+            LargeBinary(n) => ("LargeBinary", n),
+            Date32(n) => ("Date32", n),
+            Date64(n) => ("Date64", n),
+            Time64(n) => ("Time64", n),
+            DateTimeTz(n) => ("Timestamp(UTC)", n),
+            Decimal(n) => ("Decimal128", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of an Arrow data type as displayed by [`ArrowTypeSystem`], e.g. `Int64(nullable)`, the
+/// strings and binaries being large ones whatever their offsets.
+impl<'a> TryFrom<&'a str> for ArrowTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use ArrowTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "INT32" => Int32,
+            "INT64" => Int64,
+            "UINT32" => UInt32,
+            "UINT64" => UInt64,
+            "FLOAT32" => Float32,
+            "FLOAT64" => Float64,
+            "BOOLEAN" => Boolean,
+            "LARGEUTF8" | "UTF8" => LargeUtf8,
+            "LARGEBINARY" | "BINARY" => LargeBinary,
+            "DATE32" => Date32,
+            "DATE64" => Date64,
+            "TIME64" => Time64,
+            "TIMESTAMP" => DateTimeTz,
+            "DECIMAL128" | "DECIMAL" => Decimal,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
     }
 }
@@ -22,7 +22,10 @@ use fehler::throws;
 use funcs::{FFinishBuilder, FNewBuilder, FNewField};
 use polars::prelude::{ArrowField, DataFrame, PolarsError, Series};
 use std::convert::TryFrom;
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
 pub use typesystem::Arrow2TypeSystem;
 
 type Builder = Box<dyn MutableArray + 'static + Send>;
@@ -33,6 +36,7 @@ pub struct Arrow2Destination {
     names: Vec<String>,
     data: Arc<Mutex<Vec<Chunk<Arc<dyn Array>>>>>,
     arrow_schema: Arc<Schema>,
+    rows: Arc<AtomicUsize>,
 }
 
 impl Default for Arrow2Destination {
@@ -42,6 +46,7 @@ impl Default for Arrow2Destination {
             names: vec![],
             data: Arc::new(Mutex::new(vec![])),
             arrow_schema: Arc::new(Schema::default()),
+            rows: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -96,6 +101,7 @@ impl Destination for Arrow2Destination {
             partitions.push(ArrowPartitionWriter::new(
                 self.schema.clone(),
                 Arc::clone(&self.data),
+                Arc::clone(&self.rows),
             )?);
         }
         partitions
@@ -104,6 +110,10 @@ impl Destination for Arrow2Destination {
     fn schema(&self) -> &[Arrow2TypeSystem] {
         self.schema.as_slice()
     }
+
+    fn row_count(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
 }
 
 impl Arrow2Destination {
@@ -167,17 +177,24 @@ pub struct ArrowPartitionWriter {
     current_row: usize,
     current_col: usize,
     data: Arc<Mutex<Vec<Chunk<Arc<dyn Array>>>>>,
+    /// The rows written by all the partitions of the destination
+    rows: Arc<AtomicUsize>,
 }
 
 impl ArrowPartitionWriter {
     #[throws(Arrow2DestinationError)]
-    fn new(schema: Vec<Arrow2TypeSystem>, data: Arc<Mutex<Vec<Chunk<Arc<dyn Array>>>>>) -> Self {
+    fn new(
+        schema: Vec<Arrow2TypeSystem>,
+        data: Arc<Mutex<Vec<Chunk<Arc<dyn Array>>>>>,
+        rows: Arc<AtomicUsize>,
+    ) -> Self {
         let mut pw = ArrowPartitionWriter {
             schema,
             builders: None,
             current_row: 0,
             current_col: 0,
             data,
+            rows,
         };
         pw.allocate()?;
         pw
@@ -267,6 +284,7 @@ where
         // flush if exceed batch_size
         if self.current_col == 0 {
             self.current_row += 1;
+            self.rows.fetch_add(1, Ordering::Relaxed);
             if self.current_row >= RECORD_BATCH_SIZE {
                 self.flush()?;
                 self.allocate()?;
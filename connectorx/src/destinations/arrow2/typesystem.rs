@@ -1,5 +1,9 @@
+use crate::errors::ConnectorXError;
 use crate::impl_typesystem;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use fehler::{throw, throws};
+use std::convert::TryFrom;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Arrow2TypeSystem {
@@ -36,3 +40,54 @@ impl_typesystem! {
         { DateTimeTz => DateTime<Utc> }
     }
 }
+
+impl std::fmt::Display for Arrow2TypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Arrow2TypeSystem::*;
+        let (name, nullable) = match *self {
+            Int32(n) => ("Int32", n),
+            Int64(n) => ("Int64", n),
+            UInt32(n) => ("UInt32", n),
+            UInt64(n) => ("UInt64", n),
+            Float32(n) => ("Float32", n),
+            Float64(n) => ("Float64", n),
+            Boolean(n) => ("Boolean", n),
+            LargeUtf8(n) => ("LargeUtf8", n),
+            LargeBinary(n) => ("LargeBinary", n),
+            Date32(n) => ("Date32", n),
+            Date64(n) => ("Date64", n),
+            Time64(n) => ("Time64", n),
+            DateTimeTz(n) => ("Timestamp(UTC)", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of an Arrow data type as displayed by [`Arrow2TypeSystem`], e.g. `Int64(nullable)`, the
+/// strings and binaries being large ones whatever their offsets.
+impl<'a> TryFrom<&'a str> for Arrow2TypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use Arrow2TypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "INT32" => Int32,
+            "INT64" => Int64,
+            "UINT32" => UInt32,
+            "UINT64" => UInt64,
+            "FLOAT32" => Float32,
+            "FLOAT64" => Float64,
+            "BOOLEAN" => Boolean,
+            "LARGEUTF8" | "UTF8" => LargeUtf8,
+            "LARGEBINARY" | "BINARY" => LargeBinary,
+            "DATE32" => Date32,
+            "DATE64" => Date64,
+            "TIME64" => Time64,
+            "TIMESTAMP" => DateTimeTz,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
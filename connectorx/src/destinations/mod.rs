@@ -7,6 +7,9 @@ pub mod arrow;
 #[cfg(feature = "dst_arrow2")]
 pub mod arrow2;
 
+pub mod tee;
+pub mod text_buffer;
+
 use crate::data_order::DataOrder;
 use crate::errors::ConnectorXError;
 use crate::typesystem::{TypeAssoc, TypeSystem};
@@ -23,6 +26,7 @@ pub trait Destination: Sized {
 
     /// Specify whether the destination needs total rows in advance
     /// in order to pre-allocate the buffer.
+    #[must_use]
     fn needs_count(&self) -> bool;
 
     /// Construct the `Destination`.
@@ -39,7 +43,14 @@ pub trait Destination: Sized {
     /// Create a bunch of partition destinations, with each write `count` number of rows.
     fn partition(&mut self, counts: usize) -> Result<Vec<Self::Partition<'_>>, Self::Error>;
     /// Return the schema of the destination.
+    #[must_use]
     fn schema(&self) -> &[Self::TypeSystem];
+
+    /// The number of rows written so far by the partitions. Once the load is done, it is the sum
+    /// of the [`crate::sources::SourcePartition::nrows`] of the source partitions, when those are
+    /// exact.
+    #[must_use]
+    fn row_count(&self) -> usize;
 }
 
 /// `PartitionDestination` writes values to its own region. `PartitionDestination` is parameterized
@@ -60,6 +71,7 @@ pub trait DestinationPartition<'a>: Send {
     }
 
     /// Number of rows this `PartitionDestination` controls.
+    #[must_use]
     fn ncols(&self) -> usize;
 
     /// Final clean ups
@@ -0,0 +1,132 @@
+//! Destination writing the same data into two destinations, e.g. to archive a query result while
+//! loading it into a dataframe without reading it from the source twice.
+
+use super::{Consume, Destination, DestinationPartition};
+use crate::data_order::DataOrder;
+use crate::errors::ConnectorXError;
+use crate::typesystem::TypeSystem;
+use fehler::{throw, throws};
+
+/// Write each value produced by the source into both `D1` and `D2`, which share a type system:
+/// the source partitions are read once, and the value is cloned for `D2`. Use it as the
+/// destination of a [`crate::prelude::Dispatcher`], with a transport routed to `TeeDestination`.
+/// Nest a `TeeDestination` as `D2` to fan out to more than two destinations.
+///
+/// The rows are only counted if either destination needs them, and the data order must be
+/// supported by both: the order of `D1` is the one negotiated with the source.
+pub struct TeeDestination<D1, D2> {
+    first: D1,
+    second: D2,
+}
+
+impl<D1, D2> TeeDestination<D1, D2> {
+    pub fn new(first: D1, second: D2) -> Self {
+        TeeDestination { first, second }
+    }
+
+    /// The destinations, to take the data out of them once loaded.
+    pub fn into_inner(self) -> (D1, D2) {
+        (self.first, self.second)
+    }
+}
+
+impl<TS, D1, D2> Destination for TeeDestination<D1, D2>
+where
+    TS: TypeSystem,
+    D1: Destination<TypeSystem = TS>,
+    D2: Destination<TypeSystem = TS>,
+    D1::Error: From<D2::Error>,
+{
+    const DATA_ORDERS: &'static [DataOrder] = D1::DATA_ORDERS;
+    type TypeSystem = TS;
+    type Partition<'a> = TeePartition<D1::Partition<'a>, D2::Partition<'a>> where Self: 'a;
+    type Error = D1::Error;
+
+    fn needs_count(&self) -> bool {
+        self.first.needs_count() || self.second.needs_count()
+    }
+
+    #[throws(D1::Error)]
+    fn allocate<S: AsRef<str>>(
+        &mut self,
+        nrow: usize,
+        names: &[S],
+        schema: &[TS],
+        data_order: DataOrder,
+    ) {
+        if !D2::DATA_ORDERS.contains(&data_order) {
+            throw!(ConnectorXError::UnsupportedDataOrder(data_order));
+        }
+        self.first.allocate(nrow, names, schema, data_order)?;
+        self.second.allocate(nrow, names, schema, data_order)?;
+    }
+
+    #[throws(D1::Error)]
+    fn partition(&mut self, counts: usize) -> Vec<Self::Partition<'_>> {
+        let firsts = self.first.partition(counts)?;
+        let seconds = self.second.partition(counts)?;
+        firsts
+            .into_iter()
+            .zip(seconds)
+            .map(|(first, second)| TeePartition { first, second })
+            .collect()
+    }
+
+    fn schema(&self) -> &[TS] {
+        self.first.schema()
+    }
+
+    fn row_count(&self) -> usize {
+        self.first.row_count()
+    }
+}
+
+/// A partition of [`TeeDestination`], writing into a partition of each destination.
+pub struct TeePartition<P1, P2> {
+    first: P1,
+    second: P2,
+}
+
+impl<'a, TS, P1, P2> DestinationPartition<'a> for TeePartition<P1, P2>
+where
+    TS: TypeSystem,
+    P1: DestinationPartition<'a, TypeSystem = TS>,
+    P2: DestinationPartition<'a, TypeSystem = TS>,
+    P1::Error: From<P2::Error>,
+{
+    type TypeSystem = TS;
+    type Error = P1::Error;
+
+    fn ncols(&self) -> usize {
+        self.first.ncols()
+    }
+
+    #[throws(P1::Error)]
+    fn finalize(&mut self) {
+        self.first.finalize()?;
+        self.second.finalize()?;
+    }
+
+    #[throws(P1::Error)]
+    fn aquire_row(&mut self, n: usize) -> usize {
+        let row = self.first.aquire_row(n)?;
+        self.second.aquire_row(n)?;
+        row
+    }
+}
+
+impl<T, P1, P2> Consume<T> for TeePartition<P1, P2>
+where
+    T: Clone,
+    P1: Consume<T>,
+    P2: Consume<T>,
+    P1::Error: From<P2::Error>,
+{
+    type Error = P1::Error;
+
+    #[throws(P1::Error)]
+    fn consume(&mut self, value: T) {
+        self.second.consume(value.clone())?;
+        self.first.consume(value)?;
+    }
+}
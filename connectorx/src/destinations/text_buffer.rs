@@ -0,0 +1,132 @@
+//! A string column accumulated in one contiguous buffer, in the layout of an Arrow `LargeUtf8`
+//! array, for the destinations which want the values of a column back to back instead of a
+//! `String` per value.
+
+use crate::sources::Produce;
+use fehler::throws;
+
+/// The values of a string column: their bytes back to back in `values`, value `i` being
+/// `values[offsets[i]..offsets[i + 1]]`, and bit `i` of `validity` (least significant bit first)
+/// set if value `i` is not null. A null takes no bytes.
+///
+/// The buffers grow with the values, so filling a column takes a few reallocations instead of an
+/// allocation per value. They can be handed to Arrow as they are, the offsets being those of a
+/// `LargeUtf8` array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextBuffer {
+    values: Vec<u8>,
+    offsets: Vec<i64>,
+    validity: Vec<u8>,
+    null_count: usize,
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    /// A buffer with room for `len` values of `bytes` bytes in total.
+    pub fn with_capacity(len: usize, bytes: usize) -> Self {
+        let mut offsets = Vec::with_capacity(len + 1);
+        offsets.push(0);
+        TextBuffer {
+            values: Vec::with_capacity(bytes),
+            offsets,
+            validity: Vec::with_capacity((len + 7) / 8),
+            null_count: 0,
+        }
+    }
+
+    pub fn push(&mut self, value: Option<&str>) {
+        let i = self.len();
+        if i % 8 == 0 {
+            self.validity.push(0);
+        }
+        match value {
+            Some(s) => {
+                self.values.extend_from_slice(s.as_bytes());
+                self.validity[i / 8] |= 1 << (i % 8);
+            }
+            None => self.null_count += 1,
+        }
+        self.offsets.push(self.values.len() as i64);
+    }
+
+    /// Produce the next value of the column from `parser` and copy it into the buffer, so that the
+    /// parser can hand out a borrowed `&str`.
+    #[throws(P::Error)]
+    pub fn produce<'r, P>(&mut self, parser: &'r mut P)
+    where
+        P: Produce<'r, Option<&'r str>>,
+    {
+        let value = parser.produce()?;
+        self.push(value);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[must_use]
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Value `i`, `None` if it is null.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is out of bounds.
+    #[must_use]
+    pub fn get(&self, i: usize) -> Option<&str> {
+        assert!(i < self.len(), "index {} out of {} values", i, self.len());
+        if self.validity[i / 8] & (1 << (i % 8)) == 0 {
+            return None;
+        }
+        let bytes = &self.values[self.offsets[i] as usize..self.offsets[i + 1] as usize];
+        // the bytes between two offsets were pushed as a whole `str`
+        Some(std::str::from_utf8(bytes).expect("text buffer value is not UTF-8"))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Option<&str>> + '_ {
+        (0..self.len()).map(move |i| self.get(i))
+    }
+
+    /// The bytes of the values.
+    #[must_use]
+    pub fn values(&self) -> &[u8] {
+        &self.values
+    }
+
+    /// The `len() + 1` offsets of the values in [`TextBuffer::values`], starting at 0.
+    #[must_use]
+    pub fn offsets(&self) -> &[i64] {
+        &self.offsets
+    }
+
+    /// The validity bitmap, a bit per value, least significant bit first.
+    #[must_use]
+    pub fn validity(&self) -> &[u8] {
+        &self.validity
+    }
+
+    /// Remove all the values, keeping the allocated buffers to fill the next batch.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.offsets.truncate(1);
+        self.validity.clear();
+        self.null_count = 0;
+    }
+}
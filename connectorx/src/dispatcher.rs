@@ -1,3 +1,5 @@
+#[cfg(feature = "dst_arrow")]
+use crate::destinations::arrow::{ArrowDestination, ArrowDestinationError, ArrowTypeSystem};
 ///! This module provides [`dispatcher::Dispatcher`], the core struct in ConnectorX
 ///! that drives the data loading from a source to a destination.
 use crate::{
@@ -11,7 +13,68 @@ use crate::{
 use itertools::Itertools;
 use log::debug;
 use rayon::prelude::*;
+use std::cell::Cell;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+thread_local! {
+    static CURRENT_WORKER: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// The worker running the partition on the current thread, when the partitions are assigned
+/// to the workers ahead of the load (see [`PartitionAssignment`]), e.g. to tag the log lines of
+/// a source or a transport. `None` outside of such a load.
+pub fn current_worker() -> Option<usize> {
+    CURRENT_WORKER.with(Cell::get)
+}
+
+/// Which worker loads which partition. A worker loads its partitions one after the other, in
+/// the order of their indices, on a thread of the rayon pool, and the workers run in parallel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartitionAssignment {
+    /// Any idle thread picks the next partition (the default). The fastest when the partitions
+    /// are uneven, but which thread loads which partition changes from a load to the next.
+    Dynamic,
+    /// Partition `i` is loaded by worker `i % workers`.
+    RoundRobin(usize),
+    /// Partition `i` is loaded by worker `workers[i]`, one entry per partition.
+    Pinned(Vec<usize>),
+}
+
+impl Default for PartitionAssignment {
+    fn default() -> Self {
+        PartitionAssignment::Dynamic
+    }
+}
+
+impl PartitionAssignment {
+    /// The partitions of each worker, in the order they are loaded, or `None` if the partitions
+    /// are assigned while loading.
+    pub fn workers(&self, npartitions: usize) -> CXResult<Option<Vec<Vec<usize>>>> {
+        let worker_of: Vec<usize> = match self {
+            PartitionAssignment::Dynamic => return Ok(None),
+            PartitionAssignment::RoundRobin(0) => {
+                return Err(anyhow::anyhow!("round-robin assignment to no worker").into())
+            }
+            PartitionAssignment::RoundRobin(n) => (0..npartitions).map(|i| i % n).collect(),
+            PartitionAssignment::Pinned(workers) if workers.len() != npartitions => {
+                return Err(anyhow::anyhow!(
+                    "{} partitions pinned, the load has {}",
+                    workers.len(),
+                    npartitions
+                )
+                .into())
+            }
+            PartitionAssignment::Pinned(workers) => workers.clone(),
+        };
+        let nworkers = worker_of.iter().max().map_or(0, |w| w + 1);
+        let mut workers = vec![vec![]; nworkers];
+        for (i, w) in worker_of.into_iter().enumerate() {
+            workers[w].push(i);
+        }
+        Ok(Some(workers))
+    }
+}
 
 /// A dispatcher takes a `S: Source`, a `D: Destination`, a `TP: Transport` and a vector of `queries` as input to
 /// load data from `S` to `D` using the queries.
@@ -20,6 +83,7 @@ pub struct Dispatcher<'a, S, D, TP> {
     dst: &'a mut D,
     queries: Vec<CXQuery<String>>,
     origin_query: Option<String>,
+    assignment: PartitionAssignment,
     _phantom: PhantomData<TP>,
 }
 
@@ -46,12 +110,26 @@ where
             dst,
             queries: queries.iter().map(Into::into).collect(),
             origin_query,
+            assignment: PartitionAssignment::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// Assign the partitions to the workers ahead of the load, so that a partition is loaded by
+    /// the same worker on every load of the same queries, see [`current_worker`].
+    pub fn set_partition_assignment(&mut self, assignment: PartitionAssignment) {
+        self.assignment = assignment;
+    }
+
     /// Start the data loading process.
-    pub fn run(mut self) -> Result<(), ET> {
+    pub fn run(self) -> Result<(), ET> {
+        self.dispatch(false).map(|_| ())
+    }
+
+    /// Load the data, either stopping at the first failed partition or, if `fault_tolerant`,
+    /// loading all the partitions and returning the errors of the failed ones along with the
+    /// number of partitions the source produced.
+    fn dispatch(mut self, fault_tolerant: bool) -> Result<(usize, Vec<(usize, ET)>), ET> {
         let dorder = coordinate(S::DATA_ORDERS, D::DATA_ORDERS)?;
         self.src.set_data_order(dorder)?;
         self.src.set_queries(self.queries.as_slice());
@@ -98,8 +176,10 @@ where
         );
         self.dst.allocate(total_rows, &names, &dst_schema, dorder)?;
 
+        // the source may split a query into several partitions (e.g. with partition bounds)
         debug!("Create destination partition");
-        let dst_partitions = self.dst.partition(self.queries.len())?;
+        let npartitions = src_partitions.len();
+        let dst_partitions = self.dst.partition(npartitions)?;
 
         #[cfg(all(not(feature = "branch"), not(feature = "fptr")))]
         compile_error!("branch or fptr, pick one");
@@ -113,11 +193,8 @@ where
 
         debug!("Start writing");
         // parse and write
-        dst_partitions
-            .into_par_iter()
-            .zip_eq(src_partitions)
-            .enumerate()
-            .try_for_each(|(i, (mut dst, mut src))| -> Result<(), ET> {
+        let write =
+            |i: usize, mut dst: D::Partition<'_>, mut src: S::Partition| -> Result<(), ET> {
                 #[cfg(feature = "fptr")]
                 let f: Vec<_> = src_schema
                     .iter()
@@ -173,11 +250,74 @@ where
                 dst.finalize()?;
                 debug!("Partition {} finished", i);
                 Ok(())
-            })?;
+            };
+
+        let failed = match self.assignment.workers(dst_partitions.len())? {
+            None => {
+                let partitions = dst_partitions
+                    .into_par_iter()
+                    .zip_eq(src_partitions)
+                    .enumerate();
+                if fault_tolerant {
+                    partitions
+                        .filter_map(|(i, (dst, src))| write(i, dst, src).err().map(|e| (i, e)))
+                        .collect()
+                } else {
+                    partitions.try_for_each(|(i, (dst, src))| write(i, dst, src))?;
+                    vec![]
+                }
+            }
+            Some(workers) => {
+                let mut partitions: Vec<_> = dst_partitions
+                    .into_iter()
+                    .zip_eq(src_partitions)
+                    .map(Some)
+                    .collect();
+                let workers: Vec<Vec<_>> = workers
+                    .into_iter()
+                    .map(|indices| {
+                        indices
+                            .into_iter()
+                            .map(|i| (i, partitions[i].take().unwrap()))
+                            .collect()
+                    })
+                    .collect();
+                // without fault tolerance, the other workers stop after their current partition
+                let aborted = AtomicBool::new(false);
+                let mut failed: Vec<(usize, ET)> = workers
+                    .into_par_iter()
+                    .enumerate()
+                    .map(|(w, partitions)| {
+                        CURRENT_WORKER.with(|worker| worker.set(Some(w)));
+                        let mut failed = vec![];
+                        for (i, (dst, src)) in partitions {
+                            if !fault_tolerant && aborted.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            debug!("Partition {} on worker {}", i, w);
+                            if let Err(e) = write(i, dst, src) {
+                                failed.push((i, e));
+                                aborted.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        CURRENT_WORKER.with(|worker| worker.set(None));
+                        failed
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                failed.sort_by_key(|&(i, _)| i);
+                if !fault_tolerant && !failed.is_empty() {
+                    return Err(failed.remove(0).1);
+                }
+                failed
+            }
+        };
 
         debug!("Writing finished");
 
-        Ok(())
+        Ok((npartitions, failed))
     }
 
     /// Only fetch the metadata (header) of the destination.
@@ -197,3 +337,112 @@ where
         Ok(())
     }
 }
+
+/// A dispatcher that keeps loading the other partitions when some of them fail, instead of
+/// stopping at the first error like [`Dispatcher`].
+pub struct FaultTolerantDispatcher<'a, S, D, TP> {
+    inner: Dispatcher<'a, S, D, TP>,
+}
+
+impl<'w, S, TSS, D, TSD, TP, ES, ED, ET> FaultTolerantDispatcher<'w, S, D, TP>
+where
+    TSS: TypeSystem,
+    S: Source<TypeSystem = TSS, Error = ES>,
+    ES: From<ConnectorXError> + Send,
+
+    TSD: TypeSystem,
+    D: Destination<TypeSystem = TSD, Error = ED>,
+    ED: From<ConnectorXError> + Send,
+
+    TP: Transport<TSS = TSS, TSD = TSD, S = S, D = D, Error = ET>,
+    ET: From<ConnectorXError> + From<ES> + From<ED> + Send,
+{
+    /// Create a new dispatcher by providing a source, a destination and the queries.
+    pub fn new<Q>(src: S, dst: &'w mut D, queries: &[Q], origin_query: Option<String>) -> Self
+    where
+        for<'a> &'a Q: Into<CXQuery>,
+    {
+        FaultTolerantDispatcher {
+            inner: Dispatcher::new(src, dst, queries, origin_query),
+        }
+    }
+
+    /// See [`Dispatcher::set_partition_assignment`].
+    pub fn set_partition_assignment(&mut self, assignment: PartitionAssignment) {
+        self.inner.set_partition_assignment(assignment);
+    }
+
+    /// Load all the partitions and return the index and error of each failed partition.
+    /// Errors before the partitions start loading (e.g. fetching the metadata) are still returned
+    /// directly.
+    pub fn run(self) -> Result<Vec<(usize, ET)>, ET> {
+        self.inner.dispatch(true)
+    }
+}
+
+/// The result of [`FaultTolerantDispatcher::fetch`]. `batches` holds the data of the succeeded
+/// partitions, along with the batches a failed partition flushed before it failed.
+#[cfg(feature = "dst_arrow")]
+pub struct PartialResult {
+    pub batches: Vec<arrow::record_batch::RecordBatch>,
+    pub failed_partitions: Vec<(usize, ConnectorXError)>,
+}
+
+#[cfg(feature = "dst_arrow")]
+impl<'w, S, TSS, TP, ES, ET> FaultTolerantDispatcher<'w, S, ArrowDestination, TP>
+where
+    TSS: TypeSystem,
+    S: Source<TypeSystem = TSS, Error = ES>,
+    ES: From<ConnectorXError> + Send,
+
+    TP: Transport<TSS = TSS, TSD = ArrowTypeSystem, S = S, D = ArrowDestination, Error = ET>,
+    ET: From<ConnectorXError>
+        + From<ES>
+        + From<ArrowDestinationError>
+        + std::error::Error
+        + Send
+        + Sync
+        + 'static,
+{
+    /// Load all the partitions into the arrow destination and return the fetched batches with
+    /// the failed partitions. Fails with [`ConnectorXError::MultipleErrors`] only if every
+    /// partition failed.
+    pub fn fetch(self) -> CXResult<PartialResult> {
+        let Dispatcher {
+            src,
+            dst,
+            queries,
+            origin_query,
+            assignment,
+            ..
+        } = self.inner;
+        let (npartitions, failed) = Dispatcher::<_, _, TP> {
+            src,
+            dst: &mut *dst,
+            queries,
+            origin_query,
+            assignment,
+            _phantom: PhantomData,
+        }
+        .dispatch(true)
+        .map_err(to_cx_error)?;
+        let failed_partitions: Vec<_> = failed
+            .into_iter()
+            .map(|(i, e)| (i, to_cx_error(e)))
+            .collect();
+        if npartitions > 0 && failed_partitions.len() == npartitions {
+            return Err(ConnectorXError::MultipleErrors(
+                failed_partitions.into_iter().map(|(_, e)| e).collect(),
+            ));
+        }
+        Ok(PartialResult {
+            batches: dst.take_batches().map_err(to_cx_error)?,
+            failed_partitions,
+        })
+    }
+}
+
+#[cfg(feature = "dst_arrow")]
+fn to_cx_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> ConnectorXError {
+    ConnectorXError::Other(anyhow::Error::new(e))
+}
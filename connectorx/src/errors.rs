@@ -24,6 +24,9 @@ pub enum ConnectorXError {
     #[error("No conversion rule from {0} to {1}.")]
     NoConversionRule(String, String),
 
+    #[error("Unknown type {0:?}.")]
+    UnknownType(String),
+
     #[error("Only support single query with SELECT statement, got {0}.")]
     SqlQueryNotSupported(String),
 
@@ -33,6 +36,9 @@ pub enum ConnectorXError {
     #[error("File {0} not found.")]
     FileNotFoundError(String),
 
+    #[error("{} partitions failed, first error: {}", .0.len(), .0.first().map(ToString::to_string).unwrap_or_default())]
+    MultipleErrors(Vec<ConnectorXError>),
+
     #[error(transparent)]
     SQLParserError(#[from] sqlparser::parser::ParserError),
 
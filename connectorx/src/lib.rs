@@ -129,6 +129,12 @@
 //! Instead, we provide following features for you to opt-in: `src_sqlite`, `src_postgres`, `src_mysql`, `src_mssql`, `src_oracle`, `dst_arrow`, `dst_arrow2`.
 //! For example, if you'd like to load data from Postgres to Arrow, you can enable `src_postgres` and `dst_arrow` in `Cargo.toml`.
 //! This will enable [`sources::postgres`], [`destinations::arrow`] and [`transports::PostgresArrowTransport`].
+//!
+//! ## Unstable APIs
+//! The experimental APIs are behind the `unstable` feature. They are not covered by the stability guarantee of ConnectorX:
+//! they may change or be removed in any minor version, so pin an exact version of ConnectorX if you enable it.
+//! Building with `unstable` prints a cargo warning as a reminder.
+//! The unstable APIs are the federated dispatcher [`fed_dispatcher`], which also needs the `federation` feature.
 
 pub mod typesystem;
 #[macro_use]
@@ -138,7 +144,7 @@ pub mod data_order;
 pub mod destinations;
 mod dispatcher;
 pub mod errors;
-#[cfg(feature = "federation")]
+#[cfg(all(feature = "federation", feature = "unstable"))]
 pub mod fed_dispatcher;
 pub mod sources;
 #[doc(hidden)]
@@ -150,11 +156,13 @@ pub mod utils;
 pub mod prelude {
     pub use crate::data_order::{coordinate, DataOrder};
     #[cfg(feature = "dst_arrow")]
-    pub use crate::destinations::arrow::ArrowDestination;
+    pub use crate::destinations::arrow::{ArrowDestination, FromArrow};
     #[cfg(feature = "dst_arrow2")]
     pub use crate::destinations::arrow2::Arrow2Destination;
     pub use crate::destinations::{Consume, Destination, DestinationPartition};
-    pub use crate::dispatcher::Dispatcher;
+    pub use crate::dispatcher::{
+        current_worker, Dispatcher, FaultTolerantDispatcher, PartitionAssignment,
+    };
     pub use crate::errors::ConnectorXError;
     #[cfg(feature = "src_bigquery")]
     pub use crate::sources::bigquery::BigQuerySource;
@@ -172,10 +180,12 @@ pub mod prelude {
     pub use crate::sources::postgres::PostgresSource;
     #[cfg(feature = "src_sqlite")]
     pub use crate::sources::sqlite::SQLiteSource;
-    pub use crate::sources::{PartitionParser, Produce, Source, SourcePartition};
+    pub use crate::sources::{PartitionParser, Produce, ProduceRow, Source, SourcePartition};
     pub use crate::transports::*;
     pub use crate::typesystem::{
         ParameterizedFunc, ParameterizedOn, Realize, Transport, TypeAssoc, TypeConversion,
         TypeSystem,
     };
+    #[cfg(feature = "derive")]
+    pub use connectorx_derive::{FromArrow, SerializeToArrow};
 }
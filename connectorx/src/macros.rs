@@ -65,7 +65,7 @@ macro_rules! impl_typesystem {
                     $(
                         $TS::$V(false) => Ok(()),
                     )+
-                    _ => fehler::throw!($crate::errors::ConnectorXError::TypeCheckFailed(format!("{:?}", ts), std::any::type_name::<$NT>()))
+                    _ => fehler::throw!($crate::errors::ConnectorXError::TypeCheckFailed(format!("{}", ts), std::any::type_name::<$NT>()))
                 }
             }
         }
@@ -76,7 +76,7 @@ macro_rules! impl_typesystem {
                     $(
                         $TS::$V(true) => Ok(()),
                     )+
-                    _ => fehler::throw!($crate::errors::ConnectorXError::TypeCheckFailed(format!("{:?}", ts), std::any::type_name::<$NT>()))
+                    _ => fehler::throw!($crate::errors::ConnectorXError::TypeCheckFailed(format!("{}", ts), std::any::type_name::<$NT>()))
                 }
             }
         }
@@ -189,7 +189,7 @@ macro_rules! impl_transport {
                 )*
                 #[allow(unreachable_patterns)]
                 _ => fehler::throw!($crate::errors::ConnectorXError::NoConversionRule(
-                    format!("{:?}", ts), format!("{}", std::any::type_name::<Self::TSD>())
+                    format!("{}", ts), format!("{}", std::any::type_name::<Self::TSD>())
                 ))
             }
         }
@@ -220,7 +220,7 @@ macro_rules! impl_transport {
                 )*
                 #[allow(unreachable_patterns)]
                 _ => fehler::throw!($crate::errors::ConnectorXError::NoConversionRule(
-                    format!("{:?}", ts1), format!("{:?}", ts1))
+                    format!("{}", ts1), format!("{}", ts2))
                 )
             }
 
@@ -249,7 +249,7 @@ macro_rules! impl_transport {
                 )*
                 #[allow(unreachable_patterns)]
                 _ => fehler::throw!($crate::errors::ConnectorXError::NoConversionRule(
-                    format!("{:?}", ts1), format!("{:?}", ts1))
+                    format!("{}", ts1), format!("{}", ts2))
                 )
             }
 
@@ -7,8 +7,8 @@ pub use self::errors::BigQuerySourceError;
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
-    sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, limit1_query, CXQuery},
+    sources::{probe_health, HealthStatus, PartitionParser, Produce, Source, SourcePartition},
+    sql::{count_query, label_query, limit1_query, CXQuery},
 };
 use anyhow::anyhow;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
@@ -22,6 +22,7 @@ use gcp_bigquery_client::{
 };
 use sqlparser::dialect::Dialect;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 pub use typesystem::BigQueryTypeSystem;
 use url::Url;
@@ -50,6 +51,7 @@ pub struct BigQuerySource {
     project_id: String,
     origin_query: Option<String>,
     queries: Vec<CXQuery<String>>,
+    label: Option<String>,
     names: Vec<String>,
     schema: Vec<BigQueryTypeSystem>,
 }
@@ -76,6 +78,7 @@ impl BigQuerySource {
             project_id,
             origin_query: None,
             queries: vec![],
+            label: None,
             names: vec![],
             schema: vec![],
         }
@@ -100,13 +103,24 @@ where
     }
 
     fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+        self.queries = queries
+            .iter()
+            .map(|q| label_query(q.map(Q::to_string), self.label.as_deref()))
+            .collect();
+    }
+
+    fn with_query_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
     }
 
     fn set_origin_query(&mut self, query: Option<String>) {
         self.origin_query = query;
     }
 
+    fn origin_query(&self) -> Option<String> {
+        self.origin_query.clone()
+    }
+
     #[throws(BigQuerySourceError)]
     fn fetch_metadata(&mut self) {
         assert!(!self.queries.is_empty());
@@ -166,6 +180,21 @@ where
         self.schema.clone()
     }
 
+    #[throws(BigQuerySourceError)]
+    fn health_check(&self, timeout: Duration) -> HealthStatus {
+        probe_health(timeout, || -> Result<(), BigQuerySourceError> {
+            let probe = self
+                .client
+                .job()
+                .query(self.project_id.as_str(), QueryRequest::new("SELECT 1"));
+            // the request is cancelled on the runtime once the timeout elapses
+            self.rt
+                .block_on(tokio::time::timeout(timeout, probe))
+                .map_err(|_| anyhow!("timed out"))??;
+            Ok(())
+        })
+    }
+
     #[throws(BigQuerySourceError)]
     fn partition(self) -> Vec<Self::Partition> {
         let mut ret = vec![];
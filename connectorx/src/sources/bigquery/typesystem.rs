@@ -1,5 +1,9 @@
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use fehler::{throw, throws};
 use gcp_bigquery_client::model::field_type::FieldType;
+use std::convert::TryFrom;
 
 #[derive(Copy, Clone, Debug)]
 pub enum BigQueryTypeSystem {
@@ -33,6 +37,56 @@ impl_typesystem! {
     }
 }
 
+impl std::fmt::Display for BigQueryTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use BigQueryTypeSystem::*;
+        let (name, nullable) = match *self {
+            Bool(n) | Boolean(n) => ("BOOL", n),
+            Int64(n) | Integer(n) => ("INT64", n),
+            Float(n) | Float64(n) => ("FLOAT64", n),
+            Numeric(n) => ("NUMERIC", n),
+            Bignumeric(n) => ("BIGNUMERIC", n),
+            String(n) => ("STRING", n),
+            Bytes(n) => ("BYTES", n),
+            Date(n) => ("DATE", n),
+            Datetime(n) => ("DATETIME", n),
+            Time(n) => ("TIME", n),
+            Timestamp(n) => ("TIMESTAMP", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of a BigQuery type, e.g. `NUMERIC(10, 2)`, the legacy SQL names (`BOOLEAN`,
+/// `INTEGER`, `FLOAT`) included.
+impl<'a> TryFrom<&'a str> for BigQueryTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use BigQueryTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "BOOL" => Bool,
+            "BOOLEAN" => Boolean,
+            "INT64" => Int64,
+            "INTEGER" => Integer,
+            "FLOAT" => Float,
+            "FLOAT64" => Float64,
+            "NUMERIC" => Numeric,
+            "BIGNUMERIC" => Bignumeric,
+            "STRING" => String,
+            "BYTES" => Bytes,
+            "DATE" => Date,
+            "DATETIME" => Datetime,
+            "TIME" => Time,
+            "TIMESTAMP" => Timestamp,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
+
 impl<'a> From<&'a FieldType> for BigQueryTypeSystem {
     fn from(ty: &'a FieldType) -> BigQueryTypeSystem {
         use BigQueryTypeSystem::*;
@@ -5,7 +5,7 @@ mod typesystem;
 
 pub use self::errors::CSVSourceError;
 pub use self::typesystem::CSVTypeSystem;
-use super::{PartitionParser, Produce, Source, SourcePartition};
+use super::{HealthStatus, PartitionParser, Produce, Source, SourcePartition};
 use crate::{data_order::DataOrder, errors::ConnectorXError, sql::CXQuery};
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
@@ -14,6 +14,7 @@ use fehler::{throw, throws};
 use regex::{Regex, RegexBuilder};
 use std::collections::HashSet;
 use std::fs::File;
+use std::time::Duration;
 
 pub struct CSVSource {
     schema: Vec<CSVTypeSystem>,
@@ -182,6 +183,12 @@ impl Source for CSVSource {
         self.schema.clone()
     }
 
+    /// The CSV source has no connection to probe, it is always healthy.
+    #[throws(CSVSourceError)]
+    fn health_check(&self, _timeout: Duration) -> HealthStatus {
+        HealthStatus::Ok { latency_ms: 0 }
+    }
+
     #[throws(CSVSourceError)]
     fn partition(self) -> Vec<Self::Partition> {
         let mut partitions = vec![];
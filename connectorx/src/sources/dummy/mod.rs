@@ -4,13 +4,14 @@
 mod typesystem;
 
 pub use self::typesystem::DummyTypeSystem;
-use super::{PartitionParser, Produce, Source, SourcePartition};
+use super::{HealthStatus, PartitionParser, Produce, Source, SourcePartition};
 use crate::data_order::DataOrder;
 use crate::errors::{ConnectorXError, Result};
 use crate::sql::CXQuery;
 use chrono::{offset, Date, DateTime, Utc};
 use fehler::{throw, throws};
 use num_traits::cast::FromPrimitive;
+use std::time::Duration;
 
 pub struct DummySource {
     names: Vec<String>,
@@ -65,6 +66,10 @@ impl Source for DummySource {
         self.schema.clone()
     }
 
+    fn health_check(&self, _timeout: Duration) -> Result<HealthStatus> {
+        Ok(HealthStatus::Ok { latency_ms: 0 })
+    }
+
     fn partition(self) -> Result<Vec<Self::Partition>> {
         assert!(!self.queries.is_empty());
         let queries = self.queries;
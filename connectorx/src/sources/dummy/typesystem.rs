@@ -6,7 +6,11 @@
 // 3. Add `DataType::T => N` to the macro impl_transmit!.
 //
 
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{DateTime, Utc};
+use fehler::{throw, throws};
+use std::convert::TryFrom;
 /// This is a dummy type system used in this library.
 /// For all the sources, their output values must be one of the types defined by DummyTypeSystem.
 /// For all the destinations, they must support writing any value whose type is defined by DummyTypeSystem.
@@ -29,3 +33,37 @@ impl_typesystem! {
         { DateTime => DateTime<Utc> }
     }
 }
+
+impl std::fmt::Display for DummyTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use DummyTypeSystem::*;
+        let (name, nullable) = match *self {
+            F64(n) => ("f64", n),
+            I64(n) => ("i64", n),
+            Bool(n) => ("bool", n),
+            String(n) => ("String", n),
+            DateTime(n) => ("DateTime", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of a type as displayed by [`DummyTypeSystem`], e.g. `i64(nullable)`.
+impl<'a> TryFrom<&'a str> for DummyTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use DummyTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "F64" => F64,
+            "I64" => I64,
+            "BOOL" => Bool,
+            "STRING" => String,
+            "DATETIME" => DateTime,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
@@ -0,0 +1,270 @@
+//! Watermark based incremental fetching on top of any [`Source`].
+//!
+//! [`IncrementalSource`] remembers the largest value of a watermark column seen so far in a state file,
+//! and rewrites the queries to only fetch rows beyond it. This is enough to build simple CDC pipelines
+//! on append-only (or update-timestamped) tables without a streaming system.
+
+use super::Source;
+use crate::{
+    destinations::arrow::{ArrowDestination, ArrowDestinationError, ArrowTypeSystem},
+    dispatcher::Dispatcher,
+    errors::ConnectorXError,
+    sql::{date_literal, datetime_literal, incremental_query, string_literal, CXQuery},
+    typesystem::{Transport, TypeSystem},
+};
+use anyhow::anyhow;
+use arrow::{
+    array::{as_largestring_array, as_primitive_array, as_string_array, Array},
+    compute,
+    datatypes::{
+        DataType, Date32Type, Date64Type, Float32Type, Float64Type, Int32Type, Int64Type, TimeUnit,
+        TimestampNanosecondType, UInt32Type, UInt64Type,
+    },
+    record_batch::RecordBatch,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use fehler::{throw, throws};
+use log::debug;
+use sqlparser::dialect::Dialect;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// A value of the watermark column.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Watermark {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(String),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Watermark {
+    /// The watermark as a SQL literal of `dialect`: strings are quoted and escaped, and dates are
+    /// typed `DATE` and `TIMESTAMP` literals where the database supports them.
+    pub fn to_sql<T: Dialect>(&self, dialect: &T) -> String {
+        match self {
+            Watermark::Int(v) => v.to_string(),
+            Watermark::UInt(v) => v.to_string(),
+            Watermark::Float(v) => format!("{:?}", v),
+            Watermark::Str(s) => string_literal(s, dialect),
+            Watermark::Date(d) => date_literal(*d, dialect),
+            Watermark::DateTime(dt) => datetime_literal(*dt, dialect),
+        }
+    }
+
+    fn encode(&self) -> String {
+        match self {
+            Watermark::Int(v) => format!("int:{}", v),
+            Watermark::UInt(v) => format!("uint:{}", v),
+            Watermark::Float(v) => format!("float:{:?}", v),
+            Watermark::Str(s) => format!("str:{}", s),
+            Watermark::Date(d) => format!("date:{}", d.format("%Y-%m-%d")),
+            Watermark::DateTime(dt) => format!("datetime:{}", dt.format(DATETIME_FORMAT)),
+        }
+    }
+
+    #[throws(ConnectorXError)]
+    fn decode(s: &str) -> Self {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("malformed watermark {}", s))?;
+        let parsed = match kind {
+            "int" => value.parse().map(Watermark::Int).ok(),
+            "uint" => value.parse().map(Watermark::UInt).ok(),
+            "float" => value.parse().map(Watermark::Float).ok(),
+            "str" => Some(Watermark::Str(value.to_string())),
+            "date" => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(Watermark::Date)
+                .ok(),
+            "datetime" => NaiveDateTime::parse_from_str(value, DATETIME_FORMAT)
+                .map(Watermark::DateTime)
+                .ok(),
+            _ => None,
+        };
+        parsed.ok_or_else(|| anyhow!("malformed watermark {}", s))?
+    }
+}
+
+pub struct IncrementalSource<S> {
+    inner: S,
+    watermark_col: String,
+    state_file: PathBuf,
+    last_watermark: Option<Watermark>,
+}
+
+impl<S> IncrementalSource<S> {
+    /// Wrap `inner` and load the last watermark from `state_file`. A missing state file means
+    /// nothing has been fetched yet.
+    #[throws(ConnectorXError)]
+    pub fn new(inner: S, watermark_col: &str, state_file: &Path) -> Self {
+        let last_watermark = match fs::read_to_string(state_file) {
+            Ok(w) if w.trim().is_empty() => None,
+            Ok(w) => Some(Watermark::decode(w.trim_end_matches('\n'))?),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => throw!(e),
+        };
+        debug!("Last watermark of {}: {:?}", watermark_col, last_watermark);
+
+        Self {
+            inner,
+            watermark_col: watermark_col.to_string(),
+            state_file: state_file.to_path_buf(),
+            last_watermark,
+        }
+    }
+
+    /// The watermark persisted by the last successful fetch.
+    pub fn last_watermark(&self) -> Option<&Watermark> {
+        self.last_watermark.as_ref()
+    }
+
+    /// Rewrite `queries` to add `WHERE watermark_col > last_watermark ORDER BY watermark_col`,
+    /// with the watermark written as a literal of `dialect`.
+    pub fn queries<Q: AsRef<str>, T: Dialect>(
+        &self,
+        queries: &[CXQuery<Q>],
+        dialect: &T,
+    ) -> Vec<CXQuery<String>> {
+        let watermark = self.last_watermark.as_ref().map(|w| w.to_sql(dialect));
+        queries
+            .iter()
+            .map(|q| {
+                incremental_query(
+                    &q.map(|q| q.as_ref().to_string()),
+                    &self.watermark_col,
+                    watermark.as_deref(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl<S, TSS, ES> IncrementalSource<S>
+where
+    TSS: TypeSystem,
+    S: Source<TypeSystem = TSS, Error = ES>,
+    ES: From<ConnectorXError> + Send,
+{
+    /// Fetch the rows beyond the last watermark into `dst` and return the fetched record batches
+    /// (taken out of `dst`, which must not be a streaming destination). The returned
+    /// [`WatermarkCommit`] holds the largest watermark of the batches, to be persisted once the
+    /// caller has safely stored them.
+    pub fn fetch<TP, ET, Q, T>(
+        self,
+        dst: &mut ArrowDestination,
+        queries: &[CXQuery<Q>],
+        dialect: &T,
+    ) -> Result<(Vec<RecordBatch>, WatermarkCommit), ET>
+    where
+        Q: AsRef<str>,
+        T: Dialect,
+        TP: Transport<TSS = TSS, TSD = ArrowTypeSystem, S = S, D = ArrowDestination, Error = ET>,
+        ET: From<ConnectorXError> + From<ES> + From<ArrowDestinationError> + Send,
+    {
+        let queries = self.queries(queries, dialect);
+        let dispatcher =
+            Dispatcher::<S, ArrowDestination, TP>::new(self.inner, dst, &queries, None);
+        dispatcher.run()?;
+        let batches = dst.take_batches()?;
+        // no new rows keep the last watermark
+        let watermark = max_watermark(&batches, &self.watermark_col)?.or(self.last_watermark);
+        let commit = WatermarkCommit {
+            state_file: self.state_file,
+            watermark,
+        };
+        Ok((batches, commit))
+    }
+}
+
+/// The largest value of `col` in `batches`, ignoring nulls. Timestamps are read from the
+/// destination in milliseconds (`Date64`) or nanoseconds.
+#[throws(ConnectorXError)]
+fn max_watermark(batches: &[RecordBatch], col: &str) -> Option<Watermark> {
+    let mut max: Option<Watermark> = None;
+    for rb in batches {
+        let idx = rb
+            .schema()
+            .fields()
+            .iter()
+            .position(|f| f.name().eq_ignore_ascii_case(col))
+            .ok_or_else(|| anyhow!("watermark column {} is not in the result", col))?;
+        let array = rb.column(idx);
+        let value = match array.data_type() {
+            DataType::Int32 => compute::max(as_primitive_array::<Int32Type>(array))
+                .map(|v| Watermark::Int(v as i64)),
+            DataType::Int64 => {
+                compute::max(as_primitive_array::<Int64Type>(array)).map(Watermark::Int)
+            }
+            DataType::UInt32 => compute::max(as_primitive_array::<UInt32Type>(array))
+                .map(|v| Watermark::UInt(v as u64)),
+            DataType::UInt64 => {
+                compute::max(as_primitive_array::<UInt64Type>(array)).map(Watermark::UInt)
+            }
+            DataType::Float32 => compute::max(as_primitive_array::<Float32Type>(array))
+                .map(|v| Watermark::Float(v as f64)),
+            DataType::Float64 => {
+                compute::max(as_primitive_array::<Float64Type>(array)).map(Watermark::Float)
+            }
+            DataType::Utf8 => {
+                compute::max_string(as_string_array(array)).map(|s| Watermark::Str(s.to_string()))
+            }
+            DataType::LargeUtf8 => compute::max_string(as_largestring_array(array))
+                .map(|s| Watermark::Str(s.to_string())),
+            DataType::Date32 => compute::max(as_primitive_array::<Date32Type>(array)).map(|d| {
+                Watermark::Date(NaiveDate::from_ymd(1970, 1, 1) + Duration::days(d as i64))
+            }),
+            DataType::Date64 => compute::max(as_primitive_array::<Date64Type>(array)).map(|ms| {
+                Watermark::DateTime(NaiveDateTime::from_timestamp(
+                    ms.div_euclid(1_000),
+                    (ms.rem_euclid(1_000) * 1_000_000) as u32,
+                ))
+            }),
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                compute::max(as_primitive_array::<TimestampNanosecondType>(array)).map(|ns| {
+                    Watermark::DateTime(NaiveDateTime::from_timestamp(
+                        ns.div_euclid(1_000_000_000),
+                        ns.rem_euclid(1_000_000_000) as u32,
+                    ))
+                })
+            }
+            dt => throw!(anyhow!(
+                "unsupported type {:?} of watermark column {}",
+                dt,
+                col
+            )),
+        };
+        if let Some(v) = value {
+            if max.as_ref().map_or(true, |m| v > *m) {
+                max = Some(v);
+            }
+        }
+    }
+    max
+}
+
+/// Proof of a successful incremental fetch, allowing to persist the new watermark.
+pub struct WatermarkCommit {
+    state_file: PathBuf,
+    watermark: Option<Watermark>,
+}
+
+impl WatermarkCommit {
+    /// The largest watermark fetched so far, `None` if nothing has ever been fetched.
+    pub fn watermark(&self) -> Option<&Watermark> {
+        self.watermark.as_ref()
+    }
+
+    /// Persist the watermark as the new maximum.
+    #[throws(ConnectorXError)]
+    pub fn commit(self) {
+        if let Some(watermark) = self.watermark {
+            debug!("Persist watermark {:?} to {:?}", watermark, self.state_file);
+            fs::write(&self.state_file, watermark.encode())?;
+        }
+    }
+}
@@ -7,21 +7,37 @@ pub mod bigquery;
 pub mod csv;
 #[cfg(feature = "src_dummy")]
 pub mod dummy;
+#[cfg(feature = "dst_arrow")]
+pub mod incremental;
 #[cfg(feature = "src_mssql")]
 pub mod mssql;
 #[cfg(feature = "src_mysql")]
 pub mod mysql;
+#[cfg(feature = "oauth")]
+pub mod oauth;
 #[cfg(feature = "src_oracle")]
 pub mod oracle;
 #[cfg(feature = "src_postgres")]
 pub mod postgres;
+#[cfg(feature = "rds_iam")]
+pub mod rds;
+pub mod row_errors;
 #[cfg(feature = "src_sqlite")]
 pub mod sqlite;
+pub mod stats;
+#[cfg(feature = "dst_arrow")]
+pub mod table_scan;
+pub mod watermark;
+#[cfg(feature = "dst_arrow")]
+pub mod writable;
 
 use crate::data_order::DataOrder;
 use crate::errors::ConnectorXError;
 use crate::sql::CXQuery;
 use crate::typesystem::{TypeAssoc, TypeSystem};
+use anyhow::anyhow;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
 
 pub trait Source {
     /// Supported data orders, ordering by preference.
@@ -38,15 +54,84 @@ pub trait Source {
 
     fn set_origin_query(&mut self, query: Option<String>);
 
+    /// The origin query last set with [`Source::set_origin_query`]. The default is `None`, for the
+    /// sources that do not keep it; [`Source::count`] then clears it.
+    fn origin_query(&self) -> Option<String> {
+        None
+    }
+
+    /// Label the queries set afterwards with a leading `/* connectorx: {label} */` comment, see
+    /// [`crate::sql::label_query`]. The sources that do not run SQL ignore the label.
+    fn with_query_label(&mut self, _label: &str) {}
+
     fn fetch_metadata(&mut self) -> Result<(), Self::Error>;
     /// Get total number of rows if available
     fn result_rows(&mut self) -> Result<Option<usize>, Self::Error>;
 
+    #[must_use]
     fn names(&self) -> Vec<String>;
 
+    #[must_use]
     fn schema(&self) -> Vec<Self::TypeSystem>;
 
     fn partition(self) -> Result<Vec<Self::Partition>, Self::Error>;
+
+    /// Count the rows of `query` with a `COUNT(*)` of it, see [`crate::sql::count_query`]. A
+    /// limit of the query (e.g. `LIMIT 10`) applies, so the count is at most the limit. The
+    /// default runs [`Source::result_rows`] with `query` as the origin query, then sets the
+    /// origin query back, and fails if the source cannot count it.
+    fn count(&mut self, query: &str) -> Result<u64, Self::Error> {
+        let origin_query = self.origin_query();
+        self.set_origin_query(Some(query.to_string()));
+        let nrows = self.result_rows();
+        self.set_origin_query(origin_query);
+        match nrows? {
+            Some(nrows) => Ok(nrows as u64),
+            None => {
+                Err(ConnectorXError::from(anyhow!("cannot count the rows of {}", query)).into())
+            }
+        }
+    }
+
+    /// Probe the source with a lightweight query (e.g. `SELECT 1`), waiting at most `timeout`
+    /// for it to complete. A failed probe is reported as [`HealthStatus::Degraded`] rather than
+    /// as an error. The default reports [`HealthStatus::Unsupported`], for the sources that
+    /// cannot be probed.
+    fn health_check(&self, _timeout: Duration) -> Result<HealthStatus, Self::Error> {
+        Ok(HealthStatus::Unsupported)
+    }
+}
+
+/// The outcome of [`Source::health_check`], `Unsupported` for the sources that have no probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok { latency_ms: u64 },
+    Degraded { error: String, latency_ms: u64 },
+    Timeout,
+    Unsupported,
+}
+
+/// Run `probe` and report its outcome, or `Timeout` if it took `timeout` or longer, whatever it
+/// failed with. The probe runs on the calling thread: it must bound itself to `timeout` with the
+/// timeouts of the driver, e.g. the timeout to check out a connection from the pool and a
+/// statement timeout, so that nothing is left running once it returns.
+pub(crate) fn probe_health<F, E>(timeout: Duration, probe: F) -> HealthStatus
+where
+    F: FnOnce() -> Result<(), E>,
+    E: Display,
+{
+    let start = Instant::now();
+    let result = probe();
+    let elapsed = start.elapsed();
+    let latency_ms = elapsed.as_millis() as u64;
+    match result {
+        _ if elapsed >= timeout => HealthStatus::Timeout,
+        Ok(()) => HealthStatus::Ok { latency_ms },
+        Err(e) => HealthStatus::Degraded {
+            error: e.to_string(),
+            latency_ms,
+        },
+    }
 }
 
 /// In general, a `DataSource` abstracts the data source as a stream, which can produce
@@ -65,9 +150,11 @@ pub trait SourcePartition: Sized {
 
     /// Number of rows this `DataSource` got.
     /// Sometimes it is not possible for the source to know how many rows it gets before reading the whole data.
+    #[must_use]
     fn nrows(&self) -> usize;
 
     /// Number of cols this `DataSource` got.
+    #[must_use]
     fn ncols(&self) -> usize;
 }
 
@@ -95,3 +182,11 @@ pub trait Produce<'r, T> {
 
     fn produce(&'r mut self) -> Result<T, Self::Error>;
 }
+
+/// A row type that can be read from a parser field by field, usually implemented through
+/// `#[derive(SerializeToArrow)]` (feature `derive`).
+pub trait ProduceRow<P>: Sized {
+    type Error;
+
+    fn produce_row(parser: &mut P) -> Result<Self, Self::Error>;
+}
@@ -9,8 +9,8 @@ use crate::constants::DB_BUFFER_SIZE;
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
-    sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, CXQuery},
+    sources::{probe_health, HealthStatus, PartitionParser, Produce, Source, SourcePartition},
+    sql::{count_query, label_query, CXQuery},
     utils::DummyBox,
 };
 use anyhow::anyhow;
@@ -26,6 +26,7 @@ use rust_decimal::Decimal;
 use sqlparser::dialect::MsSqlDialect;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tiberius::{AuthMethod, Config, EncryptionLevel, QueryResult, Row};
 use tokio::runtime::{Handle, Runtime};
 use url::Url;
@@ -38,6 +39,7 @@ pub struct MsSQLSource {
     pool: Pool<ConnectionManager>,
     origin_query: Option<String>,
     queries: Vec<CXQuery<String>>,
+    label: Option<String>,
     names: Vec<String>,
     schema: Vec<MsSQLTypeSystem>,
 }
@@ -108,6 +110,7 @@ impl MsSQLSource {
             pool,
             origin_query: None,
             queries: vec![],
+            label: None,
             names: vec![],
             schema: vec![],
         }
@@ -131,13 +134,24 @@ where
     }
 
     fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+        self.queries = queries
+            .iter()
+            .map(|q| label_query(q.map(Q::to_string), self.label.as_deref()))
+            .collect();
+    }
+
+    fn with_query_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
     }
 
     fn set_origin_query(&mut self, query: Option<String>) {
         self.origin_query = query;
     }
 
+    fn origin_query(&self) -> Option<String> {
+        self.origin_query.clone()
+    }
+
     #[throws(MsSQLSourceError)]
     fn fetch_metadata(&mut self) {
         assert!(!self.queries.is_empty());
@@ -202,6 +216,21 @@ where
         self.schema.clone()
     }
 
+    #[throws(MsSQLSourceError)]
+    fn health_check(&self, timeout: Duration) -> HealthStatus {
+        probe_health(timeout, || -> Result<(), MsSQLSourceError> {
+            let probe = async {
+                let mut conn = self.pool.get().await?;
+                conn.query("SELECT 1", &[]).await?.into_row().await?;
+                Ok::<_, MsSQLSourceError>(())
+            };
+            // the probe is cancelled on the runtime once the timeout elapses
+            self.rt
+                .block_on(tokio::time::timeout(timeout, probe))
+                .map_err(|_| anyhow!("timed out"))?
+        })
+    }
+
     #[throws(MsSQLSourceError)]
     fn partition(self) -> Vec<Self::Partition> {
         let mut ret = vec![];
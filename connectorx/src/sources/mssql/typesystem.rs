@@ -1,5 +1,9 @@
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use fehler::{throw, throws};
 use rust_decimal::Decimal;
+use std::convert::TryFrom;
 use tiberius::{ColumnData, ColumnType, FromSql};
 use uuid::Uuid;
 
@@ -60,6 +64,96 @@ impl_typesystem! {
     }
 }
 
+impl std::fmt::Display for MsSQLTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use MsSQLTypeSystem::*;
+        let (name, nullable) = match *self {
+            Tinyint(n) => ("TINYINT", n),
+            Smallint(n) => ("SMALLINT", n),
+            Int(n) => ("INT", n),
+            Bigint(n) => ("BIGINT", n),
+            Intn(n) => ("INTN", n),
+            Float24(n) => ("REAL", n),
+            Float53(n) => ("FLOAT", n),
+            Floatn(n) => ("FLOATN", n),
+            Bit(n) => ("BIT", n),
+            Nvarchar(n) => ("NVARCHAR", n),
+            Varchar(n) => ("VARCHAR", n),
+            Nchar(n) => ("NCHAR", n),
+            Char(n) => ("CHAR", n),
+            Ntext(n) => ("NTEXT", n),
+            Text(n) => ("TEXT", n),
+            Binary(n) => ("BINARY", n),
+            Varbinary(n) => ("VARBINARY", n),
+            Image(n) => ("IMAGE", n),
+            Uniqueidentifier(n) => ("UNIQUEIDENTIFIER", n),
+            Numeric(n) => ("NUMERIC", n),
+            Decimal(n) => ("DECIMAL", n),
+            Datetime(n) => ("DATETIME", n),
+            Datetime2(n) => ("DATETIME2", n),
+            Smalldatetime(n) => ("SMALLDATETIME", n),
+            Date(n) => ("DATE", n),
+            Time(n) => ("TIME", n),
+            Datetimeoffset(n) => ("DATETIMEOFFSET", n),
+            Money(n) => ("MONEY", n),
+            SmallMoney(n) => ("SMALLMONEY", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of a SQL Server type, e.g. `NVARCHAR(MAX)` or `FLOAT(24)`.
+impl<'a> TryFrom<&'a str> for MsSQLTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use MsSQLTypeSystem::*;
+        let TypeName {
+            name,
+            params,
+            nullable,
+        } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "TINYINT" => Tinyint,
+            "SMALLINT" => Smallint,
+            "INT" | "INTEGER" => Int,
+            "BIGINT" => Bigint,
+            "INTN" => Intn,
+            "REAL" => Float24,
+            // FLOAT(1) to FLOAT(24) is a REAL
+            "FLOAT" => match params.first().and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if n <= 24 => Float24,
+                _ => Float53,
+            },
+            "FLOATN" => Floatn,
+            "BIT" => Bit,
+            "NVARCHAR" => Nvarchar,
+            "VARCHAR" => Varchar,
+            "NCHAR" => Nchar,
+            "CHAR" => Char,
+            "NTEXT" => Ntext,
+            "TEXT" => Text,
+            "BINARY" => Binary,
+            "VARBINARY" => Varbinary,
+            "IMAGE" => Image,
+            "UNIQUEIDENTIFIER" => Uniqueidentifier,
+            "NUMERIC" => Numeric,
+            "DECIMAL" => Decimal,
+            "DATETIME" => Datetime,
+            "DATETIME2" => Datetime2,
+            "SMALLDATETIME" => Smalldatetime,
+            "DATE" => Date,
+            "TIME" => Time,
+            "DATETIMEOFFSET" => Datetimeoffset,
+            "MONEY" => Money,
+            "SMALLMONEY" => SmallMoney,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
+
 impl<'a> From<&'a ColumnType> for MsSQLTypeSystem {
     fn from(ty: &'a ColumnType) -> MsSQLTypeSystem {
         use MsSQLTypeSystem::*;
@@ -5,25 +5,35 @@ mod typesystem;
 
 pub use self::errors::MySQLSourceError;
 use crate::constants::DB_BUFFER_SIZE;
+#[cfg(feature = "rds_iam")]
+use crate::sources::rds::RdsIamAuthenticator;
+#[cfg(feature = "dst_arrow")]
+use crate::sources::writable::{
+    chunks, matched_count_query, upsert_query_mysql, MergeStats, WritableSource,
+};
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
-    sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, limit1_query, CXQuery},
+    sources::{probe_health, HealthStatus, PartitionParser, Produce, Source, SourcePartition},
+    sql::{count_query, label_query, limit1_query, CXQuery},
 };
 use anyhow::anyhow;
+#[cfg(feature = "dst_arrow")]
+use arrow::record_batch::RecordBatch;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use fehler::{throw, throws};
 use log::{debug, warn};
 use r2d2::{Pool, PooledConnection};
 use r2d2_mysql::{
-    mysql::{prelude::Queryable, Binary, Opts, OptsBuilder, QueryResult, Row, Text},
+    mysql::{prelude::Queryable, Binary, Opts, OptsBuilder, QueryResult, Row, Text, TxOpts},
     MysqlConnectionManager,
 };
 use rust_decimal::Decimal;
 use serde_json::Value;
 use sqlparser::dialect::MySqlDialect;
 use std::marker::PhantomData;
+use std::sync::RwLock;
+use std::time::Duration;
 pub use typesystem::MySQLTypeSystem;
 
 type MysqlManager = MysqlConnectionManager;
@@ -39,31 +49,128 @@ fn get_total_rows(conn: &mut MysqlConn, query: &CXQuery<String>) -> usize {
 }
 
 pub struct MySQLSource<P> {
-    pool: Pool<MysqlManager>,
+    // replaced when the authentication token is refreshed, see `get_conn`
+    pool: RwLock<Pool<MysqlManager>>,
+    #[cfg(feature = "rds_iam")]
+    opts: Opts,
+    #[cfg(feature = "rds_iam")]
+    rds_iam: Option<RdsIamAuthenticator>,
     origin_query: Option<String>,
     queries: Vec<CXQuery<String>>,
+    label: Option<String>,
     names: Vec<String>,
     schema: Vec<MySQLTypeSystem>,
     _protocol: PhantomData<P>,
 }
 
 impl<P> MySQLSource<P> {
+    /// The connections are established lazily, when checked out, so that the password of `conn`
+    /// may be replaced by a token (see `with_rds_iam_auth`) first.
     #[throws(MySQLSourceError)]
     pub fn new(conn: &str, nconn: usize) -> Self {
-        let manager = MysqlConnectionManager::new(OptsBuilder::from_opts(Opts::from_url(conn)?));
+        let opts = Opts::from_url(conn)?;
+        let manager = MysqlConnectionManager::new(OptsBuilder::from_opts(opts.clone()));
         let pool = r2d2::Pool::builder()
             .max_size(nconn as u32)
-            .build(manager)?;
+            .min_idle(Some(0))
+            .build_unchecked(manager);
 
         Self {
-            pool,
+            pool: RwLock::new(pool),
+            #[cfg(feature = "rds_iam")]
+            opts,
+            #[cfg(feature = "rds_iam")]
+            rds_iam: None,
             origin_query: None,
             queries: vec![],
+            label: None,
             names: vec![],
             schema: vec![],
             _protocol: PhantomData,
         }
     }
+
+    /// Authenticate as `db_user` with the IAM authentication tokens of Amazon RDS in `region`,
+    /// see [`RdsIamAuthenticator`]. The token is generated for the host and port of the
+    /// connection string, and the connections of the pool are established again with it. RDS
+    /// only accepts the tokens over TLS, so the connection string should require it
+    /// (`require_ssl=true`); the token is sent with the `mysql_clear_password` plugin. The token
+    /// and the connections are refreshed when a connection is checked out with the token about
+    /// to expire.
+    #[cfg(feature = "rds_iam")]
+    #[throws(MySQLSourceError)]
+    pub fn with_rds_iam_auth(&mut self, region: &str, db_user: &str) {
+        self.opts = OptsBuilder::from_opts(self.opts.clone())
+            .user(Some(db_user))
+            .enable_cleartext_plugin(true)
+            .into();
+        self.rds_iam = Some(RdsIamAuthenticator::new(region, db_user));
+        self.refresh_rds_iam()?;
+    }
+
+    /// Rebuild the pool with a new IAM token if the current one is about to expire.
+    #[cfg(feature = "rds_iam")]
+    #[throws(MySQLSourceError)]
+    fn refresh_rds_iam(&self) {
+        let rds_iam = match &self.rds_iam {
+            Some(rds_iam) => rds_iam,
+            None => return,
+        };
+        let host = self.opts.get_ip_or_hostname().to_string();
+        let port = self.opts.get_tcp_port();
+        if !rds_iam.needs_refresh(&host, port) {
+            return;
+        }
+        let token = rds_iam.token(&host, port)?;
+        let manager = MysqlConnectionManager::new(
+            OptsBuilder::from_opts(self.opts.clone()).pass(Some(token)),
+        );
+        let mut pool = self.pool.write().unwrap();
+        *pool = r2d2::Pool::builder()
+            .max_size(pool.max_size())
+            .build(manager)?;
+    }
+
+    /// The pool, after refreshing the authentication token.
+    #[throws(MySQLSourceError)]
+    fn pool(&self) -> Pool<MysqlManager> {
+        #[cfg(feature = "rds_iam")]
+        self.refresh_rds_iam()?;
+        self.pool.read().unwrap().clone()
+    }
+
+    /// Check out a connection of the pool, after refreshing the authentication token.
+    #[throws(MySQLSourceError)]
+    fn get_conn(&self) -> MysqlConn {
+        self.pool()?.get()?
+    }
+}
+
+#[cfg(feature = "dst_arrow")]
+impl<P> WritableSource for MySQLSource<P> {
+    type Error = MySQLSourceError;
+
+    #[throws(MySQLSourceError)]
+    fn merge(&self, target_table: &str, key_cols: &[&str], batch: &RecordBatch) -> MergeStats {
+        if batch.num_rows() == 0 {
+            return MergeStats::default();
+        }
+        let mut conn = self.get_conn()?;
+        let mut tx = conn.start_transaction(TxOpts::default())?;
+        let mut updated = 0;
+        for chunk in chunks(batch) {
+            let count = matched_count_query(target_table, key_cols, &chunk, &MySqlDialect {})?;
+            updated += tx
+                .query_first::<usize, _>(&count)?
+                .ok_or_else(|| anyhow!("mysql failed to get the count of query: {}", count))?;
+            tx.query_drop(upsert_query_mysql(target_table, key_cols, &chunk)?)?;
+        }
+        tx.commit()?;
+        MergeStats {
+            inserted: batch.num_rows() - updated,
+            updated,
+        }
+    }
 }
 
 impl<P> Source for MySQLSource<P>
@@ -85,18 +192,29 @@ where
     }
 
     fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+        self.queries = queries
+            .iter()
+            .map(|q| label_query(q.map(Q::to_string), self.label.as_deref()))
+            .collect();
+    }
+
+    fn with_query_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
     }
 
     fn set_origin_query(&mut self, query: Option<String>) {
         self.origin_query = query;
     }
 
+    fn origin_query(&self) -> Option<String> {
+        self.origin_query.clone()
+    }
+
     #[throws(MySQLSourceError)]
     fn fetch_metadata(&mut self) {
         assert!(!self.queries.is_empty());
 
-        let mut conn = self.pool.get()?;
+        let mut conn = self.get_conn()?;
         let first_query = &self.queries[0];
 
         match conn.prep(&*first_query) {
@@ -173,7 +291,7 @@ where
         match &self.origin_query {
             Some(q) => {
                 let cxq = CXQuery::Naked(q.clone());
-                let mut conn = self.pool.get()?;
+                let mut conn = self.get_conn()?;
                 let nrows = get_total_rows(&mut conn, &cxq)?;
                 Some(nrows)
             }
@@ -190,10 +308,24 @@ where
     }
 
     #[throws(MySQLSourceError)]
-    fn partition(self) -> Vec<Self::Partition> {
+    fn health_check(&self, timeout: Duration) -> HealthStatus {
+        let pool = self.pool()?;
+        probe_health(timeout, || -> Result<(), MySQLSourceError> {
+            let mut conn = pool.get_timeout(timeout)?;
+            // a MAX_EXECUTION_TIME of 0 disables it
+            conn.query_drop(format!(
+                "SELECT /*+ MAX_EXECUTION_TIME({}) */ 1",
+                timeout.as_millis().max(1)
+            ))?;
+            Ok(())
+        })
+    }
+
+    #[throws(MySQLSourceError)]
+    fn partition(mut self) -> Vec<Self::Partition> {
         let mut ret = vec![];
-        for query in self.queries {
-            let conn = self.pool.get()?;
+        for query in std::mem::take(&mut self.queries) {
+            let conn = self.get_conn()?;
             ret.push(MySQLSourcePartition::new(conn, &query, &self.schema));
         }
         ret
@@ -1,7 +1,11 @@
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use fehler::{throw, throws};
 use r2d2_mysql::mysql::consts::{ColumnFlags, ColumnType};
 use rust_decimal::Decimal;
 use serde_json::Value;
+use std::convert::TryFrom;
 
 #[derive(Copy, Clone, Debug)]
 pub enum MySQLTypeSystem {
@@ -56,6 +60,82 @@ impl_typesystem! {
     }
 }
 
+impl std::fmt::Display for MySQLTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use MySQLTypeSystem::*;
+        let (name, nullable) = match *self {
+            Float(n) => ("FLOAT", n),
+            Double(n) => ("DOUBLE", n),
+            Tiny(n) => ("TINYINT", n),
+            Short(n) => ("SMALLINT", n),
+            Long(n) => ("INT", n),
+            Int24(n) => ("MEDIUMINT", n),
+            LongLong(n) => ("BIGINT", n),
+            UTiny(n) => ("TINYINT UNSIGNED", n),
+            UShort(n) => ("SMALLINT UNSIGNED", n),
+            ULong(n) => ("INT UNSIGNED", n),
+            UInt24(n) => ("MEDIUMINT UNSIGNED", n),
+            ULongLong(n) => ("BIGINT UNSIGNED", n),
+            Datetime(n) => ("DATETIME", n),
+            Date(n) => ("DATE", n),
+            Time(n) => ("TIME", n),
+            Decimal(n) => ("DECIMAL", n),
+            Char(n) => ("CHAR", n),
+            VarChar(n) => ("VARCHAR", n),
+            Timestamp(n) => ("TIMESTAMP", n),
+            Year(n) => ("YEAR", n),
+            Enum(n) => ("ENUM", n),
+            TinyBlob(n) => ("TINYBLOB", n),
+            Blob(n) => ("BLOB", n),
+            MediumBlob(n) => ("MEDIUMBLOB", n),
+            LongBlob(n) => ("LONGBLOB", n),
+            Json(n) => ("JSON", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of a MySQL type, e.g. `DECIMAL(10,2)` or `INT(11) UNSIGNED`.
+impl<'a> TryFrom<&'a str> for MySQLTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use MySQLTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "FLOAT" => Float,
+            "DOUBLE" | "DOUBLE PRECISION" | "REAL" => Double,
+            "TINYINT" | "BOOL" | "BOOLEAN" => Tiny,
+            "SMALLINT" => Short,
+            "INT" | "INTEGER" => Long,
+            "MEDIUMINT" => Int24,
+            "BIGINT" => LongLong,
+            "TINYINT UNSIGNED" => UTiny,
+            "SMALLINT UNSIGNED" => UShort,
+            "INT UNSIGNED" | "INTEGER UNSIGNED" => ULong,
+            "MEDIUMINT UNSIGNED" => UInt24,
+            "BIGINT UNSIGNED" => ULongLong,
+            "DATETIME" => Datetime,
+            "DATE" => Date,
+            "TIME" => Time,
+            "DECIMAL" | "DEC" | "NUMERIC" => Decimal,
+            "CHAR" => Char,
+            "VARCHAR" => VarChar,
+            "TIMESTAMP" => Timestamp,
+            "YEAR" => Year,
+            "ENUM" => Enum,
+            "TINYBLOB" => TinyBlob,
+            "BLOB" => Blob,
+            "MEDIUMBLOB" => MediumBlob,
+            "LONGBLOB" => LongBlob,
+            "JSON" => Json,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
+
 impl<'a> From<(&'a ColumnType, &'a ColumnFlags)> for MySQLTypeSystem {
     fn from(col: (&'a ColumnType, &'a ColumnFlags)) -> MySQLTypeSystem {
         use MySQLTypeSystem::*;
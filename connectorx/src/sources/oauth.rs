@@ -0,0 +1,129 @@
+//! OAuth 2.0 access tokens for the databases accepting them in place of a password.
+//!
+//! [`OAuthTokenCache`] gets a token with the client credentials flow and keeps it until shortly
+//! before it expires (`expires_in` of the token response), so that connections established later
+//! in a load get a fresh one.
+
+use anyhow::anyhow;
+use fehler::throws;
+use serde_json::Value;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The tokens are refreshed this long before they expire.
+pub const OAUTH_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// The lifetime of the tokens whose response has no `expires_in`.
+const DEFAULT_EXPIRES_IN: u64 = 3600;
+
+/// The current time the expiry of the tokens is checked against, see
+/// [`OAuthTokenCache::set_clock`].
+pub type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// A client of the token endpoint of an OAuth 2.0 authorization server.
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// The access token of an [`OAuthConfig`]. It is fetched on the first use and fetched again when
+/// it is about to expire. The cache is shared by the threads of a load: the refresh is done under
+/// a lock, so that concurrent callers wait for a single request to the token endpoint.
+pub struct OAuthTokenCache {
+    config: OAuthConfig,
+    client: reqwest::blocking::Client,
+    clock: Clock,
+    token: Mutex<Option<CachedToken>>,
+}
+
+impl fmt::Debug for OAuthTokenCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthTokenCache")
+            .field("config", &self.config)
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+impl OAuthTokenCache {
+    pub fn new(config: OAuthConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::blocking::Client::new(),
+            clock: Arc::new(Instant::now),
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Check the expiry of the tokens against `clock` rather than `Instant::now`, e.g. to test
+    /// the refreshes without waiting for the tokens to expire.
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    /// Whether the cached token is missing or about to expire, i.e. [`Self::token`] would fetch a
+    /// new one.
+    pub fn needs_refresh(&self) -> bool {
+        match &*self.token.lock().unwrap() {
+            Some(token) => token.expires_at <= (self.clock)() + OAUTH_REFRESH_MARGIN,
+            None => true,
+        }
+    }
+
+    /// The access token, fetched from the token endpoint if needed.
+    #[throws(anyhow::Error)]
+    pub fn token(&self) -> String {
+        let mut cached = self.token.lock().unwrap();
+        match &*cached {
+            Some(token) if token.expires_at > (self.clock)() + OAUTH_REFRESH_MARGIN => {
+                token.access_token.clone()
+            }
+            _ => {
+                let token = self.fetch()?;
+                let access_token = token.access_token.clone();
+                *cached = Some(token);
+                access_token
+            }
+        }
+    }
+
+    #[throws(anyhow::Error)]
+    fn fetch(&self) -> CachedToken {
+        let requested_at = (self.clock)();
+        let scope = self.config.scopes.join(" ");
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.config.client_id.as_str()),
+            ("client_secret", self.config.client_secret.as_str()),
+        ];
+        if !scope.is_empty() {
+            form.push(("scope", scope.as_str()));
+        }
+        let resp: Value = self
+            .client
+            .post(&self.config.token_url)
+            .form(&form)
+            .send()?
+            .error_for_status()?
+            .json()?;
+        let access_token = resp["access_token"]
+            .as_str()
+            .ok_or_else(|| anyhow!("no access_token in the token response"))?
+            .to_string();
+        let expires_in = resp["expires_in"].as_u64().unwrap_or(DEFAULT_EXPIRES_IN);
+        CachedToken {
+            access_token,
+            expires_at: requested_at + Duration::from_secs(expires_in),
+        }
+    }
+}
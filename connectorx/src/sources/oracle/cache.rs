@@ -0,0 +1,259 @@
+//! An in-process read-through cache of the rows of small queries, e.g. the dimension tables of a
+//! star schema loaded again and again.
+
+use super::OracleSourceError;
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use fehler::{throw, throws};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The default maximum number of rows of a cached query.
+pub const DEFAULT_CACHE_MAX_ROWS: usize = 10_000;
+/// The default maximum estimated size of the rows of a cached query, in bytes.
+pub const DEFAULT_CACHE_MAX_BYTES: usize = 16 << 20;
+
+/// The rows of a cached query.
+pub type CachedRows = Arc<Vec<Vec<CachedValue>>>;
+
+/// A cell of a cached row. The numbers are kept as the text Oracle converts them to, so that they
+/// can be produced as any of the types the parser supports without losing precision.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CachedValue {
+    Null,
+    Text(String),
+    Float(f64),
+    Bytes(Vec<u8>),
+    DateTime(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+}
+
+impl CachedValue {
+    /// The estimated size of the value in memory, in bytes.
+    pub fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + match self {
+                CachedValue::Text(s) => s.len(),
+                CachedValue::Bytes(b) => b.len(),
+                _ => 0,
+            }
+    }
+}
+
+/// A value that can be produced from a [`CachedValue`], `None` for [`CachedValue::Null`].
+pub trait FromCached: Sized {
+    fn from_cached(value: &CachedValue) -> Result<Option<Self>, OracleSourceError>;
+}
+
+macro_rules! impl_from_cached_text {
+    ($($t: ty,)+) => {
+        $(
+            impl FromCached for $t {
+                #[throws(OracleSourceError)]
+                fn from_cached(value: &CachedValue) -> Option<$t> {
+                    match value {
+                        CachedValue::Null => None,
+                        CachedValue::Text(s) => Some(<$t>::from_str(s).map_err(|_| {
+                            anyhow!("cannot parse '{}' as {}", s, std::any::type_name::<$t>())
+                        })?),
+                        _ => throw!(mismatch::<$t>(value)),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_from_cached_text!(i64, u64,);
+
+fn mismatch<T>(value: &CachedValue) -> OracleSourceError {
+    anyhow!(
+        "cannot produce {} from the cached value {:?}",
+        std::any::type_name::<T>(),
+        value
+    )
+    .into()
+}
+
+impl FromCached for f64 {
+    #[throws(OracleSourceError)]
+    fn from_cached(value: &CachedValue) -> Option<f64> {
+        match value {
+            CachedValue::Null => None,
+            CachedValue::Float(v) => Some(*v),
+            CachedValue::Text(s) => {
+                Some(f64::from_str(s).map_err(|_| anyhow!("cannot parse '{}' as f64", s))?)
+            }
+            _ => throw!(mismatch::<f64>(value)),
+        }
+    }
+}
+
+impl FromCached for String {
+    #[throws(OracleSourceError)]
+    fn from_cached(value: &CachedValue) -> Option<String> {
+        match value {
+            CachedValue::Null => None,
+            CachedValue::Text(s) => Some(s.clone()),
+            _ => throw!(mismatch::<String>(value)),
+        }
+    }
+}
+
+impl FromCached for Vec<u8> {
+    #[throws(OracleSourceError)]
+    fn from_cached(value: &CachedValue) -> Option<Vec<u8>> {
+        match value {
+            CachedValue::Null => None,
+            CachedValue::Bytes(b) => Some(b.clone()),
+            _ => throw!(mismatch::<Vec<u8>>(value)),
+        }
+    }
+}
+
+impl FromCached for NaiveDateTime {
+    #[throws(OracleSourceError)]
+    fn from_cached(value: &CachedValue) -> Option<NaiveDateTime> {
+        match value {
+            CachedValue::Null => None,
+            CachedValue::DateTime(dt) => Some(*dt),
+            CachedValue::TimestampTz(ts) => Some(ts.naive_utc()),
+            _ => throw!(mismatch::<NaiveDateTime>(value)),
+        }
+    }
+}
+
+impl FromCached for NaiveDate {
+    #[throws(OracleSourceError)]
+    fn from_cached(value: &CachedValue) -> Option<NaiveDate> {
+        NaiveDateTime::from_cached(value)?.map(|dt| dt.date())
+    }
+}
+
+impl FromCached for DateTime<Utc> {
+    #[throws(OracleSourceError)]
+    fn from_cached(value: &CachedValue) -> Option<DateTime<Utc>> {
+        match value {
+            CachedValue::Null => None,
+            CachedValue::TimestampTz(ts) => Some(*ts),
+            CachedValue::DateTime(dt) => Some(DateTime::from_utc(*dt, Utc)),
+            _ => throw!(mismatch::<DateTime<Utc>>(value)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    rows: CachedRows,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// By context and query text
+    entries: HashMap<(String, String), CacheEntry>,
+    clock: u64,
+    hits: u64,
+}
+
+/// A least recently used cache of the rows of queries, each kept for `ttl`. Only the queries with
+/// at most `max_rows` rows and `max_bytes` bytes (estimated) are cached. A query is cached along
+/// with its context, everything else its rows depend on, e.g. the database it was run on: the
+/// same query in another context is another entry. The cache is meant to be
+/// shared, in an `Arc`, by the sources of the loads reading the same queries, see
+/// [`super::OracleSource::with_query_cache`].
+#[derive(Debug)]
+pub struct QueryCache {
+    pub capacity: usize,
+    pub ttl: Duration,
+    pub max_rows: usize,
+    pub max_bytes: usize,
+    state: Mutex<CacheState>,
+}
+
+impl QueryCache {
+    /// A cache of up to `capacity` queries, each kept for `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            max_rows: DEFAULT_CACHE_MAX_ROWS,
+            max_bytes: DEFAULT_CACHE_MAX_BYTES,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// The rows of `query` in `context` if they are cached and have not expired.
+    pub fn get(&self, context: &str, query: &str) -> Option<CachedRows> {
+        let key = (context.to_string(), query.to_string());
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+        let rows = match state.entries.get_mut(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                entry.last_used = clock;
+                entry.rows.clone()
+            }
+            Some(_) => {
+                state.entries.remove(&key);
+                return None;
+            }
+            None => return None,
+        };
+        state.hits += 1;
+        Some(rows)
+    }
+
+    /// Whether rows of `nrows` rows and `nbytes` bytes can be cached.
+    pub fn fits(&self, nrows: usize, nbytes: usize) -> bool {
+        nrows <= self.max_rows && nbytes <= self.max_bytes
+    }
+
+    /// Cache the rows of `query` in `context`, evicting the least recently used queries beyond the
+    /// capacity.
+    pub fn insert(&self, context: &str, query: &str, rows: Vec<Vec<CachedValue>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let entry = CacheEntry {
+            rows: Arc::new(rows),
+            expires_at: Instant::now() + self.ttl,
+            last_used: state.clock,
+        };
+        state
+            .entries
+            .insert((context.to_string(), query.to_string()), entry);
+        while state.entries.len() > self.capacity {
+            let lru = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+                .unwrap();
+            state.entries.remove(&lru);
+        }
+    }
+
+    /// The number of cached queries, including the expired ones not looked up since.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of lookups served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.state.lock().unwrap().hits
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+}
@@ -18,6 +18,42 @@ pub enum OracleSourceError {
     #[error(transparent)]
     OracleUrlDecodeError(#[from] FromUtf8Error),
 
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
+    #[error("cannot parse the result of a query without columns, was the metadata fetched?")]
+    EmptySchema,
+
+    #[error("consumer group {0} does not exist or cannot be switched to")]
+    InvalidConsumerGroup(String),
+
+    #[error(
+        "ORA-01406: a fetched value was truncated, in one of the columns {0}; \
+         cast the column to a wider type (e.g. CAST(col AS VARCHAR2(4000 CHAR))) or to CLOB"
+    )]
+    ValueTruncated(String),
+
+    #[error("authentication failed: {0}")]
+    AuthenticationFailed(String),
+
+    #[error("no listener at the host and port of the connection string: {0}")]
+    NoListener(String),
+
+    #[error("the service of the connection string cannot be resolved: {0}")]
+    UnresolvedService(String),
+
+    #[error("restore point {0} does not exist")]
+    UnknownRestorePoint(String),
+
+    #[error("{0} cannot be narrowed to {1} decimal places without truncating it")]
+    DecimalTruncation(rust_decimal::Decimal, u32),
+
+    #[error("the plan hash {2} of statement {0} differs from the baseline {1}")]
+    PlanChanged(String, u64, u64),
+
+    #[error("{0} does not fit in a Decimal128 of precision 38")]
+    Decimal128Overflow(String),
+
     /// Any other errors that are too trivial to be put here explicitly.
     #[error(transparent)]
     Other(#[from] anyhow::Error),
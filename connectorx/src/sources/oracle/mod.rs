@@ -1,33 +1,145 @@
+mod cache;
 mod errors;
+mod session;
 mod typesystem;
 
+use self::cache::FromCached;
+pub use self::cache::{
+    CachedRows, CachedValue, QueryCache, DEFAULT_CACHE_MAX_BYTES, DEFAULT_CACHE_MAX_ROWS,
+};
 pub use self::errors::OracleSourceError;
-pub use self::typesystem::OracleTypeSystem;
-use crate::constants::{DB_BUFFER_SIZE, ORACLE_ARRAY_SIZE};
+use self::session::{Session, SessionManager, SessionSettings, Setting};
+pub use self::session::{DEFAULT_ACTION_NAME, DEFAULT_MODULE_NAME};
+pub use self::typesystem::{
+    default_type_widths, ColumnDescriptor, Iso8601Format, LobStorage, OracleTypeSystem,
+    TimeZoneSuffix,
+};
+use crate::constants::{DB_BUFFER_SIZE, DECIMAL_PRECISION, DECIMAL_SCALE, ORACLE_ARRAY_SIZE};
+#[cfg(feature = "dst_arrow")]
+use crate::sources::{
+    table_scan::TableScan,
+    writable::{chunks, matched_count_query, merge_query_oracle, MergeStats, WritableSource},
+};
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
-    sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, limit1_query_oracle, CXQuery},
-    utils::DummyBox,
+    sources::{
+        probe_health,
+        row_errors::{RowError, RowErrorPolicy, RowErrors},
+        stats::{ColumnStat, ColumnStats, OptimizerStats},
+        HealthStatus, PartitionParser, Produce, Source, SourcePartition,
+    },
+    sql::{
+        bounds_partition_query_oracle, count_query, count_query_oracle, dblink_query_oracle,
+        flashback_query_oracle, label_query, limit1_query_oracle, lob_prefetch_query_oracle,
+        monitoring_query_oracle, null_partition, order_query_oracle, parallel_query_oracle,
+        rowscn_query_oracle, score_columns_oracle, single_table, tail_query_oracle, AliasGenerator,
+        CXQuery, NullsOrder, PartitionBound,
+    },
+    typesystem::{ParameterizedFunc, ParameterizedOn, Realize},
+    utils::{Collector, DummyBox},
 };
+use anyhow::anyhow;
+#[cfg(feature = "dst_arrow")]
+use arrow::record_batch::RecordBatch;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use fehler::{throw, throws};
-use log::debug;
+use log::{debug, warn};
 use owning_ref::OwningHandle;
 use r2d2::{Pool, PooledConnection};
 use r2d2_oracle::oracle::ResultSet;
 use r2d2_oracle::{
-    oracle::{Connector, Row, Statement},
+    oracle::{
+        sql_type::{FromSql, ObjectType, OracleType, RefCursor, ToSql},
+        ColumnInfo, Connector, Row, SqlValue, Statement, Version,
+    },
     OracleConnectionManager,
 };
+use rust_decimal::{Decimal, RoundingStrategy};
 use sqlparser::dialect::Dialect;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 use url::Url;
 use urlencoding::decode;
 
-type OracleManager = OracleConnectionManager;
+type OracleManager = SessionManager;
 type OracleConn = PooledConnection<OracleManager>;
 
+/// Read a value of an object type as text, see [`OracleSource::register_type_handler`].
+pub type TypeHandler = dyn Fn(&SqlValue) -> Result<String, OracleSourceError> + Send + Sync;
+
+/// Oracle error code of "resource busy and acquire with NOWAIT specified or timeout expired".
+const ORA_RESOURCE_BUSY: i32 = 54;
+/// Oracle error code of "consumer group does not exist".
+const ORA_NO_CONSUMER_GROUP: i32 = 29368;
+/// Oracle error code of "user has not been granted privilege to switch to consumer group".
+const ORA_CONSUMER_GROUP_PRIVILEGE: i32 = 29396;
+/// Oracle error code of "fetched column value was truncated".
+const ORA_VALUE_TRUNCATED: i32 = 1406;
+/// Oracle error code of "invalid username/password; logon denied".
+const ORA_INVALID_LOGON: i32 = 1017;
+/// Oracle error code of "TNS:could not resolve the connect identifier specified".
+const ORA_TNS_UNRESOLVED: i32 = 12154;
+/// Oracle error code of "TNS:no listener".
+const ORA_TNS_NO_LISTENER: i32 = 12541;
+/// The maximum length in bytes of the identifiers before Oracle 12.2, and since.
+const ORA_IDENTIFIER_LIMIT_LEGACY: usize = 30;
+const ORA_IDENTIFIER_LIMIT: usize = 128;
+/// The maximum length in characters of the CLOB values fetched inline, see
+/// [`OracleSource::lob_prefetch`]: the 4000 bytes of a `VARCHAR2` in SQL, in characters of up to 4 bytes.
+const MAX_LOB_PREFETCH_CHARS: u32 = 1000;
+
+/// The optimizer statistics of a column, with the `RAW` low and high values decoded as text for
+/// the numeric and character types. The date and timestamp ones are returned as they are and
+/// decoded by [`decode_date`]; those of the other types are not decoded.
+const OPTIMIZER_STATS_QUERY: &str = "\
+SELECT c.data_type, s.num_distinct, s.num_nulls,
+       CASE
+         WHEN c.data_type IN ('NUMBER', 'FLOAT') THEN TO_CHAR(UTL_RAW.CAST_TO_NUMBER(s.low_value))
+         WHEN c.data_type = 'BINARY_DOUBLE' THEN TO_CHAR(UTL_RAW.CAST_TO_BINARY_DOUBLE(s.low_value))
+         WHEN c.data_type = 'BINARY_FLOAT' THEN TO_CHAR(UTL_RAW.CAST_TO_BINARY_FLOAT(s.low_value))
+         WHEN c.data_type IN ('VARCHAR2', 'CHAR') THEN UTL_RAW.CAST_TO_VARCHAR2(s.low_value)
+         WHEN c.data_type IN ('NVARCHAR2', 'NCHAR') THEN TO_CHAR(UTL_RAW.CAST_TO_NVARCHAR2(s.low_value))
+       END,
+       CASE
+         WHEN c.data_type IN ('NUMBER', 'FLOAT') THEN TO_CHAR(UTL_RAW.CAST_TO_NUMBER(s.high_value))
+         WHEN c.data_type = 'BINARY_DOUBLE' THEN TO_CHAR(UTL_RAW.CAST_TO_BINARY_DOUBLE(s.high_value))
+         WHEN c.data_type = 'BINARY_FLOAT' THEN TO_CHAR(UTL_RAW.CAST_TO_BINARY_FLOAT(s.high_value))
+         WHEN c.data_type IN ('VARCHAR2', 'CHAR') THEN UTL_RAW.CAST_TO_VARCHAR2(s.high_value)
+         WHEN c.data_type IN ('NVARCHAR2', 'NCHAR') THEN TO_CHAR(UTL_RAW.CAST_TO_NVARCHAR2(s.high_value))
+       END,
+       s.low_value, s.high_value, s.avg_col_len
+FROM all_tab_columns c
+LEFT JOIN all_tab_col_statistics s
+  ON s.owner = c.owner AND s.table_name = c.table_name AND s.column_name = c.column_name
+WHERE c.owner = NVL(:1, USER) AND c.table_name = :2 AND c.column_name = :3";
+
+/// The number of rows of a table as of its last statistics gathering.
+const TABLE_ROWS_QUERY: &str =
+    "SELECT num_rows FROM all_tables WHERE owner = NVL(:1, USER) AND table_name = :2";
+
+/// The maximum attempts of [`OracleSource::lock_retry`], so that a lock which is never released
+/// cannot keep a query retrying forever.
+pub const MAX_LOCK_RETRY_ATTEMPTS: u32 = 10;
+
+/// The statement last executed by the session and its plan, i.e. the query of a partition when
+/// run right after it was executed.
+const SQL_PLAN_QUERY: &str = "\
+SELECT q.sql_id, q.plan_hash_value
+FROM v$session s
+JOIN v$sql q ON q.sql_id = s.prev_sql_id AND q.child_number = s.prev_child_number
+WHERE s.sid = SYS_CONTEXT('USERENV', 'SID')";
+
+/// The default estimate above which [`OracleSource::estimated_memory_usage`] logs a warning, 1 GiB.
+pub const DEFAULT_MEMORY_WARNING_THRESHOLD: usize = 1 << 30;
+
 #[derive(Debug)]
 pub struct OracleDialect {}
 
@@ -49,8 +161,209 @@ pub struct OracleSource {
     pool: Pool<OracleManager>,
     origin_query: Option<String>,
     queries: Vec<CXQuery<String>>,
+    label: Option<String>,
     names: Vec<String>,
     schema: Vec<OracleTypeSystem>,
+    descriptors: Vec<ColumnDescriptor>,
+    tail: Option<(String, Option<usize>)>,
+    metadata_probe: bool,
+    decimal_scale: Option<u32>,
+    decimal_rounding: RoundingStrategy,
+    lob_prefetch: Option<u32>,
+    session: Arc<RwLock<Session>>,
+    /// The connection string without the password, to key the query cache with
+    identity: String,
+    lock_retry: Option<(u32, Duration)>,
+    count_timeout: Option<Duration>,
+    fetch_timeout: Option<Duration>,
+    explain_plan: Option<Option<u64>>,
+    plan_estimates: Vec<PlanEstimate>,
+    /// The statements of the partitions, and the plan hash they must have
+    sql_plans: Option<(SqlPlans, Option<u64>)>,
+    adaptive_buf_size: Option<AdaptiveBufSize>,
+    column_stats: Option<ColumnStats>,
+    fetch_comments: bool,
+    comments: Option<Comments>,
+    fetch_lob_storage: bool,
+    flashback: Option<String>,
+    parallel_degree: Option<usize>,
+    parallel_order: Vec<String>,
+    order_key: Vec<String>,
+    nulls: Option<NullsOrder>,
+    bounds: Option<(String, Vec<PartitionBound>)>,
+    strict: bool,
+    include_rowscn: bool,
+    rowscn_added: bool,
+    query_cache: Option<Arc<QueryCache>>,
+    refcursor: bool,
+    iso8601: Option<Iso8601Format>,
+    decimal128: bool,
+    skip_count: Option<bool>,
+    money: Vec<(String, u32)>,
+    row_errors: Option<RowErrors>,
+    /// By upper case type name, qualified or not
+    type_handlers: HashMap<String, Arc<TypeHandler>>,
+    memory_warning_threshold: usize,
+}
+
+/// The catalog comments of the table read by the query, see [`OracleSource::fetch_comments`].
+#[derive(Debug, Clone, Default)]
+struct Comments {
+    table: Option<String>,
+    columns: HashMap<String, String>,
+}
+
+/// The optimizer estimates of a partition query, from `EXPLAIN PLAN`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanEstimate {
+    pub query: String,
+    pub cardinality: Option<u64>,
+    pub cost: Option<u64>,
+}
+
+/// The statement executed by a partition, from `V$SQL`, see [`OracleSource::capture_sql_plans`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlPlan {
+    pub partition: usize,
+    pub sql_id: String,
+    pub plan_hash: u64,
+}
+
+/// The statements executed by the partitions of a source.
+#[derive(Debug, Clone, Default)]
+pub struct SqlPlans {
+    plans: Collector<SqlPlan>,
+}
+
+impl SqlPlans {
+    /// Record the statement of a partition, replacing the one of an earlier execution.
+    fn record(&self, plan: SqlPlan) {
+        self.plans.update(|plans| {
+            plans.retain(|other| other.partition != plan.partition);
+            plans.push(plan);
+        });
+    }
+
+    /// The statements recorded so far, ordered by partition.
+    pub fn get(&self) -> Vec<SqlPlan> {
+        let mut plans = self.plans.get();
+        plans.sort_by_key(|plan| plan.partition);
+        plans
+    }
+}
+
+/// A `NUMBER` value read as an `i64` when it fits, and as its text otherwise (too large, or not an
+/// integer), so that a mostly small integer column keeps its rare huge values exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntOrText {
+    Int(i64),
+    Text(String),
+}
+
+impl From<String> for IntOrText {
+    fn from(s: String) -> Self {
+        match s.parse() {
+            Ok(v) => IntOrText::Int(v),
+            Err(_) => IntOrText::Text(s),
+        }
+    }
+}
+
+/// Sizes the fetch buffer of a query to a memory budget, from the estimated size of its rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveBufSize {
+    pub target_bytes: usize,
+}
+
+impl AdaptiveBufSize {
+    pub fn new(target_mb: usize) -> Self {
+        Self {
+            target_bytes: target_mb << 20,
+        }
+    }
+
+    /// The estimated size of a value of the column in the fetch buffer, in bytes.
+    pub fn column_bytes(col: &ColumnDescriptor) -> usize {
+        match col.type_system {
+            // NUMBER takes up to 22 bytes
+            OracleTypeSystem::NumInt(_)
+            | OracleTypeSystem::Float(_)
+            | OracleTypeSystem::NumFloat(_)
+            | OracleTypeSystem::Decimal(_) => 22,
+            OracleTypeSystem::BinaryFloat(_) => 4,
+            OracleTypeSystem::BinaryDouble(_) => 8,
+            OracleTypeSystem::Date(_) => 7,
+            OracleTypeSystem::Timestamp(_) | OracleTypeSystem::TimestampTz(_) => 13,
+            OracleTypeSystem::VarChar(_) | OracleTypeSystem::Char(_) => {
+                col.length.unwrap_or(4000) as usize
+            }
+            // national characters are up to 2 bytes in AL16UTF16
+            OracleTypeSystem::NVarChar(_) | OracleTypeSystem::NChar(_) => {
+                2 * col.length.unwrap_or(2000) as usize
+            }
+            OracleTypeSystem::Blob(_) | OracleTypeSystem::Clob(_) => 4000,
+        }
+    }
+
+    /// The number of rows to fetch per round trip: the memory budget divided by the estimated
+    /// size of a row of `columns`.
+    pub fn rows(&self, columns: &[ColumnDescriptor]) -> u32 {
+        self.rows_for_bytes(columns.iter().map(Self::column_bytes).sum())
+    }
+
+    /// The number of rows of `row_bytes` bytes to fetch per round trip.
+    pub fn rows_for_bytes(&self, row_bytes: usize) -> u32 {
+        (self.target_bytes / row_bytes.max(1)).clamp(1, u32::MAX as usize) as u32
+    }
+}
+
+/// Run `f` with the call timeout of `conn` set to `timeout`, and reset it afterwards. Nothing is
+/// set for `None`, since the call timeout needs Oracle client 18 or later.
+#[throws(OracleSourceError)]
+fn with_call_timeout<T, F>(conn: &OracleConn, timeout: Option<Duration>, f: F) -> T
+where
+    F: FnOnce() -> Result<T, OracleSourceError>,
+{
+    if timeout.is_none() {
+        return f()?;
+    }
+    conn.set_call_timeout(timeout)?;
+    let result = f();
+    conn.set_call_timeout(None)?;
+    result?
+}
+
+/// The optimizer statistics of the column `column_name` of `owner.table` (names as stored in the
+/// catalog) and the average size of its values in bytes, `None` if the table has no such column.
+#[throws(OracleSourceError)]
+fn read_optimizer_stats(
+    conn: &OracleConn,
+    owner: Option<&str>,
+    table: &str,
+    column_name: &str,
+) -> Option<(OptimizerStats, Option<f64>)> {
+    let row = match conn.query_row(OPTIMIZER_STATS_QUERY, &[&owner, &table, &column_name]) {
+        Ok(row) => row,
+        Err(oracle::Error::NoDataFound) => return None,
+        Err(e) => throw!(e),
+    };
+    let data_type: String = row.get(0)?;
+    let (low, high) = match data_type.as_str() {
+        "DATE" => (row.get::<_, Option<Vec<u8>>>(5)?, row.get(6)?),
+        ty if ty.starts_with("TIMESTAMP") => (row.get(5)?, row.get(6)?),
+        _ => (None, None),
+    };
+    let stats = OptimizerStats {
+        num_distinct: row.get(1)?,
+        nulls: row.get(2)?,
+        low: row
+            .get::<_, Option<String>>(3)?
+            .or_else(|| low.and_then(decode_date)),
+        high: row
+            .get::<_, Option<String>>(4)?
+            .or_else(|| high.and_then(decode_date)),
+    };
+    Some((stats, row.get(7)?))
 }
 
 #[throws(OracleSourceError)]
@@ -70,23 +383,1550 @@ pub fn connect_oracle(conn: &Url) -> Connector {
     connector
 }
 
+/// Run `f`, retrying it with exponential backoff while it fails with ORA-00054, see
+/// [`OracleSource::lock_retry`]. `lock_retry` is the maximum number of attempts and the first
+/// backoff, `None` runs `f` once.
+pub fn retry_on_resource_busy<T, F>(
+    lock_retry: Option<(u32, Duration)>,
+    mut f: F,
+) -> Result<T, oracle::Error>
+where
+    F: FnMut() -> Result<T, oracle::Error>,
+{
+    let (max_attempts, mut backoff) = match lock_retry {
+        Some(retry) => retry,
+        None => return f(),
+    };
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(oracle::Error::OciError(e))
+                if e.code() == ORA_RESOURCE_BUSY && attempt < max_attempts =>
+            {
+                debug!(
+                    "resource busy, retry {}/{} in {:?}",
+                    attempt,
+                    max_attempts - 1,
+                    backoff
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// A statement owning the result set of its query.
+type StatementRows<'a> = OwningHandle<Box<Statement<'a>>, DummyBox<ResultSet<'a, Row>>>;
+
+/// Query a statement built by `build`, retrying with a new statement while it fails with ORA-00054.
+fn query_with_lock_retry<'a, F>(
+    lock_retry: Option<(u32, Duration)>,
+    mut build: F,
+) -> Result<StatementRows<'a>, oracle::Error>
+where
+    F: FnMut() -> Result<Statement<'a>, oracle::Error>,
+{
+    retry_on_resource_busy(lock_retry, || {
+        OwningHandle::try_new(Box::new(build()?), |stmt: *const Statement<'a>| unsafe {
+            (*(stmt as *mut Statement<'_>)).query(&[]).map(DummyBox)
+        })
+    })
+}
+
+/// Get the names and types of the columns of `query` from its description, executing it without
+/// fetching any row.
+#[throws(OracleSourceError)]
+fn describe_query(
+    conn: &OracleConn,
+    query: &str,
+    lock_retry: Option<(u32, Duration)>,
+) -> (Vec<String>, Vec<OracleTypeSystem>, Vec<ColumnDescriptor>) {
+    let rows = query_with_lock_retry(lock_retry, || {
+        conn.statement(query).prefetch_rows(0).build()
+    })?;
+    describe_columns(rows.column_info())
+}
+
+/// Get the names and types of the columns of the cursor opened by the PL/SQL `block`, see
+/// [`open_refcursor`].
+#[throws(OracleSourceError)]
+fn describe_refcursor(
+    conn: &OracleConn,
+    block: &str,
+) -> (Vec<String>, Vec<OracleTypeSystem>, Vec<ColumnDescriptor>) {
+    let mut cursor = open_refcursor(conn, block)?;
+    let rows = cursor.query()?;
+    describe_columns(rows.column_info())
+}
+
+fn describe_columns(
+    columns: &[ColumnInfo],
+) -> (Vec<String>, Vec<OracleTypeSystem>, Vec<ColumnDescriptor>) {
+    let (names, types) = columns
+        .iter()
+        .map(|col| {
+            (
+                col.name().to_string(),
+                OracleTypeSystem::from(col.oracle_type()),
+            )
+        })
+        .unzip();
+    let descriptors = columns.iter().map(ColumnDescriptor::from).collect();
+    (names, types, descriptors)
+}
+
+/// The error of fetching a row of a result with the columns `names` of types `schema`. OCI sizes
+/// the fetch buffers from the description of the columns, and raises ORA-01406 when a value does
+/// not fit (e.g. after a character set conversion) without telling which column it is, so
+/// [`OracleSourceError::ValueTruncated`] names the variable-width character and binary columns,
+/// the ones that can overflow their buffer.
+pub fn fetch_error(
+    e: oracle::Error,
+    names: &[String],
+    schema: &[OracleTypeSystem],
+) -> OracleSourceError {
+    match &e {
+        oracle::Error::OciError(err) if err.code() == ORA_VALUE_TRUNCATED => {
+            let columns = schema
+                .iter()
+                .enumerate()
+                .filter(|(_, ty)| {
+                    matches!(
+                        ty,
+                        OracleTypeSystem::VarChar(_)
+                            | OracleTypeSystem::Char(_)
+                            | OracleTypeSystem::NVarChar(_)
+                            | OracleTypeSystem::NChar(_)
+                            | OracleTypeSystem::Blob(_)
+                    )
+                })
+                .map(|(i, _)| names.get(i).cloned().unwrap_or_else(|| format!("#{}", i)))
+                .collect::<Vec<_>>();
+            OracleSourceError::ValueTruncated(columns.join(", "))
+        }
+        _ => e.into(),
+    }
+}
+
+/// The maximum length in bytes of the identifiers of a server of `version`: 30 before 12.2, 128
+/// since, e.g. to size the aliases of the rewrites with [`crate::sql::AliasGenerator`].
+pub fn identifier_limit(version: &Version) -> usize {
+    match (version.major(), version.minor()) {
+        (major, minor) if major > 12 || (major == 12 && minor >= 2) => ORA_IDENTIFIER_LIMIT,
+        _ => ORA_IDENTIFIER_LIMIT_LEGACY,
+    }
+}
+
+/// Parse the text of a `NUMBER` into the mantissa and the scale of an Arrow `Decimal128` value,
+/// e.g. `(123450, 4)` for `12.345` with a `scale` of 4: `scale` is the one of the `NUMBER(p,s)`
+/// column, or `None` to keep the scale of the text itself. The mantissa must fit in 38 digits.
+/// The [`OracleTypeSystem::Decimal`] columns are read this way, see
+/// [`OracleSource::numbers_as_decimal128`].
+#[throws(OracleSourceError)]
+pub fn decimal128(s: &str, scale: Option<i8>) -> (i128, i8) {
+    let invalid = || anyhow!("cannot parse '{}' as Decimal128", s);
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (number, exponent) = match unsigned.find(|c| c == 'e' || c == 'E') {
+        Some(i) => (
+            &unsigned[..i],
+            unsigned[i + 1..].parse::<i32>().map_err(|_| invalid())?,
+        ),
+        None => (unsigned, 0),
+    };
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    if (int.is_empty() && frac.is_empty())
+        || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+    {
+        throw!(invalid());
+    }
+
+    let overflow = || OracleSourceError::Decimal128Overflow(s.to_string());
+    // no value beyond these exponents fits, reject them before padding the digits with zeros
+    if exponent.unsigned_abs() as usize > DECIMAL_PRECISION + int.len() + frac.len() {
+        throw!(overflow());
+    }
+
+    let mut digits = format!("{}{}", int, frac);
+    let natural = i32::try_from(frac.len())
+        .ok()
+        .and_then(|len| len.checked_sub(exponent))
+        .ok_or_else(overflow)?;
+    let target = scale.map_or(natural.max(0), i32::from);
+    if target >= natural {
+        let padding = usize::try_from(target - natural).map_err(|_| overflow())?;
+        digits.extend(std::iter::repeat('0').take(padding));
+    } else {
+        // the value has already been rounded to the scale of its column by Oracle, only zeros are
+        // dropped, e.g. for the negative scales
+        let kept = digits.len().saturating_sub((natural - target) as usize);
+        if digits[kept..].bytes().any(|b| b != b'0') {
+            throw!(anyhow!("{} has more than {} decimal places", s, target));
+        }
+        digits.truncate(kept);
+    }
+    let significant = digits.trim_start_matches('0');
+    if significant.len() > DECIMAL_PRECISION || target > DECIMAL_PRECISION as i32 {
+        throw!(overflow());
+    }
+    let mantissa = match significant.is_empty() {
+        true => 0,
+        false => significant.parse::<i128>().map_err(|_| invalid())?,
+    };
+    (if negative { -mantissa } else { mantissa }, target as i8)
+}
+
+/// Decode the internal representation of a `DATE`, the first 7 bytes of a `TIMESTAMP`, e.g. the
+/// `LOW_VALUE` of its optimizer statistics: the century and the year of the century in excess 100,
+/// the month, the day, and the hour, the minute and the second in excess 1.
+fn decode_date(raw: Vec<u8>) -> Option<String> {
+    match raw.as_slice() {
+        [century, year, month, day, hour, minute, second, ..] => {
+            let year = (*century as i32 - 100) * 100 + (*year as i32 - 100);
+            let date = NaiveDate::from_ymd_opt(year, *month as u32, *day as u32)?;
+            let dt = date.and_hms_opt(
+                hour.checked_sub(1)? as u32,
+                minute.checked_sub(1)? as u32,
+                second.checked_sub(1)? as u32,
+            )?;
+            Some(dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The error of opening or using a connection, with the common failures of a connection string
+/// told apart.
+fn connection_error(e: oracle::Error) -> OracleSourceError {
+    match &e {
+        oracle::Error::OciError(err) => match err.code() {
+            ORA_INVALID_LOGON => OracleSourceError::AuthenticationFailed(err.message().to_string()),
+            ORA_TNS_NO_LISTENER => OracleSourceError::NoListener(err.message().to_string()),
+            ORA_TNS_UNRESOLVED => OracleSourceError::UnresolvedService(err.message().to_string()),
+            _ => e.into(),
+        },
+        _ => e.into(),
+    }
+}
+
+/// Execute the PL/SQL `block`, which opens a `SYS_REFCURSOR` into its first bind parameter, e.g.
+/// `begin :1 := my_function(); end;` or `begin open :1 for select ...; end;`, and get the cursor.
+#[throws(OracleSourceError)]
+fn open_refcursor(conn: &OracleConn, block: &str) -> RefCursor {
+    let mut stmt = conn.statement(block).build()?;
+    stmt.execute(&[&OracleType::RefCursor])?;
+    stmt.bind_value(1)?
+}
+
 impl OracleSource {
+    /// Open a single connection to `conn`, outside of any pool, and return the round trip time of
+    /// `SELECT SYSDATE FROM DUAL` on it, e.g. to check a connection string before a load. The
+    /// common failures have their own errors: [`OracleSourceError::AuthenticationFailed`] (ORA-01017),
+    /// [`OracleSourceError::NoListener`] (ORA-12541) and [`OracleSourceError::UnresolvedService`]
+    /// (ORA-12154).
+    #[throws(OracleSourceError)]
+    pub fn test_connection(conn: &str) -> Duration {
+        let connector = connect_oracle(&Url::parse(conn)?)?;
+        let conn = connector.connect().map_err(connection_error)?;
+        let start = Instant::now();
+        conn.query_row_as::<NaiveDateTime>("SELECT SYSDATE FROM DUAL", &[])
+            .map_err(connection_error)?;
+        let elapsed = start.elapsed();
+        debug!("Round trip to the database in {:?}", elapsed);
+        elapsed
+    }
+
+    /// The maximum length in bytes of the identifiers on the server, see [`identifier_limit`].
+    #[throws(OracleSourceError)]
+    pub fn identifier_limit(&self) -> usize {
+        let (version, _) = self.get_conn()?.server_version()?;
+        identifier_limit(&version)
+    }
+
+    /// A generator of the aliases of the rewrites of a query, sized for the server.
+    #[throws(OracleSourceError)]
+    fn alias_generator(&self) -> AliasGenerator {
+        AliasGenerator::new(self.identifier_limit()?)?
+    }
+
     #[throws(OracleSourceError)]
     pub fn new(conn: &str, nconn: usize) -> Self {
         let conn = Url::parse(conn)?;
+        let mut identity = conn.clone();
+        let _ = identity.set_password(None);
         let connector = connect_oracle(&conn)?;
-        let manager = OracleConnectionManager::from_connector(connector);
+        let session = Arc::new(RwLock::new(Session::default()));
+        let manager = SessionManager::new(
+            OracleConnectionManager::from_connector(connector),
+            session.clone(),
+        );
         let pool = r2d2::Pool::builder()
             .max_size(nconn as u32)
             .build(manager)?;
 
-        Self {
-            pool,
-            origin_query: None,
-            queries: vec![],
-            names: vec![],
-            schema: vec![],
+        Self {
+            pool,
+            origin_query: None,
+            queries: vec![],
+            label: None,
+            names: vec![],
+            schema: vec![],
+            descriptors: vec![],
+            tail: None,
+            metadata_probe: true,
+            decimal_scale: None,
+            decimal_rounding: RoundingStrategy::MidpointNearestEven,
+            lob_prefetch: None,
+            session,
+            identity: identity.to_string(),
+            lock_retry: None,
+            count_timeout: None,
+            fetch_timeout: None,
+            explain_plan: None,
+            plan_estimates: vec![],
+            sql_plans: None,
+            adaptive_buf_size: None,
+            column_stats: None,
+            fetch_comments: false,
+            comments: None,
+            fetch_lob_storage: false,
+            flashback: None,
+            parallel_degree: None,
+            parallel_order: vec![],
+            order_key: vec![],
+            nulls: None,
+            bounds: None,
+            strict: false,
+            include_rowscn: false,
+            rowscn_added: false,
+            query_cache: None,
+            refcursor: false,
+            iso8601: None,
+            decimal128: false,
+            skip_count: None,
+            money: vec![],
+            row_errors: None,
+            type_handlers: HashMap::new(),
+            memory_warning_threshold: DEFAULT_MEMORY_WARNING_THRESHOLD,
+        }
+    }
+
+    /// A source whose queries are PL/SQL blocks returning a `SYS_REFCURSOR` in their first bind
+    /// parameter instead of `SELECT` statements, e.g. `begin :1 := my_function(); end;`, so that
+    /// the result sets of the functions and procedures returning cursors can be read. Each block
+    /// is one partition. The blocks are executed as they are: they are not labelled, and the
+    /// rewrites of the queries (flashback, tail, LOB prefetch, parallel hint...) are not applied.
+    /// Counting the rows of a partition executes its block and reads the cursor through.
+    #[throws(OracleSourceError)]
+    pub fn from_sys_refcursor(conn: &str, cursor_query: &str) -> Self {
+        let mut source = Self::new(conn, 1)?;
+        source.refcursor = true;
+        source.set_queries(&[CXQuery::naked(cursor_query)]);
+        source
+    }
+
+    /// Establish all the connections of the pool before partitioning, including those the pool
+    /// dropped since it was built (e.g. broken ones), so that no partition pays the connection
+    /// setup and a failing session setting is reported before any query runs.
+    /// [`OracleSource::pool_state`] then reports `nconn` connections.
+    #[throws(OracleSourceError)]
+    pub fn prewarm(&self) {
+        let n = self.pool.max_size() as usize;
+        let conns = (0..n)
+            .map(|_| self.get_conn())
+            .collect::<Result<Vec<_>, _>>()?;
+        debug!("Prewarmed {} oracle connections", conns.len());
+    }
+
+    /// The number of connections established by the pool, and how many of them are idle.
+    pub fn pool_state(&self) -> (u32, u32) {
+        let state = self.pool.state();
+        (state.connections, state.idle_connections)
+    }
+
+    /// Read the Oracle Text relevance scores, the `SCORE(label)` columns of the query (see
+    /// [`score_columns_oracle`]), as `f64`, whatever `NUMBER` type they are described with.
+    fn map_score_types(&mut self) {
+        let query = match &self.origin_query {
+            Some(q) => q.clone(),
+            None => match self.queries.first() {
+                Some(q) => q.to_string(),
+                None => return,
+            },
+        };
+        let scores = score_columns_oracle(&query);
+        for (i, name) in self.names.iter().enumerate() {
+            if scores.contains(name) {
+                self.schema[i] = OracleTypeSystem::NumFloat(true);
+                if let Some(col) = self.descriptors.get_mut(i) {
+                    col.type_system = OracleTypeSystem::NumFloat(true);
+                }
+            }
+        }
+    }
+
+    /// Map the date/time columns to text, see [`OracleSource::dates_as_iso8601`].
+    fn map_iso8601_types(&mut self) {
+        if self.iso8601.is_none() {
+            return;
+        }
+        for ty in &mut self.schema {
+            *ty = Iso8601Format::map_type(*ty);
+        }
+        for col in &mut self.descriptors {
+            col.type_system = Iso8601Format::map_type(col.type_system);
+        }
+    }
+
+    /// Map the `NUMBER(p,s)` columns with a scale to [`OracleTypeSystem::Decimal`], see
+    /// [`OracleSource::numbers_as_decimal128`].
+    fn map_decimal128_types(&mut self) {
+        if !self.decimal128 {
+            return;
+        }
+        for (ty, col) in self.schema.iter_mut().zip(&mut self.descriptors) {
+            let fits = match (col.precision, col.scale) {
+                // an unconstrained `NUMBER` is described as `NUMBER(0,0)`
+                (Some(p), Some(s)) if p > 0 && s > 0 => {
+                    s as usize <= DECIMAL_SCALE
+                        && (p as usize).saturating_sub(s as usize)
+                            <= DECIMAL_PRECISION - DECIMAL_SCALE
+                }
+                _ => false,
+            };
+            if let (true, OracleTypeSystem::NumFloat(nullable)) = (fits, *ty) {
+                *ty = OracleTypeSystem::Decimal(nullable);
+                col.type_system = *ty;
+            }
+        }
+    }
+
+    /// Check out a connection from the pool, with the session settings changed since it was last
+    /// checked out applied, see [`Session`].
+    #[throws(OracleSourceError)]
+    fn get_conn(&self) -> OracleConn {
+        let mut conn = self.pool.get()?;
+        self.session.read().unwrap().catch_up(&mut conn)?;
+        // a partition of an earlier load may have left its fetch timeout on the connection
+        if self.count_timeout.is_some() || self.fetch_timeout.is_some() {
+            conn.set_call_timeout(None)?;
+        }
+        conn
+    }
+
+    /// Run the statements of the file at `path` on each connection of the pool, after the other
+    /// session settings and before any query runs on it, e.g. to keep a complex session setup of
+    /// `ALTER SESSION` statements in one place. The statements are separated by `;`, and the
+    /// lines starting with `--` are comments. PL/SQL blocks are not supported, since they contain
+    /// `;` themselves. Like [`OracleSource::set_module_name`], this applies to all the
+    /// connections of the pool.
+    #[throws(OracleSourceError)]
+    pub fn init_sql_file<P: AsRef<Path>>(&mut self, path: P) {
+        let content = fs::read_to_string(path)?;
+        let content: String = content
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let init_sql = content
+            .split(';')
+            .map(str::trim)
+            .filter(|sql| !sql.is_empty())
+            .map(str::to_string)
+            .collect();
+        self.update_session(Setting::InitSql, |settings| settings.init_sql = init_sql)?;
+    }
+
+    /// Identify the connections of the source in `V$SESSION.MODULE` and `V$SESSION.ACTION` (and
+    /// so in AWR and ASH reports) with `DBMS_APPLICATION_INFO.SET_MODULE`. They default to
+    /// [`DEFAULT_MODULE_NAME`] and [`DEFAULT_ACTION_NAME`]. The new connections of the pool are
+    /// tagged as they are established, and the others as they are next checked out.
+    #[throws(OracleSourceError)]
+    pub fn set_module_name(&mut self, module: &str, action: Option<&str>) {
+        self.update_session(Setting::Module, |settings| {
+            settings.module = module.to_string();
+            settings.action = action.map(str::to_string);
+        })?;
+    }
+
+    /// Run the sessions of the source in the Resource Manager consumer group `group`
+    /// (`DBMS_SESSION.SWITCH_CURRENT_CONSUMER_GROUP`), e.g. a low priority group for the large
+    /// batch loads so that they do not starve the OLTP workload. Like
+    /// [`OracleSource::set_module_name`], this applies to all the connections of the pool.
+    /// Fails with [`OracleSourceError::InvalidConsumerGroup`] if the group does not exist or the
+    /// user is not allowed to switch to it.
+    #[throws(OracleSourceError)]
+    pub fn with_consumer_group(&mut self, group: &str) {
+        self.update_session(Setting::ConsumerGroup, |settings| {
+            settings.consumer_group = Some(group.to_string())
+        })
+        .map_err(|e| match e {
+            OracleSourceError::OracleError(oracle::Error::OciError(ref err))
+                if err.code() == ORA_NO_CONSUMER_GROUP
+                    || err.code() == ORA_CONSUMER_GROUP_PRIVILEGE =>
+            {
+                OracleSourceError::InvalidConsumerGroup(group.to_string())
+            }
+            e => e,
+        })?;
+    }
+
+    /// Set the session `TIME_ZONE` of the connections, or keep the zone of the client environment
+    /// (`ORA_SDTZ`, or the zone of the operating system) if `None`, the default. Oracle converts
+    /// the `TIMESTAMP WITH LOCAL TIME ZONE` values to the session zone as they are fetched, and
+    /// the driver only has that zone's offset to map them back to a `DateTime<Utc>`, so the zone
+    /// must be known for the values to be reproducible. It also sets the zone of
+    /// `CURRENT_TIMESTAMP`, `CURRENT_DATE` and `LOCALTIMESTAMP` in the queries, but not of
+    /// `SYSDATE`. `zone` is an offset like `-05:00` or a region name like `Europe/Paris`. Like
+    /// [`OracleSource::set_module_name`], this applies to all the connections of the pool.
+    #[throws(OracleSourceError)]
+    pub fn set_session_time_zone(&mut self, zone: Option<&str>) {
+        self.update_session(Setting::TimeZone, |settings| {
+            settings.time_zone = zone.map(str::to_string)
+        })?;
+    }
+
+    /// Change the session settings with `update`, in which `setting` changes. The setting is
+    /// applied to one connection to check it, and only kept if it could be applied; the other
+    /// connections pick it up as they are checked out, see [`Session`].
+    #[throws(OracleSourceError)]
+    fn update_session<F: FnOnce(&mut SessionSettings)>(&self, setting: Setting, update: F) {
+        let mut conn = self.get_conn()?;
+        let mut settings = self.session.read().unwrap().settings.clone();
+        update(&mut settings);
+        if let Err(e) = settings.apply_setting(setting, &conn, false) {
+            // the setting may be partially applied, e.g. the statements of `init_sql`
+            conn.broken = true;
+            throw!(e);
+        }
+        self.session
+            .write()
+            .unwrap()
+            .update(setting, settings, &mut conn);
+    }
+
+    /// Read the data as of the system change number `scn` (flashback query): `AS OF SCN` is added to
+    /// each table of the partition queries, see [`flashback_query_oracle`] for the tables that
+    /// are covered. All the partitions, and the count of the origin query, then read the same
+    /// snapshot.
+    pub fn as_of_scn(&mut self, scn: u64) {
+        self.flashback = Some(format!("AS OF SCN {}", scn));
+    }
+
+    /// Read the data as it was at `ts`, like [`OracleSource::as_of_scn`]. Oracle maps `ts` to the
+    /// closest SCN within about 3 seconds.
+    pub fn as_of_timestamp(&mut self, ts: DateTime<Utc>) {
+        self.flashback = Some(format!(
+            "AS OF TIMESTAMP TO_TIMESTAMP_TZ('{}', 'YYYY-MM-DD HH24:MI:SS.FF TZH:TZM')",
+            ts.format("%Y-%m-%d %H:%M:%S%.f +00:00")
+        ));
+    }
+
+    /// Read the data as of the restore point `name`, like [`OracleSource::as_of_scn`] with the SCN
+    /// of the restore point. Oracle has no `AS OF RESTORE POINT` clause, so the SCN is looked up in
+    /// `V$RESTORE_POINT`; like in SQL, `name` is case insensitive unless double quoted. Fails with
+    /// [`OracleSourceError::UnknownRestorePoint`] if there is no such restore point.
+    #[throws(OracleSourceError)]
+    pub fn as_of_restore_point(&mut self, name: &str) {
+        let stored = match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+            Some(quoted) => quoted.to_string(),
+            None => name.to_uppercase(),
+        };
+        let conn = self.get_conn()?;
+        let scn = match conn.query_row_as::<u64>(
+            "SELECT scn FROM v$restore_point WHERE name = :1",
+            &[&stored],
+        ) {
+            Ok(scn) => scn,
+            Err(oracle::Error::NoDataFound) => {
+                throw!(OracleSourceError::UnknownRestorePoint(name.to_string()))
+            }
+            Err(e) => throw!(e),
+        };
+        debug!("Restore point {} is at SCN {}", name, scn);
+        self.as_of_scn(scn);
+    }
+
+    /// Add the `ORA_ROWSCN` pseudo column, the system change number of the last change of each
+    /// row, after the columns of the result, e.g. to detect the rows changed since the previous
+    /// load. It is read as an integer, and is the last column of the schema. Call before
+    /// `fetch_metadata`. The query must read a single table, see [`rowscn_query_oracle`].
+    /// Unless the table was created with `ROWDEPENDENCIES`, the SCN is tracked per block rather
+    /// than per row: a change to any row of a block moves the SCN of all its rows, so it can only
+    /// tell the rows that *may* have changed.
+    pub fn include_rowscn(&mut self, enabled: bool) {
+        self.include_rowscn = enabled;
+    }
+
+    /// Run each partition query with Oracle parallel query, by adding the `PARALLEL(degree)` hint
+    /// to it (`PARALLEL` without a degree if `degree` is 0, letting Oracle choose it). This is
+    /// complementary to the partitioning of ConnectorX: the partitions are read in parallel by
+    /// ConnectorX, and each of them is executed in parallel by Oracle.
+    pub fn with_parallel_degree(&mut self, degree: usize) {
+        self.parallel_degree = Some(degree);
+    }
+
+    /// Sort each partition query by `columns` in the database, e.g. an `ORDER BY id DESC` list,
+    /// see [`order_query_oracle`]. Combined with [`Self::with_parallel_degree`], a single huge
+    /// partition is read with all the parallelism of Oracle while its rows keep their order. The
+    /// rows of a partition are still counted without the sort.
+    pub fn parallel_order_by(&mut self, columns: &[&str]) {
+        self.parallel_order = columns.iter().map(|c| c.to_string()).collect();
+    }
+
+    /// Sort the rows of each partition by the unique key `columns`, so that a partition returns
+    /// its rows in the same order on every run, e.g. to resume it after a checkpoint. Without an
+    /// `ORDER BY`, Oracle may return the rows in a different order each time. The key is appended
+    /// after the columns of [`Self::parallel_order_by`], as a tie breaker, skipping those already
+    /// sorted on. Like those, the rows are counted without the sort. The order of the rows across
+    /// partitions is left as it is.
+    pub fn partition_order_key(&mut self, columns: &[&str]) {
+        self.order_key = columns.iter().map(|c| c.to_string()).collect();
+    }
+
+    /// Sort the `NULL` values of the columns of [`Self::parallel_order_by`] and
+    /// [`Self::partition_order_key`] first or last, unless a column says otherwise. `None` (the
+    /// default) leaves it to Oracle: last in ascending order, first in descending order. The
+    /// queries split on a column should take the `NULL` keys in the partition given by
+    /// [`Self::null_partition`], so that they are read where they are sorted.
+    pub fn partition_nulls(&mut self, nulls: Option<NullsOrder>) {
+        self.nulls = nulls;
+    }
+
+    /// The index of the partition taking the `NULL` keys in, out of `num` partitions split on a
+    /// column, for the order set by [`Self::partition_nulls`], see [`null_partition`].
+    pub fn null_partition(&self, num: usize) -> usize {
+        null_partition(self.nulls, num)
+    }
+
+    /// Split each query into a partition per bound of `column`, computed elsewhere (e.g. by a
+    /// scheduler), instead of setting the partition queries: no `MIN`/`MAX` probe runs, and the
+    /// bounds are taken as they are, see [`bounds_partition_query_oracle`]. The partitions are
+    /// generated in the order of `bounds`, and it is up to the caller that they neither overlap
+    /// nor miss any key, and that one of them takes the `NULL` keys in if they are to be read.
+    pub fn set_partition_bounds(&mut self, column: &str, bounds: Vec<PartitionBound>) {
+        self.bounds = Some((column.to_string(), bounds));
+    }
+
+    /// The `ORDER BY` list of the partition queries: the columns of [`Self::parallel_order_by`]
+    /// followed by those of [`Self::partition_order_key`] not already in it.
+    fn partition_order(&self) -> Vec<String> {
+        let column = |c: &String| c.split_whitespace().next().unwrap_or("").to_uppercase();
+        let mut order = self.parallel_order.clone();
+        for key in &self.order_key {
+            if !self.parallel_order.iter().any(|c| column(c) == column(key)) {
+                order.push(key.clone());
+            }
+        }
+        match self.nulls {
+            Some(nulls) => order
+                .into_iter()
+                .map(|c| match c.to_uppercase().contains(" NULLS ") {
+                    true => c,
+                    false => format!("{} {}", c, nulls),
+                })
+                .collect(),
+            None => order,
+        }
+    }
+
+    /// Also look up the comments of the queried table and of its columns (`ALL_TAB_COMMENTS` and
+    /// `ALL_COL_COMMENTS`) in `fetch_metadata`, see [`OracleSource::column_comments`]. Only the
+    /// plain selects from a single table or view are looked up, there are no comments for the
+    /// other queries.
+    pub fn fetch_comments(&mut self, enabled: bool) {
+        self.fetch_comments = enabled;
+    }
+
+    /// Also look up the storage attributes of the LOB columns of the queried table in `ALL_LOBS` in
+    /// `fetch_metadata`, e.g. whether a `CLOB` is a compressed or deduplicated `SECUREFILE`, into
+    /// the [`ColumnDescriptor::lob_storage`] of the `BLOB` and `CLOB` columns. Like for
+    /// [`OracleSource::fetch_comments`], only the plain selects from a single table are looked up,
+    /// and the catalog is not queried at all if the result has no LOB column.
+    pub fn fetch_lob_storage(&mut self, enabled: bool) {
+        self.fetch_lob_storage = enabled;
+    }
+
+    /// The comment of the queried table, if any. Call after `fetch_metadata`.
+    pub fn table_comment(&self) -> Option<&str> {
+        self.comments.as_ref()?.table.as_deref()
+    }
+
+    /// The comment of each result column, `None` for the columns without a comment or that are not
+    /// named after a column of the table. Call after `fetch_metadata`.
+    pub fn column_comments(&self) -> Vec<Option<&str>> {
+        self.names
+            .iter()
+            .map(|name| {
+                self.comments
+                    .as_ref()
+                    .and_then(|comments| comments.columns.get(name))
+                    .map(String::as_str)
+            })
+            .collect()
+    }
+
+    /// Run the metadata queries of `fetch_metadata` and get the names and types of the columns.
+    #[throws(OracleSourceError)]
+    fn probe_metadata(&mut self) {
+        let conn = self.get_conn()?;
+        if let Some(max_cost) = self.explain_plan {
+            self.explain_queries(&conn, max_cost)?;
+        }
+        self.comments = None;
+        if self.fetch_comments {
+            self.comments = self.lookup_comments(&conn)?;
+        }
+        // the row limit of the probe is not always pushed to the remote database, which may then
+        // ship the whole result over the link: describe the query instead
+        if !self.metadata_probe || dblink_query_oracle(self.queries[0].as_str()) {
+            let (names, types, descriptors) =
+                describe_query(&conn, self.queries[0].as_str(), self.lock_retry)?;
+            self.names = names;
+            self.schema = types;
+            self.descriptors = descriptors;
+            return;
+        }
+
+        for (i, query) in self.queries.iter().enumerate() {
+            // assuming all the partition queries yield same schema
+            // without rownum = 1, derived type might be wrong
+            // example: select avg(test_int), test_char from test_table group by test_char
+            // -> (NumInt, Char) instead of (NumtFloat, Char)
+            match conn.query(limit1_query_oracle(query)?.as_str(), &[]) {
+                Ok(rows) => {
+                    let (names, types) = rows
+                        .column_info()
+                        .iter()
+                        .map(|col| {
+                            (
+                                col.name().to_string(),
+                                OracleTypeSystem::from(col.oracle_type()),
+                            )
+                        })
+                        .unzip();
+                    self.names = names;
+                    self.schema = types;
+                    self.descriptors = rows
+                        .column_info()
+                        .iter()
+                        .map(ColumnDescriptor::from)
+                        .collect();
+                    return;
+                }
+                Err(e) if i == self.queries.len() - 1 => {
+                    // tried the last query but still get an error, the wrapping may be the
+                    // culprit (e.g. with some set operations): describe the query itself instead
+                    debug!("cannot get metadata for '{}': {}, describing it", query, e);
+                    let (names, types, descriptors) =
+                        describe_query(&conn, query.as_str(), self.lock_retry)?;
+                    self.names = names;
+                    self.schema = types;
+                    self.descriptors = descriptors;
+                    return;
+                }
+                Err(_) => {}
+            }
+        }
+        // tried all queries but all get empty result set
+        let iter = conn.query(self.queries[0].as_str(), &[])?;
+        let (names, types) = iter
+            .column_info()
+            .iter()
+            .map(|col| (col.name().to_string(), OracleTypeSystem::VarChar(false)))
+            .unzip();
+        self.names = names;
+        self.schema = types;
+        self.descriptors = iter
+            .column_info()
+            .iter()
+            .map(|col| ColumnDescriptor {
+                type_system: OracleTypeSystem::VarChar(false),
+                ..ColumnDescriptor::from(col)
+            })
+            .collect();
+    }
+
+    #[throws(OracleSourceError)]
+    fn lookup_comments(&self, conn: &OracleConn) -> Option<Comments> {
+        let (owner, table) = match self.source_table() {
+            Some(table) => table,
+            None => {
+                debug!("no comments to look up for '{}'", self.queries[0]);
+                return None;
+            }
+        };
+        let table = &table;
+
+        let mut comments = Comments::default();
+        for row in conn.query(
+            "SELECT comments FROM all_tab_comments WHERE owner = NVL(:1, USER) AND table_name = :2",
+            &[&owner, table],
+        )? {
+            comments.table = row?.get(0)?;
+        }
+        for row in conn.query(
+            "SELECT column_name, comments FROM all_col_comments \
+             WHERE owner = NVL(:1, USER) AND table_name = :2 AND comments IS NOT NULL",
+            &[&owner, table],
+        )? {
+            let row = row?;
+            comments.columns.insert(row.get(0)?, row.get(1)?);
+        }
+        comments
+    }
+
+    /// Set the [`ColumnDescriptor::lob_storage`] of the LOB columns named after a LOB column of the
+    /// table read by the query.
+    #[throws(OracleSourceError)]
+    fn lookup_lob_storage(&mut self) {
+        let lobs = self.descriptors.iter().any(|col| {
+            matches!(
+                col.type_system,
+                OracleTypeSystem::Blob(_) | OracleTypeSystem::Clob(_)
+            )
+        });
+        if !lobs {
+            return;
+        }
+        let (owner, table) = match self.source_table() {
+            Some(table) => table,
+            None => {
+                debug!("no LOB storage to look up for '{}'", self.queries[0]);
+                return;
+            }
+        };
+
+        let conn = self.get_conn()?;
+        let mut storage = HashMap::new();
+        for row in conn.query(
+            "SELECT column_name, securefile, compression, deduplication, encrypt, in_row \
+             FROM all_lobs WHERE owner = NVL(:1, USER) AND table_name = :2",
+            &[&owner, &table],
+        )? {
+            let row = row?;
+            let (column, securefile, compression, deduplication, encrypt, in_row): (
+                String,
+                String,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+            ) = row.get_as()?;
+            // the attributes which do not apply, e.g. to a BASICFILE LOB, are NONE
+            let compression = compression.filter(|c| c != "NO" && c != "NONE");
+            storage.insert(
+                column,
+                LobStorage {
+                    securefile: securefile == "YES",
+                    compression,
+                    deduplication: deduplication.as_deref() == Some("LOB"),
+                    encrypted: encrypt.as_deref() == Some("YES"),
+                    in_row: in_row.as_deref() == Some("YES"),
+                },
+            );
+        }
+        for col in &mut self.descriptors {
+            if matches!(
+                col.type_system,
+                OracleTypeSystem::Blob(_) | OracleTypeSystem::Clob(_)
+            ) {
+                col.lob_storage = storage.get(&col.name).cloned();
+            }
+        }
+    }
+
+    /// The owner (`None` for the current schema) and the name of the table read by the origin
+    /// query (or the first query if there is none), if it reads a single table, see
+    /// [`single_table`]. Unquoted identifiers are in upper case, as stored in the catalog.
+    fn source_table(&self) -> Option<(Option<String>, String)> {
+        let query = match &self.origin_query {
+            Some(q) => q.clone(),
+            None => self.queries.first()?.to_string(),
+        };
+        let name = single_table(&query, &OracleDialect {})?;
+        let idents: Vec<String> = name
+            .0
+            .iter()
+            .map(|ident| match ident.quote_style {
+                Some(_) => ident.value.clone(),
+                None => ident.value.to_uppercase(),
+            })
+            .collect();
+        match idents.as_slice() {
+            [table] => Some((None, table.clone())),
+            [owner, table] => Some((Some(owner.clone()), table.clone())),
+            _ => None,
+        }
+    }
+
+    /// Read the optimizer statistics of `column` of the table read by the query from
+    /// `ALL_TAB_COL_STATISTICS`, see [`OptimizerStats`]. Unlike [`OracleSource::column_stats`]
+    /// nothing is computed over the data, so they are as recent as the last `DBMS_STATS` run on
+    /// the table, and they are all `None` if it never ran. Like in SQL, `column` is case
+    /// insensitive unless double quoted. Fails if the query does not read a single table, or if
+    /// the table has no such column.
+    #[throws(OracleSourceError)]
+    pub fn optimizer_stats(&self, column: &str) -> OptimizerStats {
+        let (owner, table) = self
+            .source_table()
+            .ok_or_else(|| anyhow!("the query does not read a single table"))?;
+        let column_name = match column.strip_prefix('"').and_then(|c| c.strip_suffix('"')) {
+            Some(quoted) => quoted.to_string(),
+            None => column.to_uppercase(),
+        };
+        let conn = self.get_conn()?;
+        match read_optimizer_stats(&conn, owner.as_deref(), &table, &column_name)? {
+            Some((stats, _)) => stats,
+            None => throw!(anyhow!("table {} has no column {}", table, column)),
+        }
+    }
+
+    /// Make the results of all the queries cacheable (`RESULT_CACHE_MODE = FORCE`), so that a
+    /// query repeated within a session, e.g. on a lookup table, is answered from the client result
+    /// cache without a round trip to the server. The client cache only exists if the server sets
+    /// `CLIENT_RESULT_CACHE_SIZE`; otherwise the results are cached on the server only. The
+    /// cached results are invalidated by any change to the tables they are read from. Like
+    /// [`OracleSource::set_module_name`], this applies to all the connections of the pool.
+    #[throws(OracleSourceError)]
+    pub fn client_result_cache(&mut self, enabled: bool) {
+        self.update_session(Setting::ResultCache, |settings| {
+            settings.result_cache = Some(enabled)
+        })?;
+    }
+
+    /// Read through the editioning views of `edition` (edition-based redefinition), e.g. the
+    /// post-upgrade edition during a rolling upgrade. `ALTER SESSION SET EDITION` is issued on each
+    /// connection of the pool, before any query runs on it.
+    #[throws(OracleSourceError)]
+    pub fn edition(&mut self, edition: String) {
+        if edition.is_empty()
+            || !edition
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '#')
+        {
+            throw!(anyhow!("invalid edition name {}", edition));
+        }
+        self.update_session(Setting::Edition, |settings| {
+            settings.edition = Some(edition)
+        })?;
+    }
+
+    /// Retry a query that fails with ORA-00054 (resource busy), e.g. `SELECT ... FOR UPDATE NOWAIT`
+    /// on rows locked by another session. The query is attempted up to `max_attempts` times
+    /// (capped at [`MAX_LOCK_RETRY_ATTEMPTS`]), sleeping `backoff` before the first retry and
+    /// doubling it before each of the next ones. This is independent of the connection retries of
+    /// the pool. Only short lived locks are worth retrying: each retry keeps its connection while
+    /// sleeping, and a lock held longer than the total backoff still fails the query.
+    pub fn lock_retry(&mut self, max_attempts: u32, backoff: Duration) {
+        self.lock_retry = Some((max_attempts.clamp(1, MAX_LOCK_RETRY_ATTEMPTS), backoff));
+    }
+
+    /// Fail the `COUNT(*)` queries, of the whole result and of each partition, that take longer
+    /// than `timeout`, e.g. to fail fast on a count that would scan a huge table, while the rows
+    /// are fetched with the [`OracleSource::fetch_timeout`]. This is the call timeout of the
+    /// connection (Oracle client 18 or later), so the error is a `DPI-1067`.
+    pub fn count_timeout(&mut self, timeout: Option<Duration>) {
+        self.count_timeout = timeout;
+    }
+
+    /// Fail the queries of the partitions when a single round trip to fetch their rows takes
+    /// longer than `timeout`, the execution of the query included. A long read made of many
+    /// round trips is never interrupted as long as each of them is fast enough. Independent of the
+    /// [`OracleSource::count_timeout`].
+    pub fn fetch_timeout(&mut self, timeout: Option<Duration>) {
+        self.fetch_timeout = timeout;
+    }
+
+    /// The name, type, nullability, precision, scale and length of each result column,
+    /// available after `fetch_metadata`.
+    pub fn column_descriptors(&self) -> Vec<ColumnDescriptor> {
+        self.descriptors.clone()
+    }
+
+    /// Whether `fetch_metadata` is allowed to issue rewritten probe queries (`rownum = 1`).
+    /// When disabled, the schema is derived solely from the column info of a zero-row
+    /// execution of the first query as is, so no SQL other than the user's is ever sent.
+    /// Note that without the probe, types of aggregated columns may be less precise.
+    /// The queries reading through a database link are never probed.
+    pub fn metadata_probe(&mut self, allow: bool) {
+        self.metadata_probe = allow;
+    }
+
+    /// Run `EXPLAIN PLAN FOR` on each partition query at the start of `fetch_metadata`, so that
+    /// invalid SQL fails before any partition is executed. The estimated cardinality and cost of
+    /// each query are available from [`OracleSource::plan_estimates`]. With `max_cost`,
+    /// `fetch_metadata` fails if the estimated cost of any query exceeds it.
+    pub fn explain_plan(&mut self, max_cost: Option<u64>) {
+        self.explain_plan = Some(max_cost);
+    }
+
+    /// The plan estimates of the partition queries, available after `fetch_metadata` if
+    /// [`OracleSource::explain_plan`] is enabled.
+    pub fn plan_estimates(&self) -> Vec<PlanEstimate> {
+        self.plan_estimates.clone()
+    }
+
+    /// Capture the `sql_id` and plan hash of the statement executed by each partition from
+    /// `V$SQL`, available from [`OracleSource::sql_plans`], e.g. to check that a query keeps its
+    /// plan across releases. With a `baseline`, a partition whose plan hash differs from it fails
+    /// with [`OracleSourceError::PlanChanged`] before its rows are read, its statement still
+    /// captured. The user needs to read `V$SESSION` and `V$SQL`. The partitions served from the
+    /// query cache and those reading a `SYS_REFCURSOR` are not captured.
+    pub fn capture_sql_plans(&mut self, baseline: Option<u64>) {
+        let plans = self.sql_plans.take().map(|(plans, _)| plans);
+        self.sql_plans = Some((plans.unwrap_or_default(), baseline));
+    }
+
+    /// The statements captured by [`OracleSource::capture_sql_plans`], shared with the partitions
+    /// to be read once the load is done.
+    pub fn sql_plans(&self) -> SqlPlans {
+        self.sql_plans
+            .as_ref()
+            .map(|(plans, _)| plans.clone())
+            .unwrap_or_default()
+    }
+
+    #[throws(OracleSourceError)]
+    fn explain_queries(&mut self, conn: &OracleConn, max_cost: Option<u64>) {
+        const STATEMENT_ID: &str = "CX_PREFLIGHT";
+        self.plan_estimates.clear();
+        for query in &self.queries {
+            conn.execute(
+                &format!(
+                    "EXPLAIN PLAN SET STATEMENT_ID = '{}' FOR {}",
+                    STATEMENT_ID, query
+                ),
+                &[],
+            )?;
+            let (cardinality, cost) = conn.query_row_as::<(Option<u64>, Option<u64>)>(
+                "SELECT cardinality, cost FROM plan_table WHERE statement_id = :1 AND id = 0",
+                &[&STATEMENT_ID],
+            )?;
+            conn.execute(
+                "DELETE FROM plan_table WHERE statement_id = :1",
+                &[&STATEMENT_ID],
+            )?;
+            debug!(
+                "plan of '{}': cardinality {:?}, cost {:?}",
+                query, cardinality, cost
+            );
+            if let (Some(max_cost), Some(cost)) = (max_cost, cost) {
+                if cost > max_cost {
+                    throw!(anyhow!(
+                        "estimated cost {} of '{}' exceeds the maximum {}",
+                        cost,
+                        query,
+                        max_cost
+                    ));
+                }
+            }
+            self.plan_estimates.push(PlanEstimate {
+                query: query.to_string(),
+                cardinality,
+                cost,
+            });
+        }
+    }
+
+    /// Size the fetch buffer of the partitions to about `target_mb` MiB instead of a fixed
+    /// number of rows, so that wide rows are fetched in fewer rows per round trip than narrow
+    /// ones. The size of a row is estimated from the schema found by `fetch_metadata`, see
+    /// [`AdaptiveBufSize`]. When there are several queries, `partition` describes each of them
+    /// and sizes its buffer from its own columns, so that e.g. a partition reading LOBs does not
+    /// share the buffer size of partitions reading narrow columns.
+    pub fn adaptive_buf_size(&mut self, target_mb: usize) {
+        self.adaptive_buf_size = Some(AdaptiveBufSize::new(target_mb));
+    }
+
+    /// The number of rows fetched per round trip by the partitions. Call after `fetch_metadata`
+    /// when [`OracleSource::adaptive_buf_size`] is used.
+    #[must_use]
+    pub fn buf_size(&self) -> u32 {
+        match (&self.adaptive_buf_size, &self.column_stats) {
+            (Some(adaptive), Some(stats)) => {
+                // the columns without statistics keep the estimate from their type, and every
+                // value takes at least a byte
+                let row_bytes: f64 = stats
+                    .columns
+                    .iter()
+                    .zip(&self.descriptors)
+                    .map(|(c, d)| {
+                        c.avg_bytes
+                            .unwrap_or(AdaptiveBufSize::column_bytes(d) as f64)
+                            .max(1.)
+                    })
+                    .sum();
+                adaptive.rows_for_bytes(row_bytes.ceil() as usize)
+            }
+            (Some(adaptive), None) if !self.descriptors.is_empty() => {
+                adaptive.rows(&self.descriptors)
+            }
+            _ => ORACLE_ARRAY_SIZE,
+        }
+    }
+
+    /// Log a warning when [`OracleSource::estimated_memory_usage`] exceeds `bytes`, instead of
+    /// [`DEFAULT_MEMORY_WARNING_THRESHOLD`].
+    pub fn memory_warning_threshold(&mut self, bytes: usize) {
+        self.memory_warning_threshold = bytes;
+    }
+
+    /// Estimate the memory taken by the result once loaded, in bytes: the number of rows of the
+    /// partition queries times the size of a row, the sum of the widths of its columns by type in
+    /// `dtype_widths`. The types missing from `dtype_widths` take their [`default_type_widths`].
+    /// Call after `fetch_metadata`. Each partition query is counted, under the
+    /// [`OracleSource::count_timeout`]. A warning is logged when the estimate exceeds the
+    /// [`OracleSource::memory_warning_threshold`].
+    #[throws(OracleSourceError)]
+    pub fn estimated_memory_usage(
+        &mut self,
+        dtype_widths: &HashMap<OracleTypeSystem, usize>,
+    ) -> usize {
+        if self.schema.is_empty() {
+            throw!(anyhow!(
+                "the memory usage is estimated from the schema, call fetch_metadata first"
+            ));
+        }
+        let defaults = default_type_widths();
+        let row_bytes: usize = self
+            .schema
+            .iter()
+            .map(|ty| dtype_widths.get(ty).or_else(|| defaults.get(ty)).unwrap())
+            .sum();
+
+        // checked out before the connection, which may be the only one of the pool
+        let aliases = self.alias_generator()?;
+        let conn = self.get_conn()?;
+        let mut nrows = 0;
+        for query in &self.queries {
+            let count = count_query_oracle(query, &mut aliases.clone());
+            nrows += with_call_timeout(&conn, self.count_timeout, || {
+                Ok(conn.query_row_as::<usize>(count.as_str(), &[])?)
+            })?;
+        }
+
+        let bytes = nrows.saturating_mul(row_bytes);
+        if bytes > self.memory_warning_threshold {
+            warn!(
+                "the result of {} rows of {} bytes is estimated to take {} bytes in memory, above {}",
+                nrows, row_bytes, bytes, self.memory_warning_threshold
+            );
+        }
+        bytes
+    }
+
+    /// The statistics of the result columns from the optimizer statistics of the table read by
+    /// the origin query (or the first query if there is none), see [`ColumnStats`] and
+    /// [`OracleSource::optimizer_stats`]. Nothing is computed over the data: the statistics are
+    /// as of the last `DBMS_STATS` run on the table, and those of the result columns that are not
+    /// columns of the table (e.g. expressions) are `None`. Call after `fetch_metadata`. Fails if
+    /// the query does not read a single table. The statistics are cached on the source. Once
+    /// read, their average row size replaces the estimate from the schema in
+    /// [`OracleSource::adaptive_buf_size`].
+    #[throws(OracleSourceError)]
+    pub fn column_stats(&mut self) -> ColumnStats {
+        if let Some(stats) = &self.column_stats {
+            return stats.clone();
+        }
+        let (owner, table) = self
+            .source_table()
+            .ok_or_else(|| anyhow!("the query does not read a single table"))?;
+
+        let conn = self.get_conn()?;
+        let rows = match conn.query_row_as::<Option<u64>>(TABLE_ROWS_QUERY, &[&owner, &table]) {
+            Ok(rows) => rows.unwrap_or(0),
+            // a view has no statistics
+            Err(oracle::Error::NoDataFound) => 0,
+            Err(e) => throw!(e),
+        };
+        let columns = self
+            .names
+            .iter()
+            .map(|name| {
+                let (stats, avg_bytes) =
+                    read_optimizer_stats(&conn, owner.as_deref(), &table, name)?
+                        .unwrap_or_default();
+                Ok(ColumnStat {
+                    name: name.clone(),
+                    min: stats.low,
+                    max: stats.high,
+                    distinct: stats.num_distinct,
+                    avg_bytes,
+                })
+            })
+            .collect::<Result<Vec<_>, OracleSourceError>>()?;
+        let stats = ColumnStats { rows, columns };
+        self.column_stats = Some(stats.clone());
+        stats
+    }
+
+    /// Narrow the produced `Decimal` values to `scale` digits after the decimal point.
+    /// `None` (the default) keeps the `NUMBER` values exact.
+    pub fn decimal_scale(&mut self, scale: Option<u32>) {
+        self.decimal_scale = scale;
+    }
+
+    /// The rounding mode used when narrowing `Decimal` values, see [`Self::decimal_scale`].
+    /// Defaults to half-even (banker's rounding).
+    pub fn decimal_rounding(&mut self, strategy: RoundingStrategy) {
+        self.decimal_rounding = strategy;
+    }
+
+    /// Produce the `Decimal` values of the columns `names` (case insensitive) with exactly `scale`
+    /// digits after the decimal point, e.g. 2 for amounts of money: `1.5` is produced as `1.50`.
+    /// A value with more digits is rounded with [`Self::decimal_rounding`], or is an
+    /// [`OracleSourceError::DecimalTruncation`] error in [`Self::strict`] mode. This takes
+    /// precedence over [`Self::decimal_scale`] for these columns. `partition` fails if a column is
+    /// not in the result.
+    pub fn money_columns(&mut self, names: &[&str], scale: u32) {
+        self.money
+            .retain(|(name, _)| !names.iter().any(|other| other.eq_ignore_ascii_case(name)));
+        self.money
+            .extend(names.iter().map(|name| (name.to_string(), scale)));
+    }
+
+    /// Fail on the lossy conversions instead of silently losing data: in strict mode, a `NUMBER`
+    /// that cannot be narrowed to [`Self::decimal_scale`] without rounding is an
+    /// [`OracleSourceError::DecimalTruncation`] error. The default is lenient.
+    pub fn strict(&mut self, enabled: bool) {
+        self.strict = enabled;
+    }
+
+    /// Produce the `DATE`, `TIMESTAMP` and `TIMESTAMP WITH (LOCAL) TIME ZONE` columns as ISO-8601
+    /// text in `format`, e.g. for JSON or CSV destinations: their type is then
+    /// [`OracleTypeSystem::VarChar`]. Call before `fetch_metadata`. `None` (the default) keeps
+    /// them as date/time values.
+    pub fn dates_as_iso8601(&mut self, format: Option<Iso8601Format>) {
+        self.iso8601 = format;
+    }
+
+    /// Produce the `NUMBER(p,s)` columns with a scale as exact [`OracleTypeSystem::Decimal`]
+    /// values, e.g. `Decimal128` columns of the Arrow destination, instead of `f64`. Only the
+    /// columns that fit the Arrow columns are mapped, of a scale up to [`DECIMAL_SCALE`] and of up
+    /// to `DECIMAL_PRECISION - DECIMAL_SCALE` digits before the decimal point; the unconstrained
+    /// `NUMBER` columns are not. Call before `fetch_metadata`. Disabled by default.
+    pub fn numbers_as_decimal128(&mut self, enabled: bool) {
+        self.decimal128 = enabled;
+    }
+
+    /// Do not run a `COUNT(*)` of the queries when the destination needs the number of rows ahead:
+    /// each partition then reads its rows once into memory and counts them, so the query is
+    /// executed once and the count matches the rows. `None` (the default) skips the count of the
+    /// queries reading monitoring views, see [`monitoring_query_oracle`], whose count is as
+    /// expensive as the query itself and may not be repeatable, and of the queries reading through
+    /// a database link, see [`dblink_query_oracle`], whose count runs the query remotely.
+    pub fn skip_count(&mut self, skip: Option<bool>) {
+        self.skip_count = skip;
+    }
+
+    fn skips_count(&self, query: &str) -> bool {
+        // the count of the query would include the rows skipped
+        self.row_errors.is_some()
+            || self
+                .skip_count
+                .unwrap_or_else(|| monitoring_query_oracle(query) || dblink_query_oracle(query))
+    }
+
+    /// What to do with a row whose values cannot be read as the types of the columns, e.g. a
+    /// `NUMBER(38)` too large for the `i64` of its column. With
+    /// [`RowErrorPolicy::SkipAndCollect`], each batch of rows is checked as it is fetched, and the
+    /// rows that fail are skipped and reported in [`OracleSource::row_errors`], with their index
+    /// in the result of their partition. The count of the rows is then skipped (see
+    /// [`OracleSource::skip_count`]), since it would include the skipped rows, and a query whose
+    /// rows were skipped is not cached.
+    pub fn on_row_error(&mut self, policy: RowErrorPolicy) {
+        self.row_errors = match policy {
+            RowErrorPolicy::Abort => None,
+            RowErrorPolicy::SkipAndCollect => Some(self.row_errors.take().unwrap_or_default()),
+        };
+    }
+
+    /// Read the columns of the object type `type_name` (e.g. a site specific `ADDRESS_TYPE`) as
+    /// text with `handler`, which gets the raw non-null values. The name is case insensitive, and
+    /// may be qualified by the owner of the type (`HR.ADDRESS_TYPE`), which then takes precedence
+    /// over a handler of the unqualified name. The columns of the object types are `VARCHAR`
+    /// columns, and reading one whose type has no handler fails.
+    pub fn register_type_handler(&mut self, type_name: &str, handler: Box<TypeHandler>) {
+        self.type_handlers
+            .insert(type_name.to_uppercase(), Arc::from(handler));
+    }
+
+    /// The rows skipped under [`RowErrorPolicy::SkipAndCollect`], shared with the partitions to be
+    /// read once the load is done. Empty under [`RowErrorPolicy::Abort`].
+    pub fn row_errors(&self) -> RowErrors {
+        self.row_errors.clone().unwrap_or_default()
+    }
+
+    /// Serve the queries of this source from `cache` while they are cached, and cache their rows
+    /// when they are small enough (see [`QueryCache`]) once a partition has read all of them. The
+    /// cache is keyed by the partition queries, after all the rewrites of `partition`, along with
+    /// the database and user connected to, the session settings changing the values read (time
+    /// zone, edition, init SQL) and the flashback snapshot. It is meant to be shared by the
+    /// sources of repeated loads, e.g. of the dimension tables of a star schema. The rows served
+    /// from the cache may be up to the TTL of the cache old.
+    pub fn with_query_cache(&mut self, cache: Arc<QueryCache>) {
+        self.query_cache = Some(cache);
+    }
+
+    /// What the rows of a query depend on besides its text, see [`OracleSource::with_query_cache`].
+    fn cache_context(&self) -> String {
+        format!(
+            "{} {} flashback={:?}",
+            self.identity,
+            self.session.read().unwrap().settings.cache_key(),
+            self.flashback
+        )
+    }
+
+    /// Fetch CLOB values of up to `size` characters inline with the row data instead of reading
+    /// each of them through its LOB locator, which saves a round trip per value on tables with many
+    /// small CLOBs. Longer values are still read through their locators. `size` is capped to 1000,
+    /// so that the values fit the 4000 bytes of a `VARCHAR2` in SQL with characters of up to 4 bytes.
+    pub fn lob_prefetch(&mut self, size: Option<u32>) {
+        self.lob_prefetch = size.map(|s| s.min(MAX_LOB_PREFETCH_CHARS));
+    }
+
+    /// Read the result tail-first, e.g. the newest rows of a log table.
+    /// Each partition query is ordered by `order_by` descending and the partitions are handed out
+    /// in reverse order. Given that the partitions are split on ascending ranges, the concatenated
+    /// result is newest-first as a whole. With a `limit`, the origin query (or the only query) is
+    /// ordered and cut to its first `limit` rows instead, and read as a single partition.
+    pub fn tail_first(&mut self, order_by: &str, limit: Option<usize>) {
+        self.tail = Some((order_by.to_string(), limit));
+    }
+
+    /// Queries reading the global dynamic performance view `view` (e.g. `GV$SESSION`) of a RAC database.
+    /// With `per_instance`, one query is generated for each instance listed in `GV$INSTANCE`, filtered on
+    /// `INST_ID`, so that every instance is read as its own partition.
+    #[throws(OracleSourceError)]
+    pub fn gv_view_queries(&self, view: &str, per_instance: bool) -> Vec<CXQuery<String>> {
+        let valid = view.len() > 3
+            && view
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+            && view[..3].eq_ignore_ascii_case("GV$");
+        if !valid {
+            throw!(anyhow!("{} is not a GV$ view", view));
+        }
+        if !per_instance {
+            return vec![CXQuery::naked(format!("SELECT * FROM {}", view))];
+        }
+
+        let conn = self.get_conn()?;
+        conn.query_as::<i64>("SELECT inst_id FROM gv$instance ORDER BY inst_id", &[])?
+            .map(|inst_id| {
+                Ok(CXQuery::naked(format!(
+                    "SELECT * FROM {} WHERE inst_id = {}",
+                    view, inst_id?
+                )))
+            })
+            .collect::<Result<Vec<_>, OracleSourceError>>()?
+    }
+
+    /// Execute the anonymous PL/SQL `block` whose OUT binds (`:name`) are declared by `outs`, and
+    /// return a single-row query over the OUT values, e.g. `BEGIN :a := 1; END;` becomes
+    /// `SELECT CAST(1 AS NUMBER(10)) "A" FROM dual`. Set the returned query on the source (or hand
+    /// it to the dispatcher) to read the procedure outputs as a result set, whose schema is derived
+    /// from the declared OUT types.
+    #[throws(OracleSourceError)]
+    pub fn out_params_query(&self, block: &str, outs: &[(&str, OracleType)]) -> CXQuery<String> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.statement(block).build()?;
+        let binds: Vec<(&str, &dyn ToSql)> = outs
+            .iter()
+            .map(|(name, ty)| (*name, ty as &dyn ToSql))
+            .collect();
+        stmt.execute_named(&binds)?;
+
+        let mut columns = vec![];
+        for (name, ty) in outs {
+            let value = match ty {
+                OracleType::Date => stmt.bind_value::<Option<NaiveDateTime>>(*name)?.map(|v| {
+                    format!(
+                        "TO_DATE('{}', 'YYYY-MM-DD HH24:MI:SS')",
+                        v.format("%Y-%m-%d %H:%M:%S")
+                    )
+                }),
+                OracleType::Timestamp(_) => {
+                    stmt.bind_value::<Option<NaiveDateTime>>(*name)?.map(|v| {
+                        format!(
+                            "TO_TIMESTAMP('{}', 'YYYY-MM-DD HH24:MI:SS.FF6')",
+                            v.format("%Y-%m-%d %H:%M:%S%.6f")
+                        )
+                    })
+                }
+                OracleType::Number(_, _)
+                | OracleType::Float(_)
+                | OracleType::BinaryFloat
+                | OracleType::BinaryDouble => stmt.bind_value::<Option<String>>(*name)?,
+                OracleType::Char(_)
+                | OracleType::NChar(_)
+                | OracleType::Varchar2(_)
+                | OracleType::NVarchar2(_)
+                | OracleType::CLOB => stmt
+                    .bind_value::<Option<String>>(*name)?
+                    .map(|v| format!("'{}'", v.replace('\'', "''"))),
+                _ => throw!(anyhow!(
+                    "unsupported OUT parameter type {} for {}",
+                    ty,
+                    name
+                )),
+            };
+            columns.push(format!(
+                "CAST({} AS {}) \"{}\"",
+                value.unwrap_or_else(|| "NULL".to_string()),
+                ty,
+                name.to_uppercase()
+            ));
+        }
+        CXQuery::naked(format!("SELECT {} FROM dual", columns.join(", ")))
+    }
+
+    /// Run a query returning exactly one row of one column, e.g. `SELECT COUNT(*) FROM t`, and read
+    /// the value as `T` through the `Produce` impls of the parser. Use `Option<T>` if the value can
+    /// be NULL.
+    #[throws(OracleSourceError)]
+    pub fn query_scalar<T>(&self, query: &str) -> T
+    where
+        for<'r, 'a> OracleTextSourceParser<'a>: Produce<'r, T, Error = OracleSourceError>,
+    {
+        let conn = self.get_conn()?;
+        let mut parser =
+            OracleTextSourceParser::new(&conn, query, &[OracleTypeSystem::VarChar(true)])?;
+        parser.decimal_scale = self
+            .decimal_scale
+            .map(|scale| (scale, self.decimal_rounding));
+        parser.strict = self.strict;
+        parser.iso8601 = self.iso8601;
+        let (n, _) = parser.fetch_next()?;
+        if n != 1 {
+            throw!(anyhow!("scalar query returned {} rows, expected 1", n));
+        }
+        let ncols = parser.rowbuf[0].sql_values().len();
+        if ncols != 1 {
+            throw!(anyhow!(
+                "scalar query returned {} columns, expected 1",
+                ncols
+            ));
+        }
+        parser.produce()?
+    }
+}
+
+#[cfg(feature = "dst_arrow")]
+impl WritableSource for OracleSource {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn merge(&self, target_table: &str, key_cols: &[&str], batch: &RecordBatch) -> MergeStats {
+        if batch.num_rows() == 0 {
+            return MergeStats::default();
+        }
+        let conn = self.get_conn()?;
+        // the count and the merge belong to the same transaction, which is rolled back on failure
+        // rather than left open on the pooled connection
+        let merge = || -> Result<usize, OracleSourceError> {
+            let mut updated = 0;
+            for chunk in chunks(batch) {
+                updated += conn.query_row_as::<usize>(
+                    &matched_count_query(target_table, key_cols, &chunk, &OracleDialect {})?,
+                    &[],
+                )?;
+                conn.execute(&merge_query_oracle(target_table, key_cols, &chunk)?, &[])?;
+            }
+            Ok(updated)
+        };
+        let updated = match merge() {
+            Ok(updated) => updated,
+            Err(e) => {
+                conn.rollback()?;
+                throw!(e);
+            }
+        };
+        conn.commit()?;
+        MergeStats {
+            inserted: batch.num_rows() - updated,
+            updated,
+        }
+    }
+}
+
+/// The parts of a possibly qualified table name as written in SQL, e.g. `hr."Employees"`, as
+/// stored in the catalog: unquoted parts in upper case, quoted ones as they are.
+#[cfg(feature = "dst_arrow")]
+fn catalog_name(table: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    for c in table.trim().chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            '.' if !in_quotes => {
+                parts.push(match quoted {
+                    true => std::mem::take(&mut part),
+                    false => std::mem::take(&mut part).trim().to_uppercase(),
+                });
+                quoted = false;
+            }
+            _ => part.push(c),
+        }
+    }
+    parts.push(match quoted {
+        true => part,
+        false => part.trim().to_uppercase(),
+    });
+    parts
+}
+
+#[cfg(feature = "dst_arrow")]
+impl TableScan for OracleSource {
+    #[throws(OracleSourceError)]
+    fn describe_table(&self, table: &str) -> Vec<String> {
+        let (owner, table) = match catalog_name(table).as_slice() {
+            [table] => (None, table.clone()),
+            [owner, table] => (Some(owner.clone()), table.clone()),
+            _ => throw!(anyhow!("invalid table name {}", table)),
+        };
+        let conn = self.get_conn()?;
+        let mut columns = vec![];
+        for row in conn.query(
+            "SELECT column_name FROM all_tab_columns \
+             WHERE owner = NVL(:1, USER) AND table_name = :2 ORDER BY column_id",
+            &[&owner, &table],
+        )? {
+            columns.push(row?.get(0)?);
+        }
+        columns
+    }
+
+    fn quote_table(table: &str) -> String {
+        catalog_name(table)
+            .iter()
+            .map(|part| Self::quote_column(part))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    fn quote_column(column: &str) -> String {
+        format!("\"{}\"", column.replace('"', "\"\""))
+    }
+
+    fn table_scan_query(table: &str, columns: &[String], limit: Option<usize>) -> String {
+        let mut query = format!("SELECT {} FROM {}", columns.join(", "), table);
+        if let Some(n) = limit {
+            query.push_str(&format!(" WHERE ROWNUM <= {}", n));
         }
+        query
     }
 }
 
@@ -108,68 +1948,88 @@ where
     }
 
     fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+        // a comment ahead of a PL/SQL block is not allowed
+        let label = self.label.as_deref().filter(|_| !self.refcursor);
+        self.queries = queries
+            .iter()
+            .map(|q| label_query(q.map(Q::to_string), label))
+            .collect();
+        self.rowscn_added = false;
+    }
+
+    fn with_query_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
     }
 
     fn set_origin_query(&mut self, query: Option<String>) {
         self.origin_query = query;
     }
 
+    fn origin_query(&self) -> Option<String> {
+        self.origin_query.clone()
+    }
+
     #[throws(OracleSourceError)]
     fn fetch_metadata(&mut self) {
         assert!(!self.queries.is_empty());
 
-        let conn = self.pool.get()?;
-        for (i, query) in self.queries.iter().enumerate() {
-            // assuming all the partition queries yield same schema
-            // without rownum = 1, derived type might be wrong
-            // example: select avg(test_int), test_char from test_table group by test_char
-            // -> (NumInt, Char) instead of (NumtFloat, Char)
-            match conn.query(limit1_query_oracle(query)?.as_str(), &[]) {
-                Ok(rows) => {
-                    let (names, types) = rows
-                        .column_info()
-                        .iter()
-                        .map(|col| {
-                            (
-                                col.name().to_string(),
-                                OracleTypeSystem::from(col.oracle_type()),
-                            )
-                        })
-                        .unzip();
-                    self.names = names;
-                    self.schema = types;
-                    return;
-                }
-                Err(e) if i == self.queries.len() - 1 => {
-                    // tried the last query but still get an error
-                    debug!("cannot get metadata for '{}': {}", query, e);
-                    throw!(e);
-                }
-                Err(_) => {}
+        if self.refcursor {
+            let conn = self.get_conn()?;
+            let (names, types, descriptors) = describe_refcursor(&conn, self.queries[0].as_str())?;
+            self.names = names;
+            self.schema = types;
+            self.descriptors = descriptors;
+            self.map_decimal128_types();
+            self.map_iso8601_types();
+            return;
+        }
+        if self.include_rowscn && !self.rowscn_added {
+            // the rewrite drops the comments of the queries, label them again
+            self.queries = self
+                .queries
+                .iter()
+                .map(|q| Ok(label_query(rowscn_query_oracle(q)?, self.label.as_deref())))
+                .collect::<Result<_, OracleSourceError>>()?;
+            self.rowscn_added = true;
+        }
+        self.probe_metadata()?;
+        if self.include_rowscn {
+            // a NUMBER without scale, which would be read as a float otherwise
+            if let Some(ty) = self.schema.last_mut() {
+                *ty = OracleTypeSystem::NumInt(false);
+            }
+            if let Some(col) = self.descriptors.last_mut() {
+                col.type_system = OracleTypeSystem::NumInt(false);
             }
         }
-        // tried all queries but all get empty result set
-        let iter = conn.query(self.queries[0].as_str(), &[])?;
-        let (names, types) = iter
-            .column_info()
-            .iter()
-            .map(|col| (col.name().to_string(), OracleTypeSystem::VarChar(false)))
-            .unzip();
-        self.names = names;
-        self.schema = types;
+        if self.fetch_lob_storage {
+            self.lookup_lob_storage()?;
+        }
+        self.map_decimal128_types();
+        self.map_score_types();
+        self.map_iso8601_types();
     }
 
     #[throws(OracleSourceError)]
     fn result_rows(&mut self) -> Option<usize> {
         match &self.origin_query {
+            Some(q) if self.skips_count(q) => None,
             Some(q) => {
-                let cxq = CXQuery::Naked(q.clone());
-                let conn = self.pool.get()?;
-
-                let nrows = conn
-                    .query_row_as::<usize>(count_query(&cxq, &OracleDialect {})?.as_str(), &[])?;
-                Some(nrows)
+                let mut cxq = CXQuery::Naked(q.clone());
+                // count the snapshot the partitions read, see `partition`
+                if let Some(flashback) = self.flashback.as_ref().filter(|_| !self.refcursor) {
+                    cxq = flashback_query_oracle(&cxq, flashback)?;
+                }
+                let count = count_query_oracle(&cxq, &mut self.alias_generator()?);
+                let conn = self.get_conn()?;
+                let nrows = with_call_timeout(&conn, self.count_timeout, || {
+                    Ok(conn.query_row_as::<usize>(count.as_str(), &[])?)
+                })?;
+                // the tail limit cuts the whole result, see `partition`
+                match &self.tail {
+                    Some((_, Some(limit))) if !self.refcursor => Some(nrows.min(*limit)),
+                    _ => Some(nrows),
+                }
             }
             None => None,
         }
@@ -184,11 +2044,174 @@ where
     }
 
     #[throws(OracleSourceError)]
-    fn partition(self) -> Vec<Self::Partition> {
+    fn health_check(&self, timeout: Duration) -> HealthStatus {
+        probe_health(timeout, || -> Result<(), OracleSourceError> {
+            let conn = self.pool.get_timeout(timeout)?;
+            // a call timeout of 0 disables it
+            let call_timeout = timeout.max(Duration::from_millis(1));
+            with_call_timeout(&conn, Some(call_timeout), || {
+                conn.query_row("SELECT 1 FROM dual", &[])?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Count on a connection of the pool, leaving the origin query as it is. Unlike
+    /// [`Source::result_rows`], the query is counted even if the count is skipped for the loads
+    /// (see [`OracleSource::skip_count`]). The [`OracleSource::count_timeout`] applies.
+    #[throws(OracleSourceError)]
+    fn count(&mut self, query: &str) -> u64 {
+        let count = count_query_oracle(
+            &CXQuery::Naked(query.to_string()),
+            &mut self.alias_generator()?,
+        );
+        let conn = self.get_conn()?;
+        let nrows = with_call_timeout(&conn, self.count_timeout, || {
+            Ok(conn.query_row_as::<usize>(count.as_str(), &[])?)
+        })?;
+        nrows as u64
+    }
+
+    #[throws(OracleSourceError)]
+    fn partition(mut self) -> Vec<Self::Partition> {
         let mut ret = vec![];
-        for query in self.queries {
-            let conn = self.pool.get()?;
-            ret.push(OracleSourcePartition::new(conn, &query, &self.schema));
+        // taken rather than moved out, `self` is still used to check out the connections
+        let queries = std::mem::take(&mut self.queries);
+        // the PL/SQL blocks opening cursors are executed as they are
+        let rewrite = !self.refcursor;
+        // the tail limit applies to the whole result, which is then read as a single partition
+        let tail_limit = self
+            .tail
+            .as_ref()
+            .and_then(|(_, limit)| *limit)
+            .filter(|_| rewrite);
+        let queries = match (tail_limit, &self.origin_query) {
+            (Some(_), Some(origin)) => vec![CXQuery::Naked(origin.clone())],
+            (Some(_), None) if queries.len() > 1 => {
+                throw!(anyhow!(
+                    "a tail limit over {} partition queries needs the origin query",
+                    queries.len()
+                ))
+            }
+            _ => queries,
+        };
+        // each query is rewritten with its own aliases, sized for the server
+        let aliases = self.alias_generator()?;
+        let queries: Vec<_> = queries.into_iter().map(|q| (q, aliases.clone())).collect();
+        let bounds = self
+            .bounds
+            .as_ref()
+            .filter(|_| rewrite && tail_limit.is_none());
+        let queries: Vec<_> = match bounds {
+            Some((col, bounds)) => queries
+                .iter()
+                .flat_map(|(q, aliases)| {
+                    bounds.iter().map(move |bound| {
+                        let mut aliases = aliases.clone();
+                        let q = bounds_partition_query_oracle(q.as_str(), col, bound, &mut aliases);
+                        (CXQuery::Wrapped(q), aliases)
+                    })
+                })
+                .collect(),
+            None => queries,
+        };
+        let queries: Vec<_> = match self.flashback.as_ref().filter(|_| rewrite) {
+            Some(flashback) => queries
+                .into_iter()
+                .map(|(q, aliases)| Ok((flashback_query_oracle(&q, flashback)?, aliases)))
+                .collect::<Result<_, OracleSourceError>>()?,
+            None => queries,
+        };
+        let queries: Vec<_> = match self.tail.as_ref().filter(|_| rewrite) {
+            Some((col, limit)) => queries
+                .into_iter()
+                .rev()
+                .map(|(q, mut aliases)| (tail_query_oracle(&q, col, *limit, &mut aliases), aliases))
+                .collect(),
+            None => queries,
+        };
+        let clobs: Vec<bool> = self
+            .schema
+            .iter()
+            .map(|ty| matches!(ty, OracleTypeSystem::Clob(_)))
+            .collect();
+        let lob_prefetch = self
+            .lob_prefetch
+            .filter(|_| rewrite && clobs.iter().any(|&c| c));
+        // measured statistics take precedence over the widths of the columns of each query
+        let per_query = self
+            .adaptive_buf_size
+            .filter(|_| rewrite && queries.len() > 1 && self.column_stats.is_none());
+        let mut money = vec![None; self.names.len()];
+        for (name, scale) in &self.money {
+            let idx = self
+                .names
+                .iter()
+                .position(|n| n.eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow!("money column {} is not in the result", name))?;
+            money[idx] = Some((*scale, self.decimal_rounding));
+        }
+        // an unconstrained `NUMBER` is described as `NUMBER(0,0)`, its values keep their own scale
+        let scales: Vec<_> = self
+            .descriptors
+            .iter()
+            .map(|d| d.scale.filter(|_| d.precision != Some(0)))
+            .collect();
+        let order = self.partition_order();
+        for (i, (query, mut aliases)) in queries.into_iter().enumerate() {
+            let conn = self.get_conn()?;
+            let buf_size = match per_query {
+                Some(adaptive) => {
+                    let (_, _, descriptors) =
+                        describe_query(&conn, query.as_str(), self.lock_retry)?;
+                    adaptive.rows(&descriptors)
+                }
+                None => self.buf_size(),
+            };
+            let query = match lob_prefetch {
+                Some(size) => {
+                    lob_prefetch_query_oracle(&query, &self.names, &clobs, size, &mut aliases)
+                }
+                None => query,
+            };
+            let unordered = query.clone();
+            let query = match order.is_empty() || !rewrite {
+                true => query,
+                false => order_query_oracle(&query, &order, &mut aliases),
+            };
+            let query = match self.parallel_degree.filter(|_| rewrite) {
+                Some(degree) => parallel_query_oracle(&query, degree, &mut aliases)?,
+                None => query,
+            };
+            let mut partition = OracleSourcePartition::new(conn, &query, &self.schema, buf_size);
+            if !order.is_empty() && rewrite {
+                partition.count_query = Some(unordered);
+            }
+            partition.decimal_scale = self.decimal_scale.map(|s| (s, self.decimal_rounding));
+            partition.strict = self.strict;
+            partition.money = money.clone();
+            partition.names = self.names.clone();
+            partition.scales = scales.clone();
+            partition.iso8601 = self.iso8601;
+            partition.lob_prefetch = lob_prefetch.is_some();
+            partition.lock_retry = self.lock_retry;
+            partition.count_timeout = self.count_timeout;
+            partition.fetch_timeout = self.fetch_timeout;
+            partition.refcursor = self.refcursor;
+            partition.sql_plans = self
+                .sql_plans
+                .clone()
+                .map(|(plans, baseline)| (plans, i, baseline));
+            partition.skip_count = self.skips_count(partition.query.as_str());
+            partition.row_errors = self.row_errors.clone().map(|errors| (errors, i));
+            partition.type_handlers = self.type_handlers.clone();
+            partition.aliases = Some(aliases);
+            if let Some(cache) = &self.query_cache {
+                let context = self.cache_context();
+                partition.cached = cache.get(&context, partition.query.as_str());
+                partition.query_cache = Some((cache.clone(), context));
+            }
+            ret.push(partition);
         }
         ret
     }
@@ -200,18 +2223,96 @@ pub struct OracleSourcePartition {
     schema: Vec<OracleTypeSystem>,
     nrows: usize,
     ncols: usize,
+    decimal_scale: Option<(u32, RoundingStrategy)>,
+    strict: bool,
+    lob_prefetch: bool,
+    lock_retry: Option<(u32, Duration)>,
+    count_timeout: Option<Duration>,
+    fetch_timeout: Option<Duration>,
+    buf_size: u32,
+    /// The cache and the context of the query in it
+    query_cache: Option<(Arc<QueryCache>, String)>,
+    cached: Option<CachedRows>,
+    refcursor: bool,
+    /// Where to record the statement, the index of the partition and the baseline plan hash
+    sql_plans: Option<(SqlPlans, usize, Option<u64>)>,
+    /// The query counted instead of `query`, e.g. without its sort
+    count_query: Option<CXQuery<String>>,
+    iso8601: Option<Iso8601Format>,
+    skip_count: bool,
+    /// The fixed scale of the money columns, by column index
+    money: Vec<Option<(u32, RoundingStrategy)>>,
+    names: Vec<String>,
+    /// The scale of the `NUMBER(p,s)` columns, by column index
+    scales: Vec<Option<i8>>,
+    /// Where to report the skipped rows, and the index of the partition
+    row_errors: Option<(RowErrors, usize)>,
+    type_handlers: HashMap<String, Arc<TypeHandler>>,
+    /// The aliases of the rewrites of `query`, for its count query
+    aliases: Option<AliasGenerator>,
 }
 
 impl OracleSourcePartition {
-    pub fn new(conn: OracleConn, query: &CXQuery<String>, schema: &[OracleTypeSystem]) -> Self {
+    pub fn new(
+        conn: OracleConn,
+        query: &CXQuery<String>,
+        schema: &[OracleTypeSystem],
+        buf_size: u32,
+    ) -> Self {
         Self {
             conn,
             query: query.clone(),
             schema: schema.to_vec(),
             nrows: 0,
             ncols: schema.len(),
+            decimal_scale: None,
+            strict: false,
+            lob_prefetch: false,
+            lock_retry: None,
+            count_timeout: None,
+            fetch_timeout: None,
+            buf_size,
+            query_cache: None,
+            cached: None,
+            refcursor: false,
+            sql_plans: None,
+            count_query: None,
+            iso8601: None,
+            skip_count: false,
+            money: vec![],
+            names: vec![],
+            scales: vec![],
+            row_errors: None,
+            type_handlers: HashMap::new(),
+            aliases: None,
         }
     }
+
+    /// Whether the rows of this partition are served from memory: from the query cache (see
+    /// [`OracleSource::with_query_cache`]), or read by `result_rows` when the count is skipped.
+    pub fn is_cached(&self) -> bool {
+        self.cached.is_some()
+    }
+
+    /// Whether this partition is counted by reading its rows rather than by a `COUNT(*)` query,
+    /// see [`OracleSource::skip_count`].
+    pub fn skips_count(&self) -> bool {
+        self.skip_count
+    }
+
+    /// The number of rows fetched per round trip by this partition.
+    #[must_use]
+    pub fn buf_size(&self) -> u32 {
+        self.buf_size
+    }
+
+    /// Re-execute the query of this partition on the same pooled connection and get a fresh parser
+    /// starting from the first row, e.g. for destinations that need multiple passes over the data.
+    /// Since the query is executed again, the rows of each pass may differ if the data changes in between.
+    #[throws(OracleSourceError)]
+    pub fn reset(&mut self) -> OracleTextSourceParser<'_> {
+        self.parser()?
+    }
 }
 
 impl SourcePartition for OracleSourcePartition {
@@ -221,17 +2322,111 @@ impl SourcePartition for OracleSourcePartition {
 
     #[throws(OracleSourceError)]
     fn result_rows(&mut self) {
-        self.nrows = self
-            .conn
-            .query_row_as::<usize>(count_query(&self.query, &OracleDialect {})?.as_str(), &[])?;
+        self.nrows = match &self.cached {
+            Some(rows) => rows.len(),
+            None if self.refcursor => {
+                let mut cursor = open_refcursor(&self.conn, self.query.as_str())?;
+                let mut nrows = 0;
+                for row in cursor.query()? {
+                    row?;
+                    nrows += 1;
+                }
+                nrows
+            }
+            None if self.skip_count => {
+                // served from memory by the parser afterwards
+                let rows = self.parser()?.read_all()?;
+                let nrows = rows.len();
+                self.cached = Some(Arc::new(rows));
+                nrows
+            }
+            None => {
+                let query = self.count_query.as_ref().unwrap_or(&self.query);
+                let count = match &mut self.aliases {
+                    Some(aliases) => count_query_oracle(query, aliases),
+                    None => count_query(query, &OracleDialect {})?,
+                };
+                let conn = &self.conn;
+                with_call_timeout(conn, self.count_timeout, || {
+                    Ok(conn.query_row_as::<usize>(count.as_str(), &[])?)
+                })?
+            }
+        };
     }
 
     #[throws(OracleSourceError)]
     fn parser(&mut self) -> Self::Parser<'_> {
+        if let Some(rows) = &self.cached {
+            return OracleTextSourceParser::from_cache(rows.clone(), &self.schema);
+        }
         let query = self.query.clone();
+        if self.fetch_timeout.is_some() {
+            // kept for the fetches of the parser, reset when the connection is checked out again
+            self.conn.set_call_timeout(self.fetch_timeout)?;
+        }
 
         // let iter = self.conn.query(query.as_str(), &[])?;
-        OracleTextSourceParser::new(&self.conn, query.as_str(), &self.schema)?
+        let mut parser = if self.refcursor {
+            OracleTextSourceParser::from_refcursor(&self.conn, query.as_str(), &self.schema)?
+        } else {
+            OracleTextSourceParser::with_options(
+                &self.conn,
+                query.as_str(),
+                &self.schema,
+                self.buf_size,
+                self.lock_retry,
+            )?
+        };
+        parser.decimal_scale = self.decimal_scale;
+        parser.strict = self.strict;
+        if let (Some((plans, partition, baseline)), false) = (&self.sql_plans, self.refcursor) {
+            let (sql_id, plan_hash) = self
+                .conn
+                .query_row_as::<(String, u64)>(SQL_PLAN_QUERY, &[])?;
+            debug!(
+                "partition {} executed {} with plan hash {}",
+                partition, sql_id, plan_hash
+            );
+            plans.record(SqlPlan {
+                partition: *partition,
+                sql_id: sql_id.clone(),
+                plan_hash,
+            });
+            if let Some(expected) = baseline.filter(|&expected| expected != plan_hash) {
+                throw!(OracleSourceError::PlanChanged(sql_id, expected, plan_hash));
+            }
+        }
+        parser.money = self.money.clone();
+        parser.names = self.names.clone();
+        parser.scales = self.scales.clone();
+        parser.iso8601 = self.iso8601;
+        parser.row_errors = self.row_errors.clone();
+        parser.type_handlers = self.type_handlers.clone();
+        if self.lob_prefetch {
+            // see `lob_prefetch_query_oracle` for the layout of the columns
+            let mut next = self.schema.len();
+            parser.lob_fallback = self
+                .schema
+                .iter()
+                .map(|ty| match ty {
+                    OracleTypeSystem::Clob(_) => {
+                        next += 1;
+                        Some(next - 1)
+                    }
+                    _ => None,
+                })
+                .collect();
+        }
+        if let Some((cache, context)) = &self.query_cache {
+            parser.recording = Some(Recording {
+                cache: cache.clone(),
+                context: context.clone(),
+                query: query.to_string(),
+                rows: vec![],
+                bytes: 0,
+            });
+        }
+        parser
     }
 
     fn nrows(&self) -> usize {
@@ -243,34 +2438,269 @@ impl SourcePartition for OracleSourcePartition {
     }
 }
 
+/// The result set read by a parser, of a query or of the cursor opened by a PL/SQL block.
+enum ParserRows<'a> {
+    Query(StatementRows<'a>),
+    Cursor(OwningHandle<Box<RefCursor>, DummyBox<ResultSet<'a, Row>>>),
+}
+
+impl<'a> ParserRows<'a> {
+    fn next(&mut self) -> Option<r2d2_oracle::oracle::Result<Row>> {
+        match self {
+            ParserRows::Query(rows) => (**rows).next(),
+            ParserRows::Cursor(rows) => (**rows).next(),
+        }
+    }
+}
+
 pub struct OracleTextSourceParser<'a> {
-    rows: OwningHandle<Box<Statement<'a>>, DummyBox<ResultSet<'a, Row>>>,
+    /// `None` when the rows are served from the query cache
+    rows: Option<ParserRows<'a>>,
     rowbuf: Vec<Row>,
+    schema: Vec<OracleTypeSystem>,
     ncols: usize,
     current_col: usize,
     current_row: usize,
+    decimal_scale: Option<(u32, RoundingStrategy)>,
+    strict: bool,
+    lob_fallback: Vec<Option<usize>>,
+    /// The cached rows and the range of them in the current batch.
+    cached: Option<(CachedRows, usize, usize)>,
+    recording: Option<Recording>,
+    iso8601: Option<Iso8601Format>,
+    money: Vec<Option<(u32, RoundingStrategy)>>,
+    /// The names of the columns, for the errors
+    names: Vec<String>,
+    scales: Vec<Option<i8>>,
+    row_errors: Option<(RowErrors, usize)>,
+    /// The number of rows fetched so far, the skipped ones included
+    fetched: usize,
+    type_handlers: HashMap<String, Arc<TypeHandler>>,
+}
+
+/// The rows read so far by a parser whose query is to be cached once they are all read.
+struct Recording {
+    cache: Arc<QueryCache>,
+    context: String,
+    query: String,
+    rows: Vec<Vec<CachedValue>>,
+    bytes: usize,
 }
 
 impl<'a> OracleTextSourceParser<'a> {
     #[throws(OracleSourceError)]
     pub fn new(conn: &'a OracleConn, query: &str, schema: &[OracleTypeSystem]) -> Self {
-        let stmt = conn
-            .statement(query)
-            .prefetch_rows(ORACLE_ARRAY_SIZE)
-            .fetch_array_size(ORACLE_ARRAY_SIZE)
-            .build()?;
-        let rows: OwningHandle<Box<Statement<'a>>, DummyBox<ResultSet<'a, Row>>> =
-            OwningHandle::new_with_fn(Box::new(stmt), |stmt: *const Statement<'a>| unsafe {
-                DummyBox((&mut *(stmt as *mut Statement<'_>)).query(&[]).unwrap())
-            });
+        Self::with_options(conn, query, schema, ORACLE_ARRAY_SIZE, None)?
+    }
+
+    #[throws(OracleSourceError)]
+    fn with_options(
+        conn: &'a OracleConn,
+        query: &str,
+        schema: &[OracleTypeSystem],
+        buf_size: u32,
+        lock_retry: Option<(u32, Duration)>,
+    ) -> Self {
+        // `next_loc` walks the buffer modulo the number of columns
+        if schema.is_empty() {
+            throw!(OracleSourceError::EmptySchema);
+        }
+        let rows = query_with_lock_retry(lock_retry, || {
+            conn.statement(query)
+                .prefetch_rows(buf_size)
+                .fetch_array_size(buf_size)
+                .build()
+        })?;
+        Self::with_rows(Some(ParserRows::Query(rows)), schema)
+    }
+
+    /// A parser of the cursor opened by the PL/SQL `block`, see
+    /// [`OracleSource::from_sys_refcursor`].
+    #[throws(OracleSourceError)]
+    fn from_refcursor(conn: &'a OracleConn, block: &str, schema: &[OracleTypeSystem]) -> Self {
+        if schema.is_empty() {
+            throw!(OracleSourceError::EmptySchema);
+        }
+        let cursor = open_refcursor(conn, block)?;
+        let rows: OwningHandle<Box<RefCursor>, DummyBox<ResultSet<'a, Row>>> =
+            OwningHandle::try_new(Box::new(cursor), |cursor: *const RefCursor| unsafe {
+                (*(cursor as *mut RefCursor)).query().map(DummyBox)
+            })?;
+        Self::with_rows(Some(ParserRows::Cursor(rows)), schema)
+    }
 
+    /// A parser of rows served from the query cache, without executing the query.
+    fn from_cache(rows: CachedRows, schema: &[OracleTypeSystem]) -> Self {
+        Self {
+            cached: Some((rows, 0, 0)),
+            ..Self::with_rows(None, schema)
+        }
+    }
+
+    fn with_rows(rows: Option<ParserRows<'a>>, schema: &[OracleTypeSystem]) -> Self {
         Self {
             rows,
             rowbuf: Vec::with_capacity(DB_BUFFER_SIZE),
+            schema: schema.to_vec(),
             ncols: schema.len(),
             current_row: 0,
             current_col: 0,
+            decimal_scale: None,
+            strict: false,
+            lob_fallback: vec![],
+            cached: None,
+            recording: None,
+            iso8601: None,
+            money: vec![],
+            names: vec![],
+            scales: vec![],
+            row_errors: None,
+            fetched: 0,
+            type_handlers: HashMap::new(),
+        }
+    }
+
+    /// Read a cell of the current batch, from the row buffer or from the cached rows.
+    #[throws(OracleSourceError)]
+    fn cell<T: FromSql + FromCached>(&self, ridx: usize, cidx: usize) -> Option<T> {
+        match &self.cached {
+            Some((rows, start, _)) => T::from_cached(&rows[start + ridx][cidx])?,
+            None => self.rowbuf[ridx].get(cidx)?,
+        }
+    }
+
+    /// Read a row of the row buffer as the values to cache.
+    #[throws(OracleSourceError)]
+    fn cache_row(&self, ridx: usize) -> Vec<CachedValue> {
+        let row = &self.rowbuf[ridx];
+        let mut values = Vec::with_capacity(self.ncols);
+        for (cidx, ty) in self.schema.iter().enumerate() {
+            let value = match ty {
+                OracleTypeSystem::NumInt(_)
+                | OracleTypeSystem::Float(_)
+                | OracleTypeSystem::NumFloat(_)
+                | OracleTypeSystem::Decimal(_) => row
+                    .get::<usize, Option<String>>(cidx)?
+                    .map(CachedValue::Text),
+                OracleTypeSystem::BinaryFloat(_) | OracleTypeSystem::BinaryDouble(_) => {
+                    row.get::<usize, Option<f64>>(cidx)?.map(CachedValue::Float)
+                }
+                OracleTypeSystem::Blob(_) => row
+                    .get::<usize, Option<Vec<u8>>>(cidx)?
+                    .map(CachedValue::Bytes),
+                OracleTypeSystem::Date(_) | OracleTypeSystem::Timestamp(_) => row
+                    .get::<usize, Option<NaiveDateTime>>(cidx)?
+                    .map(CachedValue::DateTime),
+                OracleTypeSystem::TimestampTz(_) => row
+                    .get::<usize, Option<DateTime<Utc>>>(cidx)?
+                    .map(CachedValue::TimestampTz),
+                OracleTypeSystem::Clob(_)
+                | OracleTypeSystem::VarChar(_)
+                | OracleTypeSystem::Char(_)
+                | OracleTypeSystem::NVarChar(_)
+                | OracleTypeSystem::NChar(_) => self.string(ridx, cidx)?.map(CachedValue::Text),
+            };
+            values.push(value.unwrap_or(CachedValue::Null));
+        }
+        values
+    }
+
+    /// Read all the remaining rows, as the values to cache.
+    #[throws(OracleSourceError)]
+    fn read_all(mut self) -> Vec<Vec<CachedValue>> {
+        let mut rows = vec![];
+        loop {
+            let (n, is_last) = self.fetch_next()?;
+            for ridx in 0..n {
+                rows.push(self.cache_row(ridx)?);
+            }
+            if is_last {
+                break;
+            }
+        }
+        rows
+    }
+
+    /// Add the rows of the current batch to the recording, dropping it once the rows are too
+    /// large to be cached, and cache them after the last batch.
+    #[throws(OracleSourceError)]
+    fn record(&mut self, is_last: bool) {
+        if self.recording.is_none() {
+            return;
+        }
+        let rows = (0..self.rowbuf.len())
+            .map(|ridx| self.cache_row(ridx))
+            .collect::<Result<Vec<_>, _>>()?;
+        let recording = self.recording.as_mut().unwrap();
+        recording.bytes += rows.iter().flatten().map(CachedValue::size).sum::<usize>();
+        recording.rows.extend(rows);
+        if !recording.cache.fits(recording.rows.len(), recording.bytes) {
+            self.recording = None;
+        } else if is_last {
+            let recording = self.recording.take().unwrap();
+            recording
+                .cache
+                .insert(&recording.context, &recording.query, recording.rows);
+        }
+    }
+
+    /// Read a value of the object type `ty` with the handler registered for the type.
+    #[throws(OracleSourceError)]
+    fn object(&self, ty: &ObjectType, value: &SqlValue) -> Option<String> {
+        let qualified = format!("{}.{}", ty.schema(), ty.name()).to_uppercase();
+        let handler = self
+            .type_handlers
+            .get(&qualified)
+            .or_else(|| self.type_handlers.get(&ty.name().to_uppercase()))
+            .ok_or_else(|| anyhow!("no type handler registered for {}", qualified))?;
+        match value.is_null()? {
+            true => None,
+            false => Some(handler(value)?),
+        }
+    }
+
+    /// Remove the rows of the row buffer whose values cannot be produced and report them, under
+    /// [`RowErrorPolicy::SkipAndCollect`]. Each value is produced as the type of its column, like
+    /// the transports do, so a CLOB read through its locator is read twice. Returns whether any
+    /// row was skipped.
+    #[throws(OracleSourceError)]
+    fn skip_bad_rows(&mut self) -> bool {
+        let first = self.fetched;
+        self.fetched += self.rowbuf.len();
+        let (errors, partition) = match &self.row_errors {
+            Some((errors, partition)) => (errors.clone(), *partition),
+            None => return false,
+        };
+        let checks = self
+            .schema
+            .iter()
+            .map(|&ty| Realize::<FCheckProduce<'a>>::realize(ty))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut keep = Vec::with_capacity(self.rowbuf.len());
+        for ridx in 0..self.rowbuf.len() {
+            self.current_row = ridx;
+            self.current_col = 0;
+            let failed = checks
+                .iter()
+                .enumerate()
+                .find_map(|(cidx, check)| check(self).err().map(|e| (cidx, e)));
+            if let Some((cidx, e)) = &failed {
+                errors.push(RowError {
+                    partition,
+                    row: first + ridx,
+                    column: self.names.get(*cidx).cloned().unwrap_or_default(),
+                    message: e.to_string(),
+                });
+            }
+            keep.push(failed.is_none());
         }
+        self.current_row = 0;
+        self.current_col = 0;
+
+        let fetched = self.rowbuf.len();
+        let mut keep = keep.into_iter();
+        self.rowbuf.retain(|_| keep.next().unwrap_or(true));
+        self.rowbuf.len() < fetched
     }
 
     #[throws(OracleSourceError)]
@@ -280,6 +2710,83 @@ impl<'a> OracleTextSourceParser<'a> {
         self.current_col = (self.current_col + 1) % self.ncols;
         ret
     }
+
+    /// Parse the `Decimal` value of column `cidx`, narrowed to the scale of the column if it is a
+    /// money column, or to the decimal scale of the source.
+    #[throws(OracleSourceError)]
+    fn decimal(&self, cidx: usize, s: &str) -> Decimal {
+        let value = Decimal::from_str(s)
+            .or_else(|_| Decimal::from_scientific(s))
+            .map_err(|_| anyhow!("cannot parse '{}' as Decimal", s))?;
+        let money = self.money.get(cidx).copied().flatten();
+        match money.or(self.decimal_scale) {
+            Some((scale, strategy)) => {
+                let mut narrowed = value.round_dp_with_strategy(scale, strategy);
+                if self.strict && narrowed != value {
+                    throw!(OracleSourceError::DecimalTruncation(value, scale));
+                }
+                if money.is_some() {
+                    // pad with zeros, the value has at most `scale` digits now
+                    narrowed.rescale(scale);
+                }
+                narrowed
+            }
+            None => value,
+        }
+    }
+
+    /// Read a string cell, taking the CLOB read through the locator if the value was too long to
+    /// be prefetched inline, and formatting the date/time values if they are read as text.
+    #[throws(OracleSourceError)]
+    fn string(&self, ridx: usize, cidx: usize) -> Option<String> {
+        if let Some((rows, start, _)) = &self.cached {
+            return match (&self.iso8601, &rows[start + ridx][cidx]) {
+                (Some(format), CachedValue::DateTime(dt)) => Some(format.format(*dt)),
+                (Some(format), CachedValue::TimestampTz(ts)) => Some(format.format(ts.naive_utc())),
+                _ => self.cell(ridx, cidx)?,
+            };
+        }
+        let row = &self.rowbuf[ridx];
+        if let OracleType::Object(ty) = row.sql_values()[cidx].oracle_type()? {
+            return self.object(ty, &row.sql_values()[cidx])?;
+        }
+        if let Some(format) = &self.iso8601 {
+            match row.sql_values()[cidx].oracle_type()? {
+                OracleType::Date | OracleType::Timestamp(_) => {
+                    return row
+                        .get::<usize, Option<NaiveDateTime>>(cidx)?
+                        .map(|dt| format.format(dt));
+                }
+                OracleType::TimestampTZ(_) | OracleType::TimestampLTZ(_) => {
+                    return row
+                        .get::<usize, Option<DateTime<Utc>>>(cidx)?
+                        .map(|ts| format.format(ts.naive_utc()));
+                }
+                _ => {}
+            }
+        }
+        match row.get::<usize, Option<String>>(cidx)? {
+            None => match self.lob_fallback.get(cidx) {
+                Some(Some(idx)) => row.get(*idx)?,
+                _ => None,
+            },
+            value => value,
+        }
+    }
+
+    /// Get the untouched `SqlValue` of the current cell and move to the next cell.
+    /// This is an escape hatch for Oracle types that are not mapped by `OracleTypeSystem`.
+    /// The returned value borrows the row buffer, so it is only valid until the next call
+    /// to `next_loc` (i.e. the next `produce` or `produce_raw`). It is not available for the rows
+    /// served from the query cache.
+    #[throws(OracleSourceError)]
+    pub fn produce_raw(&mut self) -> &SqlValue {
+        if self.cached.is_some() {
+            throw!(anyhow!("raw values are not kept in the query cache"));
+        }
+        let (ridx, cidx) = self.next_loc()?;
+        &self.rowbuf[ridx].sql_values()[cidx]
+    }
 }
 
 impl<'a> PartitionParser<'a> for OracleTextSourceParser<'a> {
@@ -288,19 +2795,56 @@ impl<'a> PartitionParser<'a> for OracleTextSourceParser<'a> {
 
     #[throws(OracleSourceError)]
     fn fetch_next(&mut self) -> (usize, bool) {
+        self.current_row = 0;
+        self.current_col = 0;
+        if let Some((rows, start, len)) = &mut self.cached {
+            *start += *len;
+            *len = DB_BUFFER_SIZE.min(rows.len() - *start);
+            return (*len, *start + *len == rows.len());
+        }
         if !self.rowbuf.is_empty() {
             self.rowbuf.drain(..);
         }
+        let rows = self.rows.as_mut().unwrap();
+        let (names, schema) = (&self.names, &self.schema);
         for _ in 0..DB_BUFFER_SIZE {
-            if let Some(item) = (*self.rows).next() {
-                self.rowbuf.push(item?);
+            if let Some(item) = rows.next() {
+                self.rowbuf
+                    .push(item.map_err(|e| fetch_error(e, names, schema))?);
             } else {
                 break;
             }
         }
-        self.current_row = 0;
-        self.current_col = 0;
-        (self.rowbuf.len(), self.rowbuf.len() < DB_BUFFER_SIZE)
+        let is_last = self.rowbuf.len() < DB_BUFFER_SIZE;
+        if self.skip_bad_rows()? {
+            // the rows of the query are not all there
+            self.recording = None;
+        }
+        self.record(is_last)?;
+        (self.rowbuf.len(), is_last)
+    }
+}
+
+/// Produce a value as `T` and drop it, to tell whether it can be produced, see
+/// [`OracleTextSourceParser::skip_bad_rows`].
+struct FCheckProduce<'a>(PhantomData<&'a ()>);
+
+impl<'a> ParameterizedFunc for FCheckProduce<'a> {
+    type Function = fn(&mut OracleTextSourceParser<'a>) -> Result<(), OracleSourceError>;
+}
+
+impl<'a, T> ParameterizedOn<T> for FCheckProduce<'a>
+where
+    OracleTextSourceParser<'a>: for<'r> Produce<'r, T, Error = OracleSourceError>,
+{
+    fn parameterize() -> Self::Function {
+        fn imp<'a, T>(parser: &mut OracleTextSourceParser<'a>) -> Result<(), OracleSourceError>
+        where
+            OracleTextSourceParser<'a>: for<'r> Produce<'r, T, Error = OracleSourceError>,
+        {
+            Produce::<T>::produce(parser).map(|_| ())
+        }
+        imp::<T>
     }
 }
 
@@ -313,8 +2857,7 @@ macro_rules! impl_produce_text {
                 #[throws(OracleSourceError)]
                 fn produce(&'r mut self) -> $t {
                     let (ridx, cidx) = self.next_loc()?;
-                    let res = self.rowbuf[ridx].get(cidx)?;
-                    res
+                    self.cell(ridx, cidx)?.ok_or_else(|| unexpected_null(ridx, cidx))?
                 }
             }
 
@@ -324,8 +2867,7 @@ macro_rules! impl_produce_text {
                 #[throws(OracleSourceError)]
                 fn produce(&'r mut self) -> Option<$t> {
                     let (ridx, cidx) = self.next_loc()?;
-                    let res = self.rowbuf[ridx].get(cidx)?;
-                    res
+                    self.cell(ridx, cidx)?
                 }
             }
         )+
@@ -334,10 +2876,147 @@ macro_rules! impl_produce_text {
 
 impl_produce_text!(
     i64,
+    u64,
     f64,
-    String,
     NaiveDate,
     NaiveDateTime,
     DateTime<Utc>,
     Vec<u8>,
 );
+
+impl<'r, 'a> Produce<'r, Decimal> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> Decimal {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: String = self
+            .cell(ridx, cidx)?
+            .ok_or_else(|| unexpected_null(ridx, cidx))?;
+        self.decimal(cidx, &s)?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<Decimal>> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> Option<Decimal> {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: Option<String> = self.cell(ridx, cidx)?;
+        match s {
+            Some(s) => Some(self.decimal(cidx, &s)?),
+            None => None,
+        }
+    }
+}
+
+/// The mantissa and the scale of an Arrow `Decimal128`, parsed from the text of the value without
+/// an intermediate `Decimal`, see [`decimal128`]. The values of a `NUMBER(p,s)` column all have the
+/// scale `s`.
+impl<'r, 'a> Produce<'r, (i128, i8)> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> (i128, i8) {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: String = self
+            .cell(ridx, cidx)?
+            .ok_or_else(|| unexpected_null(ridx, cidx))?;
+        decimal128(&s, self.scales.get(cidx).copied().flatten())?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<(i128, i8)>> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> Option<(i128, i8)> {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: Option<String> = self.cell(ridx, cidx)?;
+        match s {
+            Some(s) => Some(decimal128(&s, self.scales.get(cidx).copied().flatten())?),
+            None => None,
+        }
+    }
+}
+
+impl<'r, 'a> Produce<'r, IntOrText> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> IntOrText {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: String = self
+            .cell(ridx, cidx)?
+            .ok_or_else(|| unexpected_null(ridx, cidx))?;
+        IntOrText::from(s)
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<IntOrText>> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> Option<IntOrText> {
+        let (ridx, cidx) = self.next_loc()?;
+        let s: Option<String> = self.cell(ridx, cidx)?;
+        s.map(IntOrText::from)
+    }
+}
+
+/// Timestamps as nanoseconds since the unix epoch, in 128 bits so that the timestamps after 2262
+/// (or before 1677) do not overflow as in `i64`. The timestamps with a time zone are converted to
+/// UTC first.
+impl<'r, 'a> Produce<'r, i128> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> i128 {
+        let (ridx, cidx) = self.next_loc()?;
+        let ts: DateTime<Utc> = self
+            .cell(ridx, cidx)?
+            .ok_or_else(|| unexpected_null(ridx, cidx))?;
+        nanos_since_epoch(ts)
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<i128>> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> Option<i128> {
+        let (ridx, cidx) = self.next_loc()?;
+        let ts: Option<DateTime<Utc>> = self.cell(ridx, cidx)?;
+        ts.map(nanos_since_epoch)
+    }
+}
+
+fn unexpected_null(ridx: usize, cidx: usize) -> OracleSourceError {
+    anyhow!("unexpected null value at row {} column {}", ridx, cidx).into()
+}
+
+fn nanos_since_epoch(ts: DateTime<Utc>) -> i128 {
+    ts.timestamp() as i128 * 1_000_000_000 + ts.timestamp_subsec_nanos() as i128
+}
+
+impl<'r, 'a> Produce<'r, String> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> String {
+        let (ridx, cidx) = self.next_loc()?;
+        self.string(ridx, cidx)?
+            .ok_or_else(|| unexpected_null(ridx, cidx))?
+    }
+}
+
+impl<'r, 'a> Produce<'r, Option<String>> for OracleTextSourceParser<'a> {
+    type Error = OracleSourceError;
+
+    #[throws(OracleSourceError)]
+    fn produce(&'r mut self) -> Option<String> {
+        let (ridx, cidx) = self.next_loc()?;
+        self.string(ridx, cidx)?
+    }
+}
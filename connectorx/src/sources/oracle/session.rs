@@ -0,0 +1,238 @@
+//! Settings applied to each connection of the pool as it is established, and again as it is
+//! checked out after they changed.
+
+use r2d2::ManageConnection;
+use r2d2_oracle::oracle::{Connection, Error};
+use r2d2_oracle::OracleConnectionManager;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock};
+
+/// The default `V$SESSION.MODULE` of the connections, see [`super::OracleSource::set_module_name`].
+pub const DEFAULT_MODULE_NAME: &str = "connectorx";
+/// The default `V$SESSION.ACTION` of the connections.
+pub const DEFAULT_ACTION_NAME: &str = "oracle";
+
+/// One of the [`SessionSettings`], which are applied one by one as they change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Setting {
+    Module,
+    ConsumerGroup,
+    TimeZone,
+    Edition,
+    ResultCache,
+    InitSql,
+}
+
+impl Setting {
+    /// In the order they are applied, `InitSql` last.
+    const ALL: [Setting; 6] = [
+        Setting::Module,
+        Setting::ConsumerGroup,
+        Setting::TimeZone,
+        Setting::Edition,
+        Setting::ResultCache,
+        Setting::InitSql,
+    ];
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SessionSettings {
+    pub module: String,
+    pub action: Option<String>,
+    pub consumer_group: Option<String>,
+    /// The zone of the client environment if `None`.
+    pub time_zone: Option<String>,
+    pub edition: Option<String>,
+    /// `RESULT_CACHE_MODE = FORCE` if true, `MANUAL` if false, left as it is if `None`.
+    pub result_cache: Option<bool>,
+    /// Run last, so that they can override the other settings.
+    pub init_sql: Vec<String>,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            module: DEFAULT_MODULE_NAME.to_string(),
+            action: Some(DEFAULT_ACTION_NAME.to_string()),
+            consumer_group: None,
+            time_zone: None,
+            edition: None,
+            result_cache: None,
+            init_sql: vec![],
+        }
+    }
+}
+
+impl SessionSettings {
+    /// The settings that can change the rows a query reads, e.g. the session time zone the
+    /// `TIMESTAMP WITH LOCAL TIME ZONE` values are converted to, to key the query cache with.
+    pub fn cache_key(&self) -> String {
+        format!(
+            "time_zone={:?} edition={:?} init_sql={:?}",
+            self.time_zone, self.edition, self.init_sql
+        )
+    }
+
+    /// Apply all the settings to the new session of `conn`.
+    pub fn apply(&self, conn: &Connection) -> Result<(), Error> {
+        for setting in Setting::ALL {
+            self.apply_setting(setting, conn, true)?;
+        }
+        Ok(())
+    }
+
+    /// Apply `setting` to the session of `conn`. The settings left unset are left as they are on
+    /// a `new` session, and reset on the others where they can be.
+    pub fn apply_setting(
+        &self,
+        setting: Setting,
+        conn: &Connection,
+        new: bool,
+    ) -> Result<(), Error> {
+        match setting {
+            Setting::Module => {
+                conn.execute(
+                    "BEGIN DBMS_APPLICATION_INFO.SET_MODULE(:1, :2); END;",
+                    &[&self.module, &self.action],
+                )?;
+            }
+            Setting::ConsumerGroup => {
+                if let Some(group) = &self.consumer_group {
+                    conn.execute(
+                        "DECLARE old_group VARCHAR2(128); BEGIN \
+                         DBMS_SESSION.SWITCH_CURRENT_CONSUMER_GROUP(:1, old_group, FALSE); END;",
+                        &[group],
+                    )?;
+                }
+            }
+            Setting::TimeZone => {
+                // ALTER SESSION takes no bind variables. LOCAL is the zone the session started
+                // with, i.e. the zone of the client environment
+                let zone = match (&self.time_zone, new) {
+                    (Some(zone), _) => format!("'{}'", zone.replace('\'', "''")),
+                    (None, false) => "LOCAL".to_string(),
+                    (None, true) => return Ok(()),
+                };
+                conn.execute(&format!("ALTER SESSION SET TIME_ZONE = {}", zone), &[])?;
+            }
+            Setting::Edition => {
+                if let Some(edition) = &self.edition {
+                    conn.execute(&format!("ALTER SESSION SET EDITION = {}", edition), &[])?;
+                }
+            }
+            Setting::ResultCache => {
+                if let Some(result_cache) = self.result_cache {
+                    let mode = if result_cache { "FORCE" } else { "MANUAL" };
+                    conn.execute(
+                        &format!("ALTER SESSION SET RESULT_CACHE_MODE = {}", mode),
+                        &[],
+                    )?;
+                }
+            }
+            Setting::InitSql => {
+                for sql in &self.init_sql {
+                    conn.execute(sql, &[])?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The [`SessionSettings`] of a source, shared with its pool. Each change of a setting starts a
+/// new generation, and the connections behind it are brought up to date as they are checked
+/// out, with only the settings changed since.
+#[derive(Debug, Default)]
+pub(crate) struct Session {
+    pub settings: SessionSettings,
+    generation: u64,
+    /// The generation each setting last changed at
+    changed: HashMap<Setting, u64>,
+}
+
+impl Session {
+    /// Keep `settings`, in which `setting` changed, and count `conn`, which it was applied to,
+    /// as up to date.
+    pub fn update(&mut self, setting: Setting, settings: SessionSettings, conn: &mut SessionConn) {
+        self.generation += 1;
+        self.changed.insert(setting, self.generation);
+        self.settings = settings;
+        conn.generation = self.generation;
+    }
+
+    /// Apply the settings changed since `conn` was last brought up to date. A connection they
+    /// fail on is dropped by the pool, since they may be partially applied.
+    pub fn catch_up(&self, conn: &mut SessionConn) -> Result<(), Error> {
+        if conn.generation == self.generation {
+            return Ok(());
+        }
+        for setting in Setting::ALL {
+            if self.changed.get(&setting).copied().unwrap_or(0) > conn.generation {
+                if let Err(e) = self.settings.apply_setting(setting, conn, false) {
+                    conn.broken = true;
+                    return Err(e);
+                }
+            }
+        }
+        conn.generation = self.generation;
+        Ok(())
+    }
+}
+
+/// A connection of the pool, and the generation of the session settings applied to it.
+pub struct SessionConn {
+    conn: Connection,
+    generation: u64,
+    pub(crate) broken: bool,
+}
+
+impl Deref for SessionConn {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl DerefMut for SessionConn {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+/// Establishes the connections of the pool, with the [`SessionSettings`] applied.
+pub struct SessionManager {
+    manager: OracleConnectionManager,
+    session: Arc<RwLock<Session>>,
+}
+
+impl SessionManager {
+    pub(crate) fn new(manager: OracleConnectionManager, session: Arc<RwLock<Session>>) -> Self {
+        Self { manager, session }
+    }
+}
+
+impl ManageConnection for SessionManager {
+    type Connection = SessionConn;
+    type Error = Error;
+
+    fn connect(&self) -> Result<SessionConn, Error> {
+        let conn = self.manager.connect()?;
+        let session = self.session.read().unwrap();
+        session.settings.apply(&conn)?;
+        Ok(SessionConn {
+            conn,
+            generation: session.generation,
+            broken: false,
+        })
+    }
+
+    fn is_valid(&self, conn: &mut SessionConn) -> Result<(), Error> {
+        self.manager.is_valid(&mut conn.conn)
+    }
+
+    fn has_broken(&self, conn: &mut SessionConn) -> bool {
+        conn.broken || self.manager.has_broken(&mut conn.conn)
+    }
+}
@@ -1,7 +1,12 @@
-use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
-use r2d2_oracle::oracle::sql_type::OracleType;
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Timelike, Utc};
+use fehler::{throw, throws};
+use r2d2_oracle::oracle::{sql_type::OracleType, ColumnInfo};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum OracleTypeSystem {
     NumInt(bool),
     Float(bool),
@@ -16,7 +21,12 @@ pub enum OracleTypeSystem {
     NChar(bool),
     Date(bool),
     Timestamp(bool),
+    /// `TIMESTAMP WITH TIME ZONE` and `TIMESTAMP WITH LOCAL TIME ZONE`, the latter read in the
+    /// session time zone, see [`super::OracleSource::set_session_time_zone`].
     TimestampTz(bool),
+    /// A `NUMBER(p,s)` produced as the mantissa and the scale of its exact value, see
+    /// [`super::OracleSource::numbers_as_decimal128`].
+    Decimal(bool),
 }
 
 impl_typesystem! {
@@ -29,6 +39,97 @@ impl_typesystem! {
         { Date => NaiveDate }
         { Timestamp => NaiveDateTime }
         { TimestampTz => DateTime<Utc> }
+        { Decimal => (i128, i8) }
+    }
+}
+
+impl std::fmt::Display for OracleTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use OracleTypeSystem::*;
+        let (name, nullable) = match *self {
+            NumInt(n) => ("INTEGER", n),
+            NumFloat(n) | Decimal(n) => ("NUMBER", n),
+            Float(n) => ("FLOAT", n),
+            BinaryFloat(n) => ("BINARY_FLOAT", n),
+            BinaryDouble(n) => ("BINARY_DOUBLE", n),
+            Blob(n) => ("BLOB", n),
+            Clob(n) => ("CLOB", n),
+            VarChar(n) => ("VARCHAR", n),
+            Char(n) => ("CHAR", n),
+            NVarChar(n) => ("NVARCHAR", n),
+            NChar(n) => ("NCHAR", n),
+            Date(n) => ("DATE", n),
+            Timestamp(n) => ("TIMESTAMP", n),
+            TimestampTz(n) => ("TIMESTAMP WITH TIME ZONE", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// The size in bytes of a value of each type once loaded, by the type it is produced as: 8 for
+/// `i64`, `f64` and the timestamps, 16 for the decimals, 4 for the dates and 50 for the strings
+/// and bytes, whose actual length is unknown. Both the nullable and the non-nullable types are
+/// present, see [`super::OracleSource::estimated_memory_usage`].
+pub fn default_type_widths() -> HashMap<OracleTypeSystem, usize> {
+    use OracleTypeSystem::*;
+    let widths: [(fn(bool) -> OracleTypeSystem, usize); 15] = [
+        (NumInt, 8),
+        (Float, 8),
+        (NumFloat, 8),
+        (BinaryFloat, 8),
+        (BinaryDouble, 8),
+        (Blob, 50),
+        (Clob, 50),
+        (VarChar, 50),
+        (Char, 50),
+        (NVarChar, 50),
+        (NChar, 50),
+        (Date, 4),
+        (Timestamp, 8),
+        (TimestampTz, 8),
+        (Decimal, 16),
+    ];
+    widths
+        .iter()
+        .flat_map(|&(ty, width)| vec![(ty(false), width), (ty(true), width)])
+        .collect()
+}
+
+/// Parse the name of an Oracle type, e.g. `NUMBER(18,2)` or `VARCHAR2(100 CHAR)`, mapped like
+/// the type of a result column. A `NUMBER` with a scale of 0 is an integer.
+impl<'a> TryFrom<&'a str> for OracleTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use OracleTypeSystem::*;
+        let TypeName {
+            name,
+            params,
+            nullable,
+        } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "INTEGER" | "INT" | "SMALLINT" => NumInt,
+            "NUMBER" | "NUMERIC" | "DECIMAL" => match params.get(1).map(String::as_str) {
+                None if !params.is_empty() => NumInt,
+                Some("0") => NumInt,
+                _ => NumFloat,
+            },
+            "FLOAT" => Float,
+            "BINARY_FLOAT" => BinaryFloat,
+            "BINARY_DOUBLE" => BinaryDouble,
+            "BLOB" | "RAW" | "LONG RAW" => Blob,
+            "CLOB" | "NCLOB" | "LONG" => Clob,
+            "VARCHAR" | "VARCHAR2" => VarChar,
+            "CHAR" => Char,
+            "NVARCHAR" | "NVARCHAR2" => NVarChar,
+            "NCHAR" => NChar,
+            "DATE" => Date,
+            "TIMESTAMP" => Timestamp,
+            "TIMESTAMP WITH TIME ZONE" | "TIMESTAMP WITH LOCAL TIME ZONE" => TimestampTz,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
     }
 }
 
@@ -42,16 +143,140 @@ impl<'a> From<&'a OracleType> for OracleTypeSystem {
             OracleType::Float(_) => Float(true),
             OracleType::BinaryFloat => BinaryFloat(true),
             OracleType::BinaryDouble => BinaryDouble(true),
-            OracleType::BLOB => Blob(true),
-            OracleType::CLOB => Clob(true),
+            OracleType::BLOB | OracleType::Raw(_) | OracleType::LongRaw => Blob(true),
+            OracleType::CLOB | OracleType::NCLOB | OracleType::Long => Clob(true),
             OracleType::Char(_) => Char(true),
             OracleType::NChar(_) => NChar(true),
             OracleType::Varchar2(_) => VarChar(true),
             OracleType::NVarchar2(_) => NVarChar(true),
             OracleType::Date => Date(true),
             OracleType::Timestamp(_) => Timestamp(true),
-            OracleType::TimestampTZ(_) => TimestampTz(true),
+            OracleType::TimestampTZ(_) | OracleType::TimestampLTZ(_) => TimestampTz(true),
+            // read as text by the handler registered for the type, see
+            // `OracleSource::register_type_handler`
+            OracleType::Object(_) => VarChar(true),
             _ => unimplemented!("{}", format!("hahaha {:?}", ty)),
         }
     }
 }
+
+/// The full description of a result column, e.g. to build the exact DDL of a destination table.
+#[derive(Debug, Clone)]
+pub struct ColumnDescriptor {
+    pub name: String,
+    pub type_system: OracleTypeSystem,
+    pub nullable: bool,
+    /// Precision of `NUMBER` and `FLOAT`, fractional seconds precision of timestamps.
+    pub precision: Option<u8>,
+    /// Scale of `NUMBER`.
+    pub scale: Option<i8>,
+    /// Maximum length of character and `RAW` columns.
+    pub length: Option<u32>,
+    /// Storage of the LOB columns of a table, only looked up with
+    /// [`super::OracleSource::fetch_lob_storage`].
+    pub lob_storage: Option<LobStorage>,
+}
+
+/// The storage attributes of a LOB column, from `ALL_LOBS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LobStorage {
+    /// A `SECUREFILE` rather than a `BASICFILE` LOB. Only the `SECUREFILE` LOBs can be compressed,
+    /// deduplicated or encrypted.
+    pub securefile: bool,
+    /// The compression level, `LOW`, `MEDIUM` or `HIGH`, `None` if the values are not compressed.
+    pub compression: Option<String>,
+    /// The identical values of the column are stored once.
+    pub deduplication: bool,
+    pub encrypted: bool,
+    /// The values smaller than about 4000 bytes are stored in the row rather than in the LOB
+    /// segment.
+    pub in_row: bool,
+}
+
+impl<'a> From<&'a ColumnInfo> for ColumnDescriptor {
+    fn from(col: &'a ColumnInfo) -> ColumnDescriptor {
+        let ty = col.oracle_type();
+        let (precision, scale, length) = match *ty {
+            OracleType::Number(p, s) => (Some(p), Some(s), None),
+            OracleType::Float(p)
+            | OracleType::Timestamp(p)
+            | OracleType::TimestampTZ(p)
+            | OracleType::TimestampLTZ(p) => (Some(p), None, None),
+            OracleType::Char(n)
+            | OracleType::NChar(n)
+            | OracleType::Varchar2(n)
+            | OracleType::NVarchar2(n)
+            | OracleType::Raw(n) => (None, None, Some(n)),
+            _ => (None, None, None),
+        };
+        ColumnDescriptor {
+            name: col.name().to_string(),
+            type_system: OracleTypeSystem::from(ty),
+            nullable: col.nullable(),
+            precision,
+            scale,
+            length,
+            lob_storage: None,
+        }
+    }
+}
+
+/// The suffix of the ISO-8601 text of the date/time values, see [`Iso8601Format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeZoneSuffix {
+    /// `2020-01-02T03:04:05Z`
+    Z,
+    /// `2020-01-02T03:04:05+00:00`
+    Offset,
+    /// `2020-01-02T03:04:05`
+    Omitted,
+}
+
+/// How the `DATE`, `TIMESTAMP` and `TIMESTAMP WITH (LOCAL) TIME ZONE` columns are produced as
+/// text by [`super::OracleSource::dates_as_iso8601`]. The values with a time zone are converted
+/// to UTC, the others are taken as UTC. Defaults to microseconds and `Z`, e.g.
+/// `2020-01-02T03:04:05.123456Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Iso8601Format {
+    /// Digits of the fractional seconds, at most 9; the extra digits are truncated.
+    pub precision: u8,
+    pub suffix: TimeZoneSuffix,
+}
+
+impl Default for Iso8601Format {
+    fn default() -> Self {
+        Self {
+            precision: 6,
+            suffix: TimeZoneSuffix::Z,
+        }
+    }
+}
+
+impl Iso8601Format {
+    pub fn format(&self, dt: NaiveDateTime) -> String {
+        let mut text = dt.format("%Y-%m-%dT%H:%M:%S").to_string();
+        let precision = self.precision.min(9) as usize;
+        if precision > 0 {
+            // a leap second is carried in the nanoseconds
+            let nanos = format!("{:09}", dt.nanosecond() % 1_000_000_000);
+            text.push('.');
+            text.push_str(&nanos[..precision]);
+        }
+        text.push_str(match self.suffix {
+            TimeZoneSuffix::Z => "Z",
+            TimeZoneSuffix::Offset => "+00:00",
+            TimeZoneSuffix::Omitted => "",
+        });
+        text
+    }
+
+    /// The type of a column produced as text instead of `ty`.
+    pub fn map_type(ty: OracleTypeSystem) -> OracleTypeSystem {
+        match ty {
+            OracleTypeSystem::Date(nullable)
+            | OracleTypeSystem::Timestamp(nullable)
+            | OracleTypeSystem::TimestampTz(nullable) => OracleTypeSystem::VarChar(nullable),
+            ty => ty,
+        }
+    }
+}
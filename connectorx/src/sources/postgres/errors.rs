@@ -23,6 +23,10 @@ pub enum PostgresSourceError {
     #[error(transparent)]
     TlsError(#[from] openssl::error::ErrorStack),
 
+    #[cfg(feature = "dst_arrow")]
+    #[error(transparent)]
+    ArrowError(#[from] arrow::error::ArrowError),
+
     /// Any other errors that are too trivial to be put here explicitly.
     #[error(transparent)]
     Other(#[from] anyhow::Error),
@@ -2,20 +2,38 @@
 
 mod connection;
 mod errors;
+#[cfg(feature = "dst_arrow")]
+mod replication;
 mod typesystem;
 
 pub use self::errors::PostgresSourceError;
 pub use connection::rewrite_tls_args;
+#[cfg(feature = "dst_arrow")]
+pub use replication::{get_replication_stream, PostgresReplicationSource};
 pub use typesystem::PostgresTypeSystem;
 
 use crate::constants::DB_BUFFER_SIZE;
+#[cfg(feature = "oauth")]
+use crate::sources::oauth::{OAuthConfig, OAuthTokenCache};
+#[cfg(feature = "rds_iam")]
+use crate::sources::rds::RdsIamAuthenticator;
+#[cfg(feature = "dst_arrow")]
+use crate::sources::writable::{
+    chunks, matched_count_query, upsert_query_postgres, MergeStats, WritableSource,
+};
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
-    sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, CXQuery},
+    sources::{
+        probe_health,
+        stats::{ColumnStat, ColumnStats},
+        HealthStatus, PartitionParser, Produce, Source, SourcePartition,
+    },
+    sql::{count_query, label_query, single_table, CXQuery},
 };
 use anyhow::anyhow;
+#[cfg(feature = "dst_arrow")]
+use arrow::record_batch::RecordBatch;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter};
 use fehler::{throw, throws};
@@ -34,8 +52,25 @@ use sqlparser::dialect::PostgreSqlDialect;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::marker::PhantomData;
+use std::sync::RwLock;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// The estimated number of rows of a table, `-1` before it is first analyzed.
+const PG_TABLE_ROWS_QUERY: &str =
+    "SELECT reltuples::float8 FROM pg_class WHERE oid = $1::text::regclass";
+
+/// The planner statistics of the columns of a table, with the first and last bounds of their
+/// histogram, which exclude the most common values.
+const PG_COLUMN_STATS_QUERY: &str = "\
+SELECT s.attname::text, s.n_distinct::float8, s.avg_width::float8,
+       (s.histogram_bounds::text::text[])[1],
+       (s.histogram_bounds::text::text[])[array_length(s.histogram_bounds::text::text[], 1)]
+FROM pg_stats s
+JOIN pg_namespace n ON n.nspname = s.schemaname
+JOIN pg_class c ON c.relnamespace = n.oid AND c.relname = s.tablename
+WHERE c.oid = $1::text::regclass";
+
 /// Protocol - Binary based bulk load
 pub enum BinaryProtocol {}
 
@@ -83,11 +118,20 @@ where
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
-    pool: Pool<PgManager<C>>,
+    // replaced when the authentication token is refreshed, see `get_conn`
+    pool: RwLock<Pool<PgManager<C>>>,
+    config: Config,
+    tls: C,
+    #[cfg(feature = "oauth")]
+    oauth: Option<OAuthTokenCache>,
+    #[cfg(feature = "rds_iam")]
+    rds_iam: Option<RdsIamAuthenticator>,
     origin_query: Option<String>,
     queries: Vec<CXQuery<String>>,
+    label: Option<String>,
     names: Vec<String>,
     schema: Vec<PostgresTypeSystem>,
+    column_stats: Option<ColumnStats>,
     _protocol: PhantomData<P>,
 }
 
@@ -98,20 +142,231 @@ where
     C::Stream: Send,
     <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
+    /// The connections are established lazily, when checked out, so that the password of
+    /// `config` may be replaced by a token (see `with_oauth` and `with_rds_iam_auth`) first.
     #[throws(PostgresSourceError)]
     pub fn new(config: Config, tls: C, nconn: usize) -> Self {
-        let manager = PostgresConnectionManager::new(config, tls);
-        let pool = Pool::builder().max_size(nconn as u32).build(manager)?;
+        let manager = PostgresConnectionManager::new(config.clone(), tls.clone());
+        let pool = Pool::builder()
+            .max_size(nconn as u32)
+            .min_idle(Some(0))
+            .build_unchecked(manager);
 
         Self {
-            pool,
+            pool: RwLock::new(pool),
+            config,
+            tls,
+            #[cfg(feature = "oauth")]
+            oauth: None,
+            #[cfg(feature = "rds_iam")]
+            rds_iam: None,
             origin_query: None,
             queries: vec![],
+            label: None,
             names: vec![],
             schema: vec![],
+            column_stats: None,
             _protocol: PhantomData,
         }
     }
+
+    /// Authenticate with an OAuth access token as the password, for the managed databases
+    /// accepting them (e.g. with Azure AD). The token is fetched with the client credentials flow
+    /// and the connections of the pool are established again with it. It is refreshed, along with
+    /// the connections, when a connection is checked out with the token about to expire.
+    #[cfg(feature = "oauth")]
+    #[throws(PostgresSourceError)]
+    pub fn with_oauth(&mut self, config: OAuthConfig) {
+        self.with_oauth_cache(OAuthTokenCache::new(config))?;
+    }
+
+    /// Like `with_oauth`, with the tokens of `cache`.
+    #[cfg(feature = "oauth")]
+    #[throws(PostgresSourceError)]
+    pub fn with_oauth_cache(&mut self, cache: OAuthTokenCache) {
+        self.oauth = Some(cache);
+        self.refresh_oauth()?;
+    }
+
+    /// Rebuild the pool with a new access token if the current one is about to expire.
+    #[cfg(feature = "oauth")]
+    #[throws(PostgresSourceError)]
+    fn refresh_oauth(&self) {
+        let oauth = match &self.oauth {
+            Some(oauth) if oauth.needs_refresh() => oauth,
+            _ => return,
+        };
+        let token = oauth.token()?;
+        self.rebuild_pool(token)?;
+    }
+
+    /// Authenticate as `db_user` with the IAM authentication tokens of Amazon RDS in `region`,
+    /// see [`RdsIamAuthenticator`]. The token is generated for the first host and port of the
+    /// config, and the connections of the pool are established again with it. RDS only accepts
+    /// the tokens over TLS, so the config should require it (`sslmode=require`). Like with
+    /// `with_oauth`, the token and the connections are refreshed when a connection is checked out
+    /// with the token about to expire.
+    #[cfg(feature = "rds_iam")]
+    #[throws(PostgresSourceError)]
+    pub fn with_rds_iam_auth(&mut self, region: &str, db_user: &str) {
+        self.config.user(db_user);
+        self.rds_iam = Some(RdsIamAuthenticator::new(region, db_user));
+        self.refresh_rds_iam()?;
+    }
+
+    /// Rebuild the pool with a new IAM token if the current one is about to expire.
+    #[cfg(feature = "rds_iam")]
+    #[throws(PostgresSourceError)]
+    fn refresh_rds_iam(&self) {
+        let rds_iam = match &self.rds_iam {
+            Some(rds_iam) => rds_iam,
+            None => return,
+        };
+        let host = match self.config.get_hosts().first() {
+            Some(postgres::config::Host::Tcp(host)) => host.clone(),
+            _ => throw!(anyhow!("RDS IAM authentication needs a TCP host")),
+        };
+        let port = self.config.get_ports().first().copied().unwrap_or(5432);
+        if !rds_iam.needs_refresh(&host, port) {
+            return;
+        }
+        let token = rds_iam.token(&host, port)?;
+        self.rebuild_pool(token)?;
+    }
+
+    /// Establish the connections of the pool again with `password`.
+    #[cfg(any(feature = "oauth", feature = "rds_iam"))]
+    #[throws(PostgresSourceError)]
+    fn rebuild_pool(&self, password: String) {
+        let mut config = self.config.clone();
+        config.password(password);
+        let manager = PostgresConnectionManager::new(config, self.tls.clone());
+        let mut pool = self.pool.write().unwrap();
+        *pool = Pool::builder().max_size(pool.max_size()).build(manager)?;
+    }
+
+    /// The pool, after refreshing the authentication token.
+    #[throws(PostgresSourceError)]
+    fn pool(&self) -> Pool<PgManager<C>> {
+        #[cfg(feature = "oauth")]
+        self.refresh_oauth()?;
+        #[cfg(feature = "rds_iam")]
+        self.refresh_rds_iam()?;
+        self.pool.read().unwrap().clone()
+    }
+
+    /// Check out a connection of the pool, after refreshing the authentication token.
+    #[throws(PostgresSourceError)]
+    fn get_conn(&self) -> PgConn<C> {
+        self.pool()?.get()?
+    }
+
+    /// The statistics of the result columns from the planner statistics (`pg_stats`) of the table
+    /// read by the origin query (or the first query if there is none), see [`ColumnStats`].
+    /// Nothing is computed over the data: the statistics are as of the last `ANALYZE` of the
+    /// table, `min` and `max` are the bounds of the histogram (which leaves out the most common
+    /// values), and the statistics of the result columns that are not columns of the table are
+    /// `None`. Call after `fetch_metadata`. Fails if the query does not read a single table. The
+    /// statistics are cached on the source.
+    #[throws(PostgresSourceError)]
+    pub fn column_stats(&mut self) -> ColumnStats {
+        if let Some(stats) = &self.column_stats {
+            return stats.clone();
+        }
+        let query = match &self.origin_query {
+            Some(q) => q.clone(),
+            None => self
+                .queries
+                .first()
+                .map(|q| q.to_string())
+                .ok_or_else(|| anyhow!("no query to read the statistics of"))?,
+        };
+        let table = single_table(&query, &PostgreSqlDialect {})
+            .ok_or_else(|| anyhow!("the query does not read a single table"))?
+            .to_string();
+
+        let mut conn = self.get_conn()?;
+        let rows = conn
+            .query_one(PG_TABLE_ROWS_QUERY, &[&table])?
+            .try_get::<_, f64>(0)?
+            .max(0.);
+        let mut by_name = HashMap::new();
+        for row in conn.query(PG_COLUMN_STATS_QUERY, &[&table])? {
+            let name: String = row.try_get(0)?;
+            // a negative `n_distinct` is the opposite of the ratio of distinct values to rows
+            let distinct = row.try_get::<_, Option<f64>>(1)?.map(|n| match n < 0. {
+                true => (-n * rows).round() as u64,
+                false => n as u64,
+            });
+            let stat = ColumnStat {
+                name: name.clone(),
+                min: row.try_get(3)?,
+                max: row.try_get(4)?,
+                distinct,
+                avg_bytes: row.try_get(2)?,
+            };
+            by_name.insert(name, stat);
+        }
+        let columns = self
+            .names
+            .iter()
+            .map(|name| {
+                by_name.remove(name).unwrap_or_else(|| ColumnStat {
+                    name: name.clone(),
+                    min: None,
+                    max: None,
+                    distinct: None,
+                    avg_bytes: None,
+                })
+            })
+            .collect();
+        let stats = ColumnStats {
+            rows: rows as u64,
+            columns,
+        };
+        self.column_stats = Some(stats.clone());
+        stats
+    }
+}
+
+#[cfg(feature = "dst_arrow")]
+impl<P, C> WritableSource for PostgresSource<P, C>
+where
+    C: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type Error = PostgresSourceError;
+
+    #[throws(PostgresSourceError)]
+    fn merge(&self, target_table: &str, key_cols: &[&str], batch: &RecordBatch) -> MergeStats {
+        if batch.num_rows() == 0 {
+            return MergeStats::default();
+        }
+        let mut conn = self.get_conn()?;
+        let mut tx = conn.transaction()?;
+        let mut updated = 0;
+        for chunk in chunks(batch) {
+            let count: i64 = tx
+                .query_one(
+                    matched_count_query(target_table, key_cols, &chunk, &PostgreSqlDialect {})?
+                        .as_str(),
+                    &[],
+                )?
+                .get(0);
+            updated += count;
+            tx.execute(
+                upsert_query_postgres(target_table, key_cols, &chunk)?.as_str(),
+                &[],
+            )?;
+        }
+        tx.commit()?;
+        MergeStats {
+            inserted: batch.num_rows() - updated as usize,
+            updated: updated as usize,
+        }
+    }
 }
 
 impl<P, C> Source for PostgresSource<P, C>
@@ -137,18 +392,29 @@ where
     }
 
     fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+        self.queries = queries
+            .iter()
+            .map(|q| label_query(q.map(Q::to_string), self.label.as_deref()))
+            .collect();
+    }
+
+    fn with_query_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
     }
 
     fn set_origin_query(&mut self, query: Option<String>) {
         self.origin_query = query;
     }
 
+    fn origin_query(&self) -> Option<String> {
+        self.origin_query.clone()
+    }
+
     #[throws(PostgresSourceError)]
     fn fetch_metadata(&mut self) {
         assert!(!self.queries.is_empty());
 
-        let mut conn = self.pool.get()?;
+        let mut conn = self.get_conn()?;
         let first_query = &self.queries[0];
 
         let stmt = conn.prepare(first_query.as_str())?;
@@ -173,7 +439,7 @@ where
         match &self.origin_query {
             Some(q) => {
                 let cxq = CXQuery::Naked(q.clone());
-                let mut conn = self.pool.get()?;
+                let mut conn = self.get_conn()?;
                 let nrows = get_total_rows(&mut conn, &cxq)?;
                 Some(nrows)
             }
@@ -190,10 +456,26 @@ where
     }
 
     #[throws(PostgresSourceError)]
-    fn partition(self) -> Vec<Self::Partition> {
+    fn health_check(&self, timeout: Duration) -> HealthStatus {
+        let pool = self.pool()?;
+        probe_health(timeout, || -> Result<(), PostgresSourceError> {
+            let mut conn = pool.get_timeout(timeout)?;
+            // a statement_timeout of 0 disables it; SET LOCAL keeps it to the transaction
+            let mut tx = conn.transaction()?;
+            tx.batch_execute(&format!(
+                "SET LOCAL statement_timeout = {}; SELECT 1",
+                timeout.as_millis().max(1)
+            ))?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    #[throws(PostgresSourceError)]
+    fn partition(mut self) -> Vec<Self::Partition> {
         let mut ret = vec![];
-        for query in self.queries {
-            let conn = self.pool.get()?;
+        for query in std::mem::take(&mut self.queries) {
+            let conn = self.get_conn()?;
 
             ret.push(PostgresSourcePartition::<P, C>::new(
                 conn,
@@ -0,0 +1,409 @@
+//! Reading the changes of a Postgres logical replication slot that uses the `pgoutput` plugin.
+//! The changes are pulled through `pg_logical_slot_get_binary_changes` and the `pgoutput` messages
+//! are decoded into Arrow record batches, one batch per run of changes on the same table.
+
+use super::PostgresSourceError;
+use anyhow::anyhow;
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray, TimestampMicrosecondArray,
+    },
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    record_batch::RecordBatch,
+};
+use fehler::{throw, throws};
+use futures::{channel::mpsc, executor::block_on, SinkExt, Stream};
+use postgres::{
+    tls::{MakeTlsConnect, TlsConnect},
+    Client, Config, Socket,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Microseconds between the unix epoch and the Postgres epoch (2000-01-01).
+const PG_EPOCH_OFFSET_MICROS: i64 = 946_684_800_000_000;
+
+/// Maximum number of changes fetched from the slot per round trip.
+const REPLICATION_BATCH_SIZE: i32 = 1024;
+
+/// Number of record batches buffered ahead of the consumer of [`PostgresReplicationSource::into_stream`].
+const REPLICATION_STREAM_BUFFER: usize = 16;
+
+struct Relation {
+    name: String,
+    columns: Vec<(String, u32)>,
+}
+
+struct Change {
+    relation: u32,
+    operation: &'static str,
+    lsn: i64,
+    timestamp: Option<i64>,
+    values: Vec<Option<String>>,
+}
+
+struct MessageReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MessageReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    #[throws(PostgresSourceError)]
+    fn bytes(&mut self, n: usize) -> &'a [u8] {
+        if self.pos + n > self.buf.len() {
+            throw!(anyhow!("unexpected end of pgoutput message"));
+        }
+        let ret = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        ret
+    }
+
+    #[throws(PostgresSourceError)]
+    fn u8(&mut self) -> u8 {
+        self.bytes(1)?[0]
+    }
+
+    #[throws(PostgresSourceError)]
+    fn i16(&mut self) -> i16 {
+        let b = self.bytes(2)?;
+        i16::from_be_bytes([b[0], b[1]])
+    }
+
+    #[throws(PostgresSourceError)]
+    fn i32(&mut self) -> i32 {
+        let b = self.bytes(4)?;
+        i32::from_be_bytes([b[0], b[1], b[2], b[3]])
+    }
+
+    #[throws(PostgresSourceError)]
+    fn i64(&mut self) -> i64 {
+        let b = self.bytes(8)?;
+        i64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])
+    }
+
+    #[throws(PostgresSourceError)]
+    fn cstr(&mut self) -> String {
+        let end = self.buf[self.pos..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| anyhow!("unterminated string in pgoutput message"))?;
+        let s = String::from_utf8_lossy(&self.buf[self.pos..self.pos + end]).into_owned();
+        self.pos += end + 1;
+        s
+    }
+
+    /// TupleData: the values in text format, unchanged TOAST values are read as null.
+    #[throws(PostgresSourceError)]
+    fn tuple(&mut self) -> Vec<Option<String>> {
+        let ncols = self.i16()?;
+        let mut values = Vec::with_capacity(ncols as usize);
+        for _ in 0..ncols {
+            values.push(match self.u8()? {
+                b'n' | b'u' => None,
+                b't' => {
+                    let len = self.i32()? as usize;
+                    Some(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+                }
+                kind => throw!(anyhow!("unknown tuple data kind {}", kind as char)),
+            });
+        }
+        values
+    }
+}
+
+/// Parse a textual LSN (`16/B374D848`) into its 64 bit value.
+#[throws(PostgresSourceError)]
+fn parse_lsn(lsn: &str) -> i64 {
+    let (hi, lo) = lsn
+        .split_once('/')
+        .ok_or_else(|| anyhow!("invalid lsn {}", lsn))?;
+    let hi = u32::from_str_radix(hi, 16).map_err(|_| anyhow!("invalid lsn {}", lsn))?;
+    let lo = u32::from_str_radix(lo, 16).map_err(|_| anyhow!("invalid lsn {}", lsn))?;
+    (((hi as u64) << 32) | lo as u64) as i64
+}
+
+/// A single stream of the changes captured by a logical replication slot. Each item is a
+/// `RecordBatch` of consecutive changes on one table, with the columns
+/// `["operation", "lsn", "timestamp", ...table columns...]` and the qualified table name
+/// in the `table` metadata of its schema.
+///
+/// Reading consumes the changes from the slot. The iterator ends when the slot has no pending
+/// changes, iterate again to pick up the changes made afterwards, or follow the slot with
+/// [`PostgresReplicationSource::into_stream`].
+pub struct PostgresReplicationSource {
+    client: Client,
+    slot_name: String,
+    pub_name: String,
+    relations: HashMap<u32, Relation>,
+    commit_ts: Option<i64>,
+    pending: VecDeque<RecordBatch>,
+}
+
+impl PostgresReplicationSource {
+    #[throws(PostgresSourceError)]
+    pub fn new<C>(config: &Config, tls: C, slot_name: &str, pub_name: &str) -> Self
+    where
+        C: MakeTlsConnect<Socket> + 'static + Send,
+        C::TlsConnect: Send,
+        C::Stream: Send,
+        <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+    {
+        Self {
+            client: config.connect(tls)?,
+            slot_name: slot_name.to_string(),
+            pub_name: pub_name.to_string(),
+            relations: HashMap::new(),
+            commit_ts: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Fetch the next changes from the slot and decode them into record batches. Returns no
+    /// batches only once the slot has no pending changes.
+    #[throws(PostgresSourceError)]
+    pub fn poll(&mut self) -> Vec<RecordBatch> {
+        loop {
+            // a round trip may only carry transaction boundaries and relation messages
+            let (nrows, batches) = self.fetch_changes()?;
+            if nrows == 0 || !batches.is_empty() {
+                return batches;
+            }
+        }
+    }
+
+    /// Follow the slot on a background thread, waiting `poll_interval` whenever it has no pending
+    /// changes. The stream never ends by itself, it stops after yielding an error or when it is dropped.
+    /// Up to [`REPLICATION_STREAM_BUFFER`] batches are consumed from the slot ahead of the reader, and
+    /// are lost if the stream is dropped before they are read.
+    pub fn into_stream(
+        mut self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<RecordBatch, PostgresSourceError>> {
+        let (mut tx, rx) = mpsc::channel(REPLICATION_STREAM_BUFFER);
+        thread::spawn(move || {
+            while !tx.is_closed() {
+                match self.poll() {
+                    Ok(batches) if batches.is_empty() => thread::sleep(poll_interval),
+                    Ok(batches) => {
+                        for batch in batches {
+                            if block_on(tx.send(Ok(batch))).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = block_on(tx.send(Err(e)));
+                        return;
+                    }
+                }
+            }
+        });
+        rx
+    }
+
+    #[throws(PostgresSourceError)]
+    fn fetch_changes(&mut self) -> (usize, Vec<RecordBatch>) {
+        let rows = self.client.query(
+            "SELECT lsn::text, data FROM pg_logical_slot_get_binary_changes($1, NULL, $2, 'proto_version', '1', 'publication_names', $3)",
+            &[&self.slot_name, &REPLICATION_BATCH_SIZE, &self.pub_name],
+        )?;
+
+        let nrows = rows.len();
+        let mut changes = vec![];
+        for row in rows {
+            let lsn: &str = row.get(0);
+            let data: &[u8] = row.get(1);
+            if let Some(change) = self.decode(parse_lsn(lsn)?, data)? {
+                changes.push(change);
+            }
+        }
+
+        let mut batches = vec![];
+        let mut start = 0;
+        for i in 1..=changes.len() {
+            if i == changes.len() || changes[i].relation != changes[start].relation {
+                batches.push(self.to_batch(&changes[start..i])?);
+                start = i;
+            }
+        }
+        (nrows, batches)
+    }
+
+    #[throws(PostgresSourceError)]
+    fn decode(&mut self, lsn: i64, data: &[u8]) -> Option<Change> {
+        let mut msg = MessageReader::new(data);
+        let (operation, relation, values) = match msg.u8()? {
+            b'B' => {
+                let _final_lsn = msg.i64()?;
+                self.commit_ts = Some(msg.i64()? + PG_EPOCH_OFFSET_MICROS);
+                return None;
+            }
+            b'R' => {
+                let id = msg.i32()? as u32;
+                let namespace = msg.cstr()?;
+                let name = msg.cstr()?;
+                let _replica_identity = msg.u8()?;
+                let ncols = msg.i16()?;
+                let mut columns = vec![];
+                for _ in 0..ncols {
+                    let _flags = msg.u8()?;
+                    let col = msg.cstr()?;
+                    let oid = msg.i32()? as u32;
+                    let _typmod = msg.i32()?;
+                    columns.push((col, oid));
+                }
+                self.relations.insert(
+                    id,
+                    Relation {
+                        name: format!("{}.{}", namespace, name),
+                        columns,
+                    },
+                );
+                return None;
+            }
+            b'I' => {
+                let id = msg.i32()? as u32;
+                msg.u8()?; // 'N'
+                ("INSERT", id, msg.tuple()?)
+            }
+            b'U' => {
+                let id = msg.i32()? as u32;
+                let mut kind = msg.u8()?;
+                if kind == b'K' || kind == b'O' {
+                    msg.tuple()?;
+                    kind = msg.u8()?;
+                }
+                if kind != b'N' {
+                    throw!(anyhow!("malformed pgoutput update message"));
+                }
+                ("UPDATE", id, msg.tuple()?)
+            }
+            b'D' => {
+                let id = msg.i32()? as u32;
+                msg.u8()?; // 'K' or 'O'
+                ("DELETE", id, msg.tuple()?)
+            }
+            // commit, origin, type, truncate and messages
+            _ => return None,
+        };
+        Some(Change {
+            relation,
+            operation,
+            lsn,
+            timestamp: self.commit_ts,
+            values,
+        })
+    }
+
+    #[throws(PostgresSourceError)]
+    fn to_batch(&self, changes: &[Change]) -> RecordBatch {
+        let relation = self
+            .relations
+            .get(&changes[0].relation)
+            .ok_or_else(|| anyhow!("unknown relation {}", changes[0].relation))?;
+
+        let mut fields = vec![
+            Field::new("operation", DataType::Utf8, false),
+            Field::new("lsn", DataType::Int64, false),
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                true,
+            ),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(
+                changes.iter().map(|c| c.operation).collect::<Vec<_>>(),
+            )),
+            Arc::new(Int64Array::from(
+                changes.iter().map(|c| c.lsn).collect::<Vec<_>>(),
+            )),
+            Arc::new(TimestampMicrosecondArray::from(
+                changes.iter().map(|c| c.timestamp).collect::<Vec<_>>(),
+            )),
+        ];
+
+        for (i, (name, oid)) in relation.columns.iter().enumerate() {
+            let values = changes.iter().map(|c| c.values.get(i).cloned().flatten());
+            let (dt, array): (DataType, ArrayRef) = match oid {
+                // bool
+                16 => (
+                    DataType::Boolean,
+                    Arc::new(BooleanArray::from(
+                        values.map(|v| v.map(|v| v == "t")).collect::<Vec<_>>(),
+                    )),
+                ),
+                // int8, int2, int4
+                20 | 21 | 23 => (
+                    DataType::Int64,
+                    Arc::new(Int64Array::from(
+                        values
+                            .map(|v| v.map(|v| v.parse::<i64>()).transpose())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|e| anyhow!("cannot parse {}: {}", name, e))?,
+                    )),
+                ),
+                // float4, float8
+                700 | 701 => (
+                    DataType::Float64,
+                    Arc::new(Float64Array::from(
+                        values
+                            .map(|v| v.map(|v| v.parse::<f64>()).transpose())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|e| anyhow!("cannot parse {}: {}", name, e))?,
+                    )),
+                ),
+                _ => (
+                    DataType::Utf8,
+                    Arc::new(StringArray::from(values.collect::<Vec<_>>())),
+                ),
+            };
+            fields.push(Field::new(name, dt, true));
+            columns.push(array);
+        }
+
+        let schema = Schema::new_with_metadata(
+            fields,
+            std::iter::once(("table".to_string(), relation.name.clone())).collect(),
+        );
+        RecordBatch::try_new(Arc::new(schema), columns)?
+    }
+}
+
+impl Iterator for PostgresReplicationSource {
+    type Item = Result<RecordBatch, PostgresSourceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_empty() {
+            match self.poll() {
+                Ok(batches) => self.pending.extend(batches),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Stream the changes published by `pub_name` from the logical replication slot `slot_name`,
+/// which must be created with the `pgoutput` plugin. See [`PostgresReplicationSource`].
+#[throws(PostgresSourceError)]
+pub fn get_replication_stream<C>(
+    config: &Config,
+    tls: C,
+    slot_name: &str,
+    pub_name: &str,
+) -> impl Iterator<Item = Result<RecordBatch, PostgresSourceError>>
+where
+    C: MakeTlsConnect<Socket> + 'static + Send,
+    C::TlsConnect: Send,
+    C::Stream: Send,
+    <C::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    PostgresReplicationSource::new(config, tls, slot_name, pub_name)?
+}
@@ -1,8 +1,12 @@
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use fehler::{throw, throws};
 use postgres::types::Type;
 use rust_decimal::Decimal;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use uuid::Uuid;
 
 #[derive(Copy, Clone, Debug)]
@@ -65,6 +69,85 @@ impl_typesystem! {
     }
 }
 
+impl std::fmt::Display for PostgresTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use PostgresTypeSystem::*;
+        let (name, nullable) = match *self {
+            Bool(n) => ("BOOL", n),
+            Float4(n) => ("FLOAT4", n),
+            Float8(n) => ("FLOAT8", n),
+            Numeric(n) => ("NUMERIC", n),
+            Int2(n) => ("INT2", n),
+            Int4(n) => ("INT4", n),
+            Int8(n) => ("INT8", n),
+            Float4Array(n) => ("FLOAT4[]", n),
+            Float8Array(n) => ("FLOAT8[]", n),
+            NumericArray(n) => ("NUMERIC[]", n),
+            Int2Array(n) => ("INT2[]", n),
+            Int4Array(n) => ("INT4[]", n),
+            Int8Array(n) => ("INT8[]", n),
+            Date(n) => ("DATE", n),
+            Char(n) => ("\"CHAR\"", n),
+            BpChar(n) => ("BPCHAR", n),
+            VarChar(n) => ("VARCHAR", n),
+            Text(n) => ("TEXT", n),
+            ByteA(n) => ("BYTEA", n),
+            Time(n) => ("TIME", n),
+            Timestamp(n) => ("TIMESTAMP", n),
+            TimestampTz(n) => ("TIMESTAMPTZ", n),
+            UUID(n) => ("UUID", n),
+            JSON(n) => ("JSON", n),
+            JSONB(n) => ("JSONB", n),
+            Enum(n) => ("ENUM", n),
+            HSTORE(n) => ("HSTORE", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
+/// Parse the name of a Postgres type, e.g. `NUMERIC(10,2)` or `INT4[]`, with the SQL names of the
+/// types (`INTEGER`, `DOUBLE PRECISION`, ...) as aliases.
+impl<'a> TryFrom<&'a str> for PostgresTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use PostgresTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "BOOL" | "BOOLEAN" => Bool,
+            "FLOAT4" | "REAL" => Float4,
+            "FLOAT8" | "DOUBLE PRECISION" => Float8,
+            "NUMERIC" | "DECIMAL" => Numeric,
+            "INT2" | "SMALLINT" => Int2,
+            "INT4" | "INTEGER" | "INT" => Int4,
+            "INT8" | "BIGINT" => Int8,
+            "FLOAT4[]" | "REAL[]" => Float4Array,
+            "FLOAT8[]" | "DOUBLE PRECISION[]" => Float8Array,
+            "NUMERIC[]" | "DECIMAL[]" => NumericArray,
+            "INT2[]" | "SMALLINT[]" => Int2Array,
+            "INT4[]" | "INTEGER[]" | "INT[]" => Int4Array,
+            "INT8[]" | "BIGINT[]" => Int8Array,
+            "DATE" => Date,
+            "\"CHAR\"" => Char,
+            "BPCHAR" | "CHAR" | "CHARACTER" => BpChar,
+            "VARCHAR" | "CHARACTER VARYING" => VarChar,
+            "TEXT" => Text,
+            "BYTEA" => ByteA,
+            "TIME" | "TIME WITHOUT TIME ZONE" => Time,
+            "TIMESTAMP" | "TIMESTAMP WITHOUT TIME ZONE" => Timestamp,
+            "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => TimestampTz,
+            "UUID" => UUID,
+            "JSON" => JSON,
+            "JSONB" => JSONB,
+            "ENUM" => Enum,
+            "HSTORE" => HSTORE,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
+
 impl<'a> From<&'a Type> for PostgresTypeSystem {
     fn from(ty: &'a Type) -> PostgresTypeSystem {
         use PostgresTypeSystem::*;
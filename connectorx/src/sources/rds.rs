@@ -0,0 +1,199 @@
+//! IAM database authentication for Amazon RDS.
+//!
+//! [`RdsIamAuthenticator`] generates the authentication tokens RDS accepts in place of a password:
+//! a `connect` request to the database presigned with AWS Signature Version 4, see
+//! [`rds_auth_token`]. A token is valid for 15 minutes; it only needs to be valid when a connection
+//! is established, so it is generated again shortly before it expires.
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use fehler::throws;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The lifetime of the tokens generated by RDS.
+pub const RDS_TOKEN_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// The tokens are generated again this long before they expire.
+pub const RDS_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SIGNING_SERVICE: &str = "rds-db";
+
+/// The AWS credentials signing the tokens.
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// The credentials of the environment variables `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`
+    /// and, for temporary credentials, `AWS_SESSION_TOKEN`. The profiles and the instance roles
+    /// are not read, their credentials can be set with [`RdsIamAuthenticator::set_credentials`].
+    #[throws(anyhow::Error)]
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            env::var(name).map_err(|_| anyhow!("{} is not set, cannot sign the RDS token", name))
+        };
+        Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedToken {
+    token: String,
+    expires_at: Instant,
+}
+
+/// The IAM authentication tokens of `db_user` on the RDS instances of `region`.
+#[derive(Debug)]
+pub struct RdsIamAuthenticator {
+    pub region: String,
+    pub db_user: String,
+    credentials: Option<AwsCredentials>,
+    // by host and port, a token is only valid for the instance it was generated for
+    tokens: Mutex<HashMap<(String, u16), CachedToken>>,
+}
+
+impl RdsIamAuthenticator {
+    /// The tokens are signed with the credentials of the environment (see
+    /// [`AwsCredentials::from_env`]) unless others are set.
+    pub fn new(region: &str, db_user: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            db_user: db_user.to_string(),
+            credentials: None,
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sign the tokens with `credentials` rather than with the credentials of the environment.
+    pub fn set_credentials(&mut self, credentials: AwsCredentials) {
+        self.credentials = Some(credentials);
+        self.tokens.lock().unwrap().clear();
+    }
+
+    /// Whether the cached token for `host:port` is missing or about to expire, i.e.
+    /// [`Self::token`] would generate a new one.
+    pub fn needs_refresh(&self, host: &str, port: u16) -> bool {
+        match self.tokens.lock().unwrap().get(&(host.to_string(), port)) {
+            Some(token) => token.expires_at <= Instant::now() + RDS_REFRESH_MARGIN,
+            None => true,
+        }
+    }
+
+    /// The token for connecting to `host:port`, generated if needed.
+    #[throws(anyhow::Error)]
+    pub fn token(&self, host: &str, port: u16) -> String {
+        let mut tokens = self.tokens.lock().unwrap();
+        match tokens.get(&(host.to_string(), port)) {
+            Some(token) if token.expires_at > Instant::now() + RDS_REFRESH_MARGIN => {
+                token.token.clone()
+            }
+            _ => {
+                let credentials = match &self.credentials {
+                    Some(credentials) => credentials.clone(),
+                    None => AwsCredentials::from_env()?,
+                };
+                let generated_at = Instant::now();
+                let token = rds_auth_token(
+                    host,
+                    port,
+                    &self.region,
+                    &self.db_user,
+                    &credentials,
+                    Utc::now(),
+                )?;
+                tokens.insert(
+                    (host.to_string(), port),
+                    CachedToken {
+                        token: token.clone(),
+                        expires_at: generated_at + RDS_TOKEN_LIFETIME,
+                    },
+                );
+                token
+            }
+        }
+    }
+}
+
+#[throws(anyhow::Error)]
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|e| anyhow!("{}", e))?;
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The IAM authentication token of `db_user` on the RDS instance `host:port` of `region`, signed
+/// at `now`: the URL of a `connect` request presigned with AWS Signature Version 4, without its
+/// scheme, like the `generate-db-auth-token` command of the AWS CLI.
+#[throws(anyhow::Error)]
+pub fn rds_auth_token(
+    host: &str,
+    port: u16,
+    region: &str,
+    db_user: &str,
+    credentials: &AwsCredentials,
+    now: DateTime<Utc>,
+) -> String {
+    let date = now.format("%Y%m%d").to_string();
+    let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let scope = format!("{}/{}/{}/aws4_request", date, region, SIGNING_SERVICE);
+
+    // sorted by name, as in the canonical request
+    let mut params = vec![
+        ("Action", "connect".to_string()),
+        ("DBUser", db_user.to_string()),
+        ("X-Amz-Algorithm", SIGNING_ALGORITHM.to_string()),
+        (
+            "X-Amz-Credential",
+            format!("{}/{}", credentials.access_key_id, scope),
+        ),
+        ("X-Amz-Date", timestamp.clone()),
+        ("X-Amz-Expires", RDS_TOKEN_LIFETIME.as_secs().to_string()),
+    ];
+    if let Some(session_token) = &credentials.session_token {
+        params.push(("X-Amz-Security-Token", session_token.clone()));
+    }
+    params.push(("X-Amz-SignedHeaders", "host".to_string()));
+    let query = params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, urlencoding::encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let endpoint = format!("{}:{}", host, port);
+    let canonical_request = format!(
+        "GET\n/\n{}\nhost:{}\n\nhost\n{}",
+        query,
+        endpoint,
+        hex::encode(Sha256::digest(b""))
+    );
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        SIGNING_ALGORITHM,
+        timestamp,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let mut key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        &date,
+    )?;
+    for part in &[region, SIGNING_SERVICE, "aws4_request"] {
+        key = hmac_sha256(&key, part)?;
+    }
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign)?);
+
+    format!("{}/?{}&X-Amz-Signature={}", endpoint, query, signature)
+}
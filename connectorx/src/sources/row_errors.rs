@@ -0,0 +1,65 @@
+//! What a source does with a row whose values cannot be produced, e.g. a `NUMBER` too large for
+//! the `i64` of its column: fail the partition ([`RowErrorPolicy::Abort`], the default), or skip
+//! the row and keep loading ([`RowErrorPolicy::SkipAndCollect`]), reporting the skipped rows in
+//! [`RowErrors`] once the load is done.
+
+use crate::utils::Collector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowErrorPolicy {
+    /// Fail the partition on the first row that cannot be produced.
+    Abort,
+    /// Skip the rows that cannot be produced and collect their errors.
+    SkipAndCollect,
+}
+
+impl Default for RowErrorPolicy {
+    fn default() -> Self {
+        RowErrorPolicy::Abort
+    }
+}
+
+/// A row skipped under [`RowErrorPolicy::SkipAndCollect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// The index of the partition (of its query) the row was read by.
+    pub partition: usize,
+    /// The index of the row in the result of the partition query, counting the skipped rows.
+    pub row: usize,
+    /// The name of the first column whose value could not be produced.
+    pub column: String,
+    pub message: String,
+}
+
+/// The rows skipped by the partitions of a source.
+#[derive(Debug, Clone, Default)]
+pub struct RowErrors {
+    errors: Collector<RowError>,
+}
+
+impl RowErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, error: RowError) {
+        self.errors.push(error);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The skipped rows, ordered by partition and row, leaving none behind.
+    pub fn take(&self) -> Vec<RowError> {
+        let mut errors = self.errors.take();
+        errors.sort_by_key(|e| (e.partition, e.row));
+        errors
+    }
+}
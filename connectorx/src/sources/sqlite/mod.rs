@@ -7,8 +7,8 @@ pub use self::errors::SQLiteSourceError;
 use crate::{
     data_order::DataOrder,
     errors::ConnectorXError,
-    sources::{PartitionParser, Produce, Source, SourcePartition},
-    sql::{count_query, limit1_query, CXQuery},
+    sources::{probe_health, HealthStatus, PartitionParser, Produce, Source, SourcePartition},
+    sql::{count_query, label_query, limit1_query, CXQuery},
     utils::DummyBox,
 };
 use anyhow::anyhow;
@@ -22,6 +22,7 @@ use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Row, Rows, Statement};
 use sqlparser::dialect::SQLiteDialect;
 use std::convert::TryFrom;
+use std::time::Duration;
 pub use typesystem::SQLiteTypeSystem;
 use urlencoding::decode;
 
@@ -29,6 +30,7 @@ pub struct SQLiteSource {
     pool: Pool<SqliteConnectionManager>,
     origin_query: Option<String>,
     queries: Vec<CXQuery<String>>,
+    label: Option<String>,
     names: Vec<String>,
     schema: Vec<SQLiteTypeSystem>,
 }
@@ -47,6 +49,7 @@ impl SQLiteSource {
             pool,
             origin_query: None,
             queries: vec![],
+            label: None,
             names: vec![],
             schema: vec![],
         }
@@ -70,13 +73,24 @@ where
     }
 
     fn set_queries<Q: ToString>(&mut self, queries: &[CXQuery<Q>]) {
-        self.queries = queries.iter().map(|q| q.map(Q::to_string)).collect();
+        self.queries = queries
+            .iter()
+            .map(|q| label_query(q.map(Q::to_string), self.label.as_deref()))
+            .collect();
+    }
+
+    fn with_query_label(&mut self, label: &str) {
+        self.label = Some(label.to_string());
     }
 
     fn set_origin_query(&mut self, query: Option<String>) {
         self.origin_query = query;
     }
 
+    fn origin_query(&self) -> Option<String> {
+        self.origin_query.clone()
+    }
+
     #[throws(SQLiteSourceError)]
     fn fetch_metadata(&mut self) {
         assert!(!self.queries.is_empty());
@@ -176,6 +190,16 @@ where
         self.schema.clone()
     }
 
+    #[throws(SQLiteSourceError)]
+    fn health_check(&self, timeout: Duration) -> HealthStatus {
+        probe_health(timeout, || -> Result<(), SQLiteSourceError> {
+            self.pool
+                .get_timeout(timeout)?
+                .query_row("SELECT 1", [], |_| Ok(()))?;
+            Ok(())
+        })
+    }
+
     #[throws(SQLiteSourceError)]
     fn partition(self) -> Vec<Self::Partition> {
         let mut ret = vec![];
@@ -1,4 +1,6 @@
 use super::errors::SQLiteSourceError;
+use crate::errors::ConnectorXError;
+use crate::typesystem::{parse_type, TypeName};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use fehler::{throw, throws};
 use rusqlite::types::Type;
@@ -34,6 +36,25 @@ impl_typesystem! {
     }
 }
 
+impl std::fmt::Display for SQLiteTypeSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use SQLiteTypeSystem::*;
+        let (name, nullable) = match *self {
+            Bool(n) => ("BOOLEAN", n),
+            Int8(n) => ("BIGINT", n),
+            Int4(n) => ("INTEGER", n),
+            Int2(n) => ("SMALLINT", n),
+            Real(n) => ("REAL", n),
+            Text(n) => ("TEXT", n),
+            Date(n) => ("DATE", n),
+            Time(n) => ("TIME", n),
+            Timestamp(n) => ("DATETIME", n),
+            Blob(n) => ("BLOB", n),
+        };
+        crate::typesystem::fmt_type(f, name, nullable)
+    }
+}
+
 impl TryFrom<Type> for SQLiteTypeSystem {
     type Error = SQLiteSourceError;
 
@@ -90,3 +111,31 @@ impl TryFrom<(Option<&str>, Type)> for SQLiteTypeSystem {
         }
     }
 }
+
+/// Parse the name of a SQLite type as displayed by [`SQLiteTypeSystem`], e.g. `BIGINT(nullable)`,
+/// or any other declared type by the affinity rules of SQLite. Unlike the declared type of a
+/// column, `INTEGER` is parsed back into `Int4`, the type displayed as `INTEGER`.
+impl<'a> TryFrom<&'a str> for SQLiteTypeSystem {
+    type Error = ConnectorXError;
+
+    #[throws(ConnectorXError)]
+    fn try_from(ty: &'a str) -> Self {
+        use SQLiteTypeSystem::*;
+        let TypeName { name, nullable, .. } = parse_type(ty);
+        let variant: fn(bool) -> Self = match name.as_str() {
+            "BOOLEAN" | "BOOL" => Bool,
+            "BIGINT" | "INT8" => Int8,
+            "INTEGER" | "INT4" => Int4,
+            "SMALLINT" | "INT2" => Int2,
+            "DATE" => Date,
+            "TIME" => Time,
+            "DATETIME" | "TIMESTAMP" => Timestamp,
+            _ if name.contains("INT") => Int8,
+            _ if name.contains("CHAR") || name.contains("CLOB") || name.contains("TEXT") => Text,
+            _ if name.contains("REAL") || name.contains("FLOA") || name.contains("DOUB") => Real,
+            _ if name.contains("BLOB") => Blob,
+            _ => throw!(ConnectorXError::UnknownType(ty.to_string())),
+        };
+        variant(nullable)
+    }
+}
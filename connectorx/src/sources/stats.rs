@@ -0,0 +1,62 @@
+//! Statistics of the columns of a query result, e.g. to size the fetch buffers from the actual
+//! width of the rows or to decide whether a column is worth dictionary encoding.
+
+/// The statistics of one column. `min` and `max` are rendered as text, and they are `None`
+/// along with `distinct` for the columns whose type cannot be compared (e.g. LOBs or JSON).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStat {
+    pub name: String,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub distinct: Option<u64>,
+    /// The average size of the non null values in bytes.
+    pub avg_bytes: Option<f64>,
+}
+
+/// The statistics of all the columns of a query result.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnStats {
+    pub rows: u64,
+    pub columns: Vec<ColumnStat>,
+}
+
+impl ColumnStats {
+    /// The average size of a row in bytes, the sum of the average size of each column.
+    pub fn avg_row_bytes(&self) -> f64 {
+        self.columns.iter().filter_map(|c| c.avg_bytes).sum()
+    }
+
+    /// The number of distinct values of the column `name` over the number of rows, close to 0
+    /// for a column with a few repeated values and 1 for a unique column.
+    pub fn distinct_ratio(&self, name: &str) -> Option<f64> {
+        let col = self.columns.iter().find(|c| c.name == name)?;
+        match (col.distinct, self.rows) {
+            (Some(_), 0) => Some(0.),
+            (Some(distinct), rows) => Some(distinct as f64 / rows as f64),
+            (None, _) => None,
+        }
+    }
+}
+
+/// The statistics of a table column kept by the database for its query optimizer, e.g. to choose
+/// the number of partitions of a query on the column, or between equal-width and quantile ranges.
+/// They are estimates as of the last time they were gathered, and `None` if they never were.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptimizerStats {
+    pub num_distinct: Option<u64>,
+    pub nulls: Option<u64>,
+    /// The lowest value rendered as text, `None` if the type of the column cannot be decoded.
+    pub low: Option<String>,
+    /// The highest value rendered as text, `None` if the type of the column cannot be decoded.
+    pub high: Option<String>,
+}
+
+impl OptimizerStats {
+    /// The lowest and the highest values of a numeric column, the range to split in equal-width
+    /// partitions.
+    pub fn numeric_range(&self) -> Option<(f64, f64)> {
+        let low = self.low.as_ref()?.trim().parse().ok()?;
+        let high = self.high.as_ref()?.trim().parse().ok()?;
+        Some((low, high))
+    }
+}
@@ -0,0 +1,82 @@
+//! Extension trait [`TableScan`] for sources that can read a whole table into a `RecordBatch`
+//! without the caller writing any SQL: the columns are checked against the catalog, quoted in the
+//! dialect of the source, and the query runs through the usual [`Dispatcher`].
+
+use super::Source;
+use crate::{
+    destinations::arrow::{typesystem::ArrowTypeSystem, ArrowDestination, ArrowDestinationError},
+    dispatcher::Dispatcher,
+    errors::ConnectorXError,
+    sql::CXQuery,
+    typesystem::Transport,
+};
+use anyhow::anyhow;
+use arrow::record_batch::RecordBatch;
+use fehler::{throw, throws};
+
+pub trait TableScan: Source + Sized {
+    /// The names of the columns of `table`, in the order of the table. `table` is a possibly
+    /// qualified name as written in SQL, e.g. `hr.employees`.
+    fn describe_table(&self, table: &str) -> Result<Vec<String>, Self::Error>;
+
+    /// `table` with each part of its name quoted in the dialect of the source, with the case the
+    /// unquoted parts are stored with in the catalog.
+    fn quote_table(table: &str) -> String;
+
+    /// Quote a column name as returned by [`TableScan::describe_table`].
+    fn quote_column(column: &str) -> String;
+
+    /// The query reading the (already quoted) `columns` of `table`, at most `limit` rows of it.
+    fn table_scan_query(table: &str, columns: &[String], limit: Option<usize>) -> String;
+
+    /// Read `columns` of `table`, all of them if empty, and at most `limit` rows. The columns are
+    /// matched against [`TableScan::describe_table`] ignoring the case, and an unknown column
+    /// fails before anything is read.
+    #[throws(TP::Error)]
+    fn table_scan<TP>(self, table: &str, columns: &[&str], limit: Option<usize>) -> RecordBatch
+    where
+        Self::Error: Send,
+        TP: Transport<
+            TSS = Self::TypeSystem,
+            TSD = ArrowTypeSystem,
+            S = Self,
+            D = ArrowDestination,
+        >,
+        TP::Error: From<Self::Error> + From<ArrowDestinationError>,
+    {
+        let names = self.describe_table(table)?;
+        if names.is_empty() {
+            throw!(ConnectorXError::from(anyhow!("table {} not found", table)));
+        }
+        let selected: Vec<&String> = match columns.is_empty() {
+            true => names.iter().collect(),
+            false => columns
+                .iter()
+                .map(|c| {
+                    names
+                        .iter()
+                        .find(|name| name.eq_ignore_ascii_case(c))
+                        .ok_or_else(|| anyhow!("table {} has no column {}", table, c))
+                })
+                .collect::<Result<_, _>>()
+                .map_err(ConnectorXError::from)?,
+        };
+        let selected: Vec<String> = selected
+            .into_iter()
+            .map(|c| Self::quote_column(c))
+            .collect();
+        let query = CXQuery::Naked(Self::table_scan_query(
+            &Self::quote_table(table),
+            &selected,
+            limit,
+        ));
+
+        let mut destination = ArrowDestination::new();
+        let dispatcher =
+            Dispatcher::<Self, ArrowDestination, TP>::new(self, &mut destination, &[query], None);
+        dispatcher.run()?;
+        let schema = destination.arrow_schema();
+        let batches = destination.arrow()?;
+        RecordBatch::concat(&schema, &batches).map_err(ArrowDestinationError::from)?
+    }
+}
@@ -0,0 +1,77 @@
+//! Event time watermarks for streaming sources (e.g. [`super::postgres::PostgresReplicationSource`]).
+//!
+//! [`WatermarkTracker`] keeps the largest event timestamp seen on each partition of a stream. The low
+//! watermark is the smallest of them (minus the allowed lateness): every event older than it is
+//! expected to have been seen, so downstream consumers can close their event time windows up to it.
+
+#[cfg(feature = "dst_arrow")]
+use arrow::{datatypes::Schema, error::Result as ArrowResult, record_batch::RecordBatch};
+#[cfg(feature = "dst_arrow")]
+use std::sync::Arc;
+
+/// Key of the schema metadata carrying the watermark of a tagged `RecordBatch`.
+pub const WATERMARK_METADATA_KEY: &str = "watermark";
+
+#[derive(Debug, Clone)]
+pub struct WatermarkTracker {
+    partitions: Vec<Option<i64>>,
+    lateness: i64,
+    emitted: Option<i64>,
+}
+
+impl WatermarkTracker {
+    pub fn new(npartitions: usize) -> Self {
+        Self::with_lateness(npartitions, 0)
+    }
+
+    /// A tracker holding the watermark back by `lateness` (in the unit of the timestamps),
+    /// to tolerate events arriving out of order within a partition.
+    pub fn with_lateness(npartitions: usize, lateness: i64) -> Self {
+        Self {
+            partitions: vec![None; npartitions],
+            lateness,
+            emitted: None,
+        }
+    }
+
+    /// Record an event of `partition` at `timestamp`. Returns the new low watermark if it advanced,
+    /// which only happens once every partition has seen an event.
+    pub fn update(&mut self, partition: usize, timestamp: i64) -> Option<i64> {
+        let max = &mut self.partitions[partition];
+        if max.map_or(true, |m| timestamp > m) {
+            *max = Some(timestamp);
+        }
+
+        let watermark = self.watermark();
+        if watermark != i64::MIN && self.emitted.map_or(true, |e| watermark > e) {
+            self.emitted = Some(watermark);
+            return Some(watermark);
+        }
+        None
+    }
+
+    /// The current low watermark, `i64::MIN` while any partition has not seen an event yet.
+    pub fn watermark(&self) -> i64 {
+        self.partitions
+            .iter()
+            .map(|max| match max {
+                Some(ts) => ts.saturating_sub(self.lateness),
+                None => i64::MIN,
+            })
+            .min()
+            .unwrap_or(i64::MIN)
+    }
+
+    /// Tag `batch` with the current watermark under the [`WATERMARK_METADATA_KEY`] schema metadata.
+    #[cfg(feature = "dst_arrow")]
+    pub fn tag(&self, batch: &RecordBatch) -> ArrowResult<RecordBatch> {
+        let schema = batch.schema();
+        let mut metadata = schema.metadata().clone();
+        metadata.insert(
+            WATERMARK_METADATA_KEY.to_string(),
+            self.watermark().to_string(),
+        );
+        let schema = Schema::new_with_metadata(schema.fields().clone(), metadata);
+        RecordBatch::try_new(Arc::new(schema), batch.columns().to_vec())
+    }
+}
@@ -0,0 +1,373 @@
+//! Extension trait [`WritableSource`] for sources that can also write data back, e.g. merging (upserting)
+//! a fetched `RecordBatch` into a table. The SQL generation helpers are exposed for inspection and testing.
+
+use crate::errors::ConnectorXError;
+#[cfg(feature = "src_oracle")]
+use crate::sources::oracle::OracleDialect;
+use crate::sql::{date_literal, datetime_literal, quote_ident, quote_name, string_literal};
+use anyhow::anyhow;
+use arrow::{
+    array::{as_primitive_array, Array, ArrayRef, BooleanArray, LargeStringArray, StringArray},
+    datatypes::{
+        DataType, Date32Type, Date64Type, Float32Type, Float64Type, TimeUnit,
+        TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+        TimestampSecondType,
+    },
+    record_batch::RecordBatch,
+    util::display::array_value_to_string,
+};
+use chrono::NaiveDateTime;
+use fehler::{throw, throws};
+use itertools::Itertools;
+use sqlparser::dialect::{Dialect, MySqlDialect, PostgreSqlDialect};
+use std::any::Any;
+
+/// Number of rows of each statement of a merge, see [`chunks`].
+pub const MERGE_CHUNK_ROWS: usize = 1000;
+
+/// Number of rows inserted and updated by a merge.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeStats {
+    pub inserted: usize,
+    pub updated: usize,
+}
+
+pub trait WritableSource {
+    type Error: From<ConnectorXError>;
+
+    /// Merge `batch` into `target_table`: rows whose `key_cols` match an existing row update it,
+    /// the others are inserted. The column names of `batch` must match the columns of the table;
+    /// they are quoted, and so is each part of `target_table`, so they are case sensitive. The key
+    /// columns of `batch` are assumed to be unique. The rows are written by [`chunks`], all of
+    /// them in a single transaction.
+    fn merge(
+        &self,
+        target_table: &str,
+        key_cols: &[&str],
+        batch: &RecordBatch,
+    ) -> Result<MergeStats, Self::Error>;
+}
+
+/// `batch` in slices of at most [`MERGE_CHUNK_ROWS`] rows, each written by its own statements so
+/// that they stay small enough to parse, and their `IN` lists within the 1000 items Oracle takes.
+pub fn chunks(batch: &RecordBatch) -> impl Iterator<Item = RecordBatch> + '_ {
+    (0..batch.num_rows())
+        .step_by(MERGE_CHUNK_ROWS)
+        .map(move |offset| batch.slice(offset, MERGE_CHUNK_ROWS.min(batch.num_rows() - offset)))
+}
+
+#[cfg(feature = "src_oracle")]
+fn is_oracle<T: Dialect>(dialect: &T) -> bool {
+    dialect.type_id() == (OracleDialect {}.type_id())
+}
+
+#[cfg(not(feature = "src_oracle"))]
+fn is_oracle<T: Dialect>(_dialect: &T) -> bool {
+    false
+}
+
+/// A `NaN` or infinite float as a literal of `dialect`, for the databases that store them.
+#[throws(ConnectorXError)]
+fn special_float<T: Dialect>(v: f64, dialect: &T) -> String {
+    let name = match (v.is_nan(), v > 0.) {
+        (true, _) => "NaN",
+        (false, true) => "Infinity",
+        (false, false) => "-Infinity",
+    };
+    if dialect.type_id() == (PostgreSqlDialect {}.type_id()) {
+        format!("'{}'::float8", name)
+    } else if is_oracle(dialect) {
+        match name {
+            "NaN" => "BINARY_DOUBLE_NAN",
+            "Infinity" => "BINARY_DOUBLE_INFINITY",
+            _ => "-BINARY_DOUBLE_INFINITY",
+        }
+        .to_string()
+    } else {
+        throw!(anyhow!(
+            "cannot write {} as a SQL literal of {:?}",
+            v,
+            dialect
+        ))
+    }
+}
+
+#[throws(ConnectorXError)]
+fn datetime(array: &ArrayRef, row: usize) -> NaiveDateTime {
+    let dt = match array.data_type() {
+        DataType::Date64 => as_primitive_array::<Date64Type>(array).value_as_datetime(row),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            as_primitive_array::<TimestampSecondType>(array).value_as_datetime(row)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            as_primitive_array::<TimestampMillisecondType>(array).value_as_datetime(row)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            as_primitive_array::<TimestampMicrosecondType>(array).value_as_datetime(row)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            as_primitive_array::<TimestampNanosecondType>(array).value_as_datetime(row)
+        }
+        dt => throw!(anyhow!("{:?} is not a timestamp type", dt)),
+    };
+    dt.ok_or_else(|| anyhow!("timestamp out of range"))?
+}
+
+/// The value of `array` at `row` as a SQL literal of `dialect`. The timestamps with a time zone
+/// are written in UTC, without it.
+#[throws(ConnectorXError)]
+fn literal<T: Dialect>(array: &ArrayRef, row: usize, dialect: &T) -> String {
+    if array.is_null(row) {
+        return "NULL".to_string();
+    }
+    match array.data_type() {
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => array_value_to_string(array, row).map_err(anyhow::Error::from)?,
+        DataType::Float32 | DataType::Float64 => {
+            let v = match array.data_type() {
+                DataType::Float32 => as_primitive_array::<Float32Type>(array).value(row) as f64,
+                _ => as_primitive_array::<Float64Type>(array).value(row),
+            };
+            match v.is_finite() {
+                true => array_value_to_string(array, row).map_err(anyhow::Error::from)?,
+                false => special_float(v, dialect)?,
+            }
+        }
+        DataType::Boolean => {
+            let value = array
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .ok_or_else(|| anyhow!("cannot downcast boolean array"))?
+                .value(row);
+            // Oracle has no boolean type, they are stored as numbers
+            match (is_oracle(dialect), value) {
+                (true, true) => "1".to_string(),
+                (true, false) => "0".to_string(),
+                (false, true) => "TRUE".to_string(),
+                (false, false) => "FALSE".to_string(),
+            }
+        }
+        DataType::Utf8 => string_literal(
+            array
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| anyhow!("cannot downcast string array"))?
+                .value(row),
+            dialect,
+        ),
+        DataType::LargeUtf8 => string_literal(
+            array
+                .as_any()
+                .downcast_ref::<LargeStringArray>()
+                .ok_or_else(|| anyhow!("cannot downcast large string array"))?
+                .value(row),
+            dialect,
+        ),
+        DataType::Date32 => date_literal(
+            as_primitive_array::<Date32Type>(array)
+                .value_as_date(row)
+                .ok_or_else(|| anyhow!("date out of range"))?,
+            dialect,
+        ),
+        DataType::Date64 | DataType::Timestamp(_, _) => {
+            datetime_literal(datetime(array, row)?, dialect)
+        }
+        dt => throw!(anyhow!("cannot write arrow type {:?} as a SQL literal", dt)),
+    }
+}
+
+#[throws(ConnectorXError)]
+fn row_literals<T: Dialect>(batch: &RecordBatch, row: usize, dialect: &T) -> Vec<String> {
+    batch
+        .columns()
+        .iter()
+        .map(|c| literal(c, row, dialect))
+        .collect::<Result<Vec<_>, _>>()?
+}
+
+/// The quoted column names of `batch`.
+fn names<T: Dialect>(batch: &RecordBatch, dialect: &T) -> Vec<String> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| quote_ident(f.name(), dialect))
+        .collect()
+}
+
+/// The quoted names of `batch` but the key columns.
+fn value_names<T: Dialect>(batch: &RecordBatch, key_cols: &[&str], dialect: &T) -> Vec<String> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .filter(|f| !key_cols.contains(&f.name().as_str()))
+        .map(|f| quote_ident(f.name(), dialect))
+        .collect()
+}
+
+#[throws(ConnectorXError)]
+fn check_keys(batch: &RecordBatch, key_cols: &[&str]) {
+    if key_cols.is_empty() {
+        throw!(anyhow!("merge requires at least one key column"));
+    }
+    for key in key_cols {
+        batch
+            .schema()
+            .index_of(key)
+            .map_err(|_| anyhow!("key column {} is not in the batch", key))?;
+    }
+}
+
+/// Count the rows of `target_table` whose keys appear in `batch`, i.e. the rows a merge will
+/// update: `SELECT COUNT(*) FROM ... WHERE (keys) IN ((...), ...)`. Give it a chunk of at most
+/// [`MERGE_CHUNK_ROWS`] rows, see [`chunks`].
+#[throws(ConnectorXError)]
+pub fn matched_count_query<T: Dialect>(
+    target_table: &str,
+    key_cols: &[&str],
+    batch: &RecordBatch,
+    dialect: &T,
+) -> String {
+    check_keys(batch, key_cols)?;
+    let schema = batch.schema();
+    let keys = key_cols
+        .iter()
+        .map(|k| Ok(batch.column(schema.index_of(k).map_err(anyhow::Error::from)?)))
+        .collect::<Result<Vec<_>, ConnectorXError>>()?;
+    let values = (0..batch.num_rows())
+        .map(|row| {
+            Ok(format!(
+                "({})",
+                keys.iter()
+                    .map(|col| literal(col, row, dialect))
+                    .collect::<Result<Vec<_>, ConnectorXError>>()?
+                    .join(", ")
+            ))
+        })
+        .collect::<Result<Vec<_>, ConnectorXError>>()?;
+    format!(
+        "SELECT COUNT(*) FROM {} WHERE ({}) IN ({})",
+        quote_name(target_table, dialect),
+        key_cols.iter().map(|k| quote_ident(k, dialect)).join(", "),
+        values.join(", ")
+    )
+}
+
+/// `MERGE INTO ... USING (SELECT ... FROM dual UNION ALL ...) ON (...) WHEN MATCHED ... WHEN NOT
+/// MATCHED ...`. Give it a chunk of at most [`MERGE_CHUNK_ROWS`] rows, see [`chunks`].
+#[cfg(feature = "src_oracle")]
+#[throws(ConnectorXError)]
+pub fn merge_query_oracle(target_table: &str, key_cols: &[&str], batch: &RecordBatch) -> String {
+    check_keys(batch, key_cols)?;
+    let dialect = OracleDialect {};
+    let names = names(batch, &dialect);
+    let rows = (0..batch.num_rows())
+        .map(|row| {
+            Ok(format!(
+                "SELECT {} FROM dual",
+                row_literals(batch, row, &dialect)?
+                    .iter()
+                    .zip(&names)
+                    .map(|(v, n)| format!("{} {}", v, n))
+                    .join(", ")
+            ))
+        })
+        .collect::<Result<Vec<_>, ConnectorXError>>()?;
+    let on = key_cols
+        .iter()
+        .map(|k| quote_ident(k, &dialect))
+        .map(|k| format!("CXTMPTAB_TGT.{} = CXTMPTAB_SRC.{}", k, k))
+        .join(" AND ");
+    let updates = value_names(batch, key_cols, &dialect)
+        .iter()
+        .map(|n| format!("CXTMPTAB_TGT.{} = CXTMPTAB_SRC.{}", n, n))
+        .join(", ");
+
+    let mut sql = format!(
+        "MERGE INTO {} CXTMPTAB_TGT USING ({}) CXTMPTAB_SRC ON ({})",
+        quote_name(target_table, &dialect),
+        rows.join(" UNION ALL "),
+        on
+    );
+    if !updates.is_empty() {
+        sql.push_str(&format!(" WHEN MATCHED THEN UPDATE SET {}", updates));
+    }
+    sql.push_str(&format!(
+        " WHEN NOT MATCHED THEN INSERT ({}) VALUES ({})",
+        names.join(", "),
+        names
+            .iter()
+            .map(|n| format!("CXTMPTAB_SRC.{}", n))
+            .join(", ")
+    ));
+    sql
+}
+
+#[throws(ConnectorXError)]
+fn insert_values<T: Dialect>(target_table: &str, batch: &RecordBatch, dialect: &T) -> String {
+    let rows = (0..batch.num_rows())
+        .map(|row| {
+            Ok(format!(
+                "({})",
+                row_literals(batch, row, dialect)?.join(", ")
+            ))
+        })
+        .collect::<Result<Vec<_>, ConnectorXError>>()?;
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        quote_name(target_table, dialect),
+        names(batch, dialect).join(", "),
+        rows.join(", ")
+    )
+}
+
+/// `INSERT ... ON CONFLICT (keys) DO UPDATE SET ...`. Give it a chunk of at most
+/// [`MERGE_CHUNK_ROWS`] rows, see [`chunks`].
+#[throws(ConnectorXError)]
+pub fn upsert_query_postgres(target_table: &str, key_cols: &[&str], batch: &RecordBatch) -> String {
+    check_keys(batch, key_cols)?;
+    let dialect = PostgreSqlDialect {};
+    let updates = value_names(batch, key_cols, &dialect)
+        .iter()
+        .map(|n| format!("{} = EXCLUDED.{}", n, n))
+        .join(", ");
+    let action = if updates.is_empty() {
+        "DO NOTHING".to_string()
+    } else {
+        format!("DO UPDATE SET {}", updates)
+    };
+    format!(
+        "{} ON CONFLICT ({}) {}",
+        insert_values(target_table, batch, &dialect)?,
+        key_cols.iter().map(|k| quote_ident(k, &dialect)).join(", "),
+        action
+    )
+}
+
+/// `INSERT ... ON DUPLICATE KEY UPDATE ...`, the key columns must form a unique key of the table.
+/// Give it a chunk of at most [`MERGE_CHUNK_ROWS`] rows, see [`chunks`].
+#[throws(ConnectorXError)]
+pub fn upsert_query_mysql(target_table: &str, key_cols: &[&str], batch: &RecordBatch) -> String {
+    check_keys(batch, key_cols)?;
+    let dialect = MySqlDialect {};
+    let mut updates = value_names(batch, key_cols, &dialect)
+        .iter()
+        .map(|n| format!("{} = VALUES({})", n, n))
+        .join(", ");
+    if updates.is_empty() {
+        let key = quote_ident(key_cols[0], &dialect);
+        updates = format!("{} = {}", key, key);
+    }
+    format!(
+        "{} ON DUPLICATE KEY UPDATE {}",
+        insert_values(target_table, batch, &dialect)?,
+        updates
+    )
+}
@@ -1,18 +1,32 @@
 use crate::errors::ConnectorXError;
 #[cfg(feature = "src_oracle")]
 use crate::sources::oracle::OracleDialect;
+use anyhow::anyhow;
+#[cfg(feature = "dst_arrow")]
+use chrono::{NaiveDate, NaiveDateTime};
 use fehler::{throw, throws};
 use log::{debug, trace, warn};
 use sqlparser::ast::{
     BinaryOperator, Expr, Function, FunctionArg, Ident, ObjectName, Query, Select, SelectItem,
     SetExpr, Statement, TableAlias, TableFactor, TableWithJoins, Value,
 };
-use sqlparser::dialect::Dialect;
+use sqlparser::dialect::{Dialect, MySqlDialect};
+#[cfg(feature = "dst_arrow")]
+use sqlparser::dialect::{MsSqlDialect, SQLiteDialect};
+#[cfg(feature = "src_oracle")]
+use sqlparser::keywords::Keyword;
 use sqlparser::parser::Parser;
 #[cfg(feature = "src_oracle")]
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
 use std::any::Any;
+use std::collections::HashSet;
+#[cfg(feature = "src_oracle")]
+use std::ops::Bound;
 
-#[derive(Debug, Clone)]
+/// A query, compared and hashed by its text and by whether it is wrapped, so that the partition
+/// queries can be deduplicated in a `HashSet` or used as the keys of a `HashMap`. The text is
+/// compared as is: queries differing by their whitespace or case are different.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CXQuery<Q = String> {
     Naked(Q),   // The query directly comes from the user
     Wrapped(Q), // The user query is already wrapped in a subquery
@@ -34,6 +48,42 @@ impl<Q: AsRef<str>> CXQuery<Q> {
             CXQuery::Wrapped(q) => q.as_ref(),
         }
     }
+
+    /// Whether the query is a plain `SELECT COUNT(*) FROM ...` (or `COUNT(1)`), e.g. the result of
+    /// [`count_query`]: a single select of a single count, without `GROUP BY`, so that it yields
+    /// one row. `false` if the query cannot be parsed.
+    pub fn is_count_query(&self, dialect: &dyn Dialect) -> bool {
+        let ast = match Parser::parse_sql(dialect, self.as_str()) {
+            Ok(ast) => ast,
+            Err(_) => return false,
+        };
+        let select = match ast.as_slice() {
+            [Statement::Query(query)] => match &query.body {
+                SetExpr::Select(select) => select,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        if !select.group_by.is_empty() {
+            return false;
+        }
+        let func = match select.projection.as_slice() {
+            [SelectItem::UnnamedExpr(Expr::Function(func))]
+            | [SelectItem::ExprWithAlias {
+                expr: Expr::Function(func),
+                ..
+            }] => func,
+            _ => return false,
+        };
+        matches!(func.name.0.as_slice(), [name] if name.value.eq_ignore_ascii_case("count"))
+            && !func.distinct
+            && func.over.is_none()
+            && matches!(
+                func.args.as_slice(),
+                [FunctionArg::Unnamed(Expr::Wildcard)]
+                    | [FunctionArg::Unnamed(Expr::Value(Value::Number(_, _)))]
+            )
+    }
 }
 
 impl From<&str> for CXQuery {
@@ -171,10 +221,33 @@ impl QueryExt for Query {
     }
 }
 
+/// Prefix the query with the comment `/* connectorx: {label} */`, so that the queries of
+/// ConnectorX can be told apart in the monitoring tools of the databases (e.g. AWR or
+/// `pg_stat_statements`). The comment delimiters are removed from the label, so that it cannot
+/// close the comment (or open a nested one in Postgres) and inject SQL.
+pub fn label_query(sql: CXQuery<String>, label: Option<&str>) -> CXQuery<String> {
+    let label = match label {
+        Some(label) => label,
+        None => return sql,
+    };
+    let mut label = label.to_string();
+    while label.contains("*/") || label.contains("/*") {
+        label = label.replace("*/", "").replace("/*", "");
+    }
+    sql.map(|q| format!("/* connectorx: {} */ {}", label, q))
+}
+
+/// The query counting the rows of `sql`. A wrapped count query, e.g. the result of an earlier
+/// `count_query`, is returned as is rather than counted again (see [`CXQuery::is_count_query`]).
+/// A naked one comes from the user and is counted like any query: its result is a single row.
 #[throws(ConnectorXError)]
 pub fn count_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<String> {
     trace!("Incoming query: {}", sql);
 
+    if matches!(sql, CXQuery::Wrapped(_)) && sql.is_count_query(dialect) {
+        return sql.clone();
+    }
+
     const COUNT_TMP_TAB_NAME: &str = "CXTMPTAB_COUNT";
 
     #[allow(unused_mut)]
@@ -214,10 +287,10 @@ pub fn count_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<St
                     if query.offset.is_none() {
                         query.order_by = vec![]; // mssql offset must appear with order by
                     }
-                    let select = query
-                        .as_select_mut()
-                        .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?;
-                    select.sort_by = vec![];
+                    // a set operation (e.g. UNION) has no select of its own, it is wrapped as is
+                    if let Some(select) = query.as_select_mut() {
+                        select.sort_by = vec![];
+                    }
                     wrap_query(&mut query, projection, None, table_alias)
                 }
                 CXQuery::Wrapped(ast) => {
@@ -251,8 +324,87 @@ pub fn count_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<St
     CXQuery::Wrapped(tsql)
 }
 
+/// The query counting the rows of `sql` on Oracle, like [`count_query`] with the alias of the
+/// derived table taken from `aliases`.
+#[cfg(feature = "src_oracle")]
+pub fn count_query_oracle(sql: &CXQuery<String>, aliases: &mut AliasGenerator) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    if matches!(sql, CXQuery::Wrapped(_)) && sql.is_count_query(&OracleDialect {}) {
+        return sql.clone();
+    }
+    let tsql = format!(
+        "SELECT COUNT(*) FROM ({}) {}",
+        sql.as_str(),
+        aliases.alias("CXTMPTAB_COUNT")
+    );
+
+    debug!("Transformed count query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+/// Generate the aliases of the derived tables and columns of the rewrites, unique and at most
+/// `max_len` bytes long, e.g. 30 for Oracle before 12.2 (see
+/// [`crate::sources::oracle::identifier_limit`]). An alias that is too long, or that was already
+/// generated (ignoring the case, like unquoted identifiers), is truncated and suffixed with a hash
+/// of it.
+#[derive(Debug, Clone)]
+pub struct AliasGenerator {
+    max_len: usize,
+    used: HashSet<String>,
+}
+
+impl AliasGenerator {
+    /// The length of the hash suffix, `_` and 8 hex digits.
+    const SUFFIX_LEN: usize = 9;
+
+    /// Fails if `max_len` leaves no room for the hash suffix.
+    #[throws(ConnectorXError)]
+    pub fn new(max_len: usize) -> Self {
+        if max_len <= Self::SUFFIX_LEN {
+            throw!(anyhow!(
+                "identifier limit {} is too small for the aliases",
+                max_len
+            ));
+        }
+        AliasGenerator {
+            max_len,
+            used: HashSet::new(),
+        }
+    }
+
+    pub fn alias(&mut self, name: &str) -> String {
+        let mut alias = name.to_string();
+        let mut attempt = 0u32;
+        while alias.len() > self.max_len || self.used.contains(&alias.to_uppercase()) {
+            let mut end = name.len().min(self.max_len - Self::SUFFIX_LEN);
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+            let hash = fnv1a(format!("{}#{}", name, attempt).as_bytes());
+            alias = format!("{}_{:08X}", &name[..end], hash as u32);
+            attempt += 1;
+        }
+        self.used.insert(alias.to_uppercase());
+        alias
+    }
+}
+
+/// The 64-bit FNV-1a hash, stable across Rust releases unlike the `std` one.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
 #[throws(ConnectorXError)]
 pub fn limit1_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<String> {
+    limit_query(sql, 1, dialect)?
+}
+
+/// Read the first `n` rows of the query only, with `LIMIT n`.
+#[throws(ConnectorXError)]
+pub fn limit_query<T: Dialect>(sql: &CXQuery<String>, n: usize, dialect: &T) -> CXQuery<String> {
     trace!("Incoming query: {}", sql);
 
     let sql = match Parser::parse_sql(dialect, sql.as_str()) {
@@ -263,7 +415,7 @@ pub fn limit1_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<S
 
             match &mut ast[0] {
                 Statement::Query(q) => {
-                    q.limit = Some(Expr::Value(Value::Number("1".to_string(), false)));
+                    q.limit = Some(Expr::Value(Value::Number(n.to_string(), false)));
                 }
                 _ => throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string())),
             };
@@ -272,20 +424,18 @@ pub fn limit1_query<T: Dialect>(sql: &CXQuery<String>, dialect: &T) -> CXQuery<S
         }
         Err(e) => {
             warn!("parser error: {:?}, manually compose query string", e);
-            format!("{} LIMIT 1", sql.as_str())
+            format!("{} LIMIT {}", sql.as_str(), n)
         }
     };
 
-    debug!("Transformed limit 1 query: {}", sql);
+    debug!("Transformed limit {} query: {}", n, sql);
     CXQuery::Wrapped(sql)
 }
 
 #[throws(ConnectorXError)]
 #[cfg(feature = "src_oracle")]
 pub fn limit1_query_oracle(sql: &CXQuery<String>) -> CXQuery<String> {
-    trace!("Incoming oracle query: {}", sql);
-
-    CXQuery::Wrapped(format!("SELECT * FROM ({}) WHERE rownum = 1", sql))
+    limit_query_oracle(sql, 1)
 
     // let ast = Parser::parse_sql(&OracleDialect {}, sql.as_str())?;
     // if ast.len() != 1 {
@@ -312,6 +462,687 @@ pub fn limit1_query_oracle(sql: &CXQuery<String>) -> CXQuery<String> {
     // CXQuery::Wrapped(tsql)
 }
 
+/// Read the first `n` rows of the query only, with `ROWNUM`.
+#[cfg(feature = "src_oracle")]
+pub fn limit_query_oracle(sql: &CXQuery<String>, n: usize) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    CXQuery::Wrapped(format!("SELECT * FROM ({}) WHERE rownum <= {}", sql, n))
+}
+
+#[cfg(feature = "src_oracle")]
+pub fn tail_query_oracle(
+    sql: &CXQuery<String>,
+    col: &str,
+    limit: Option<usize>,
+    aliases: &mut AliasGenerator,
+) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    let alias = aliases.alias("CXTMPTAB_TAIL");
+    let mut tsql = format!(
+        "SELECT * FROM ({}) {} ORDER BY {}.{} DESC",
+        sql, alias, alias, col
+    );
+    if let Some(n) = limit {
+        tsql.push_str(&format!(" FETCH FIRST {} ROWS ONLY", n));
+    }
+
+    debug!("Transformed tail query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+/// Fetch the CLOB columns (`clobs[i]` is true for a CLOB column) of 1 to `size` characters inline
+/// as `VARCHAR2` in the place of the column. Longer values are nulled there and selected as CLOBs
+/// in extra columns appended after the original ones, in the order of the CLOB columns. So are the
+/// empty CLOBs, which would read as null once converted to `VARCHAR2`. `size` must keep the
+/// values within the `VARCHAR2` limit in bytes, e.g. 1000 characters in `AL32UTF8`.
+#[cfg(feature = "src_oracle")]
+pub fn lob_prefetch_query_oracle(
+    sql: &CXQuery<String>,
+    names: &[String],
+    clobs: &[bool],
+    size: u32,
+    aliases: &mut AliasGenerator,
+) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    let alias = aliases.alias("CXTMPTAB_LOB");
+    let col = |name: &str| format!("{}.\"{}\"", alias, name);
+    let mut cols: Vec<String> = names
+        .iter()
+        .zip(clobs)
+        .map(|(name, &clob)| {
+            if clob {
+                format!(
+                    "CASE WHEN DBMS_LOB.GETLENGTH({c}) BETWEEN 1 AND {n} THEN DBMS_LOB.SUBSTR({c}, {n}, 1) END",
+                    c = col(name),
+                    n = size
+                )
+            } else {
+                col(name)
+            }
+        })
+        .collect();
+    cols.extend(
+        names
+            .iter()
+            .zip(clobs)
+            .filter(|(_, clob)| **clob)
+            .map(|(name, _)| {
+                format!(
+                    "CASE WHEN DBMS_LOB.GETLENGTH({c}) NOT BETWEEN 1 AND {n} THEN {c} END",
+                    c = col(name),
+                    n = size
+                )
+            }),
+    );
+    let tsql = format!("SELECT {} FROM ({}) {}", cols.join(", "), sql, alias);
+
+    debug!("Transformed lob prefetch query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+/// Add the flashback clause `flashback` (e.g. `AS OF SCN 42`) to each table read in the `FROM`
+/// clauses of the query, of its derived tables and of its common table expressions. The tables
+/// read by the subqueries in expressions (e.g. `WHERE x IN (SELECT ...)`) are left as they are.
+#[throws(ConnectorXError)]
+#[cfg(feature = "src_oracle")]
+pub fn flashback_query_oracle(sql: &CXQuery<String>, flashback: &str) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    let ast = Parser::parse_sql(&OracleDialect {}, sql.as_str())?;
+    if ast.len() != 1 {
+        throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string()));
+    }
+    let mut query = ast[0]
+        .as_query()
+        .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?
+        .clone();
+    flashback_query(&mut query, flashback, &[]);
+    let tsql = format!("{}", query);
+
+    debug!("Transformed flashback query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+#[cfg(feature = "src_oracle")]
+fn flashback_query(query: &mut Query, flashback: &str, ctes: &[String]) {
+    let mut ctes = ctes.to_vec();
+    if let Some(with) = &mut query.with {
+        for cte in &mut with.cte_tables {
+            flashback_query(&mut cte.query, flashback, &ctes);
+            ctes.push(cte.alias.name.value.to_uppercase());
+        }
+    }
+    flashback_set_expr(&mut query.body, flashback, &ctes);
+}
+
+#[cfg(feature = "src_oracle")]
+fn flashback_set_expr(body: &mut SetExpr, flashback: &str, ctes: &[String]) {
+    match body {
+        SetExpr::Select(select) => {
+            for table in &mut select.from {
+                flashback_table_with_joins(table, flashback, ctes);
+            }
+        }
+        SetExpr::Query(query) => flashback_query(query, flashback, ctes),
+        SetExpr::SetOperation { left, right, .. } => {
+            flashback_set_expr(left, flashback, ctes);
+            flashback_set_expr(right, flashback, ctes);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(feature = "src_oracle")]
+fn flashback_table_with_joins(table: &mut TableWithJoins, flashback: &str, ctes: &[String]) {
+    flashback_table_factor(&mut table.relation, flashback, ctes);
+    for join in &mut table.joins {
+        flashback_table_factor(&mut join.relation, flashback, ctes);
+    }
+}
+
+#[cfg(feature = "src_oracle")]
+fn flashback_table_factor(relation: &mut TableFactor, flashback: &str, ctes: &[String]) {
+    let printed = match relation {
+        TableFactor::Table {
+            name, alias, args, ..
+        } if args.is_empty() => {
+            let is_cte = name.0.len() == 1 && ctes.contains(&name.0[0].value.to_uppercase());
+            match (is_cte, alias.take()) {
+                (true, None) => return,
+                (true, Some(alias)) => format!("{} {}", name, alias),
+                (false, None) => format!("{} {}", name, flashback),
+                (false, Some(alias)) => format!("{} {} {}", name, flashback, alias),
+            }
+        }
+        TableFactor::Derived {
+            lateral: false,
+            subquery,
+            alias,
+        } => {
+            flashback_query(subquery, flashback, ctes);
+            match alias.take() {
+                Some(alias) => format!("({}) {}", subquery, alias),
+                None => return,
+            }
+        }
+        TableFactor::NestedJoin(table) => {
+            flashback_table_with_joins(table, flashback, ctes);
+            return;
+        }
+        _ => return,
+    };
+    *relation = bare_relation_oracle(printed);
+}
+
+// HACK: Oracle does not support "AS" for table aliases, which is how sqlparser prints them, so the
+// rewritten relations are printed into a bare identifier along with their alias
+#[cfg(feature = "src_oracle")]
+fn bare_relation_oracle(printed: String) -> TableFactor {
+    TableFactor::Table {
+        name: ObjectName(vec![Ident {
+            value: printed,
+            quote_style: None,
+        }]),
+        alias: None,
+        args: vec![],
+        with_hints: vec![],
+    }
+}
+
+/// Add the `ORA_ROWSCN` pseudo column after the columns of a query reading a single table, directly
+/// or through derived tables (e.g. the partition queries wrapping the user query).
+#[throws(ConnectorXError)]
+#[cfg(feature = "src_oracle")]
+pub fn rowscn_query_oracle(sql: &CXQuery<String>) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    let ast = Parser::parse_sql(&OracleDialect {}, sql.as_str())?;
+    if ast.len() != 1 {
+        throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string()));
+    }
+    let mut query = ast[0]
+        .as_query()
+        .ok_or_else(|| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?
+        .clone();
+    if !rowscn_query(&mut query) {
+        throw!(ConnectorXError::SqlQueryNotSupported(sql.to_string()));
+    }
+    let tsql = format!("{}", query);
+
+    debug!("Transformed rowscn query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+#[cfg(feature = "src_oracle")]
+fn rowscn_query(query: &mut Query) -> bool {
+    let select = match query.as_select_mut() {
+        Some(select) if select.from.len() == 1 && select.from[0].joins.is_empty() => select,
+        _ => return false,
+    };
+    let rowscn = Ident {
+        value: "ORA_ROWSCN".to_string(),
+        quote_style: None,
+    };
+    let relation = &mut select.from[0].relation;
+    let printed = match relation {
+        TableFactor::Table {
+            name, alias, args, ..
+        } if args.is_empty() => {
+            // `*` cannot be followed by other columns in Oracle, unlike `t.*`
+            let qualifier = match alias {
+                Some(alias) => ObjectName(vec![alias.name.clone()]),
+                None => name.clone(),
+            };
+            for item in &mut select.projection {
+                if let SelectItem::Wildcard = item {
+                    *item = SelectItem::QualifiedWildcard(qualifier.clone());
+                }
+            }
+            select
+                .projection
+                .push(SelectItem::UnnamedExpr(Expr::Identifier(rowscn)));
+            match alias.take() {
+                Some(alias) => format!("{} {}", name, alias),
+                None => return true,
+            }
+        }
+        TableFactor::Derived {
+            lateral: false,
+            subquery,
+            alias,
+        } => {
+            if !rowscn_query(subquery) {
+                return false;
+            }
+            // `*` already selects the column of the derived table
+            if !select
+                .projection
+                .iter()
+                .all(|item| matches!(item, SelectItem::Wildcard))
+            {
+                select
+                    .projection
+                    .push(SelectItem::UnnamedExpr(Expr::Identifier(rowscn)));
+            }
+            match alias.take() {
+                Some(alias) => format!("({}) {}", subquery, alias),
+                None => return true,
+            }
+        }
+        _ => return false,
+    };
+    *relation = bare_relation_oracle(printed);
+    true
+}
+
+/// Whether the query reads Oracle monitoring views, which are as expensive to count as to read and
+/// whose rows may change between the count and the read: the dynamic performance views (`V$...`,
+/// `GV$...` and their `V_$` / `GV_$` synonyms) and the AWR history (`DBA_HIST_...`). The names in
+/// string literals are ignored.
+#[cfg(feature = "src_oracle")]
+pub fn monitoring_query_oracle(sql: &str) -> bool {
+    const PREFIXES: &[&str] = &["V$", "GV$", "V_$", "GV_$", "DBA_HIST_"];
+    let sql = sql.to_uppercase();
+    let mut in_literal = false;
+    let mut prev = ' ';
+    for (i, ch) in sql.char_indices() {
+        if ch == '\'' {
+            in_literal = !in_literal;
+        } else if !in_literal
+            && !(prev.is_alphanumeric() || matches!(prev, '_' | '$' | '#'))
+            && PREFIXES.iter().any(|p| sql[i..].starts_with(p))
+        {
+            return true;
+        }
+        prev = ch;
+    }
+    false
+}
+
+/// Whether the query reads remote objects through a database link (`table@dblink`), whose count
+/// runs the whole query on the remote database and ships its result over the link. The `@` in
+/// string literals are ignored.
+#[cfg(feature = "src_oracle")]
+pub fn dblink_query_oracle(sql: &str) -> bool {
+    let mut in_literal = false;
+    let mut prev = ' ';
+    let mut chars = sql.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\'' {
+            in_literal = !in_literal;
+        } else if ch == '@'
+            && !in_literal
+            && (prev.is_alphanumeric() || matches!(prev, '_' | '$' | '#' | '"'))
+            && matches!(chars.peek(), Some(&next) if next.is_alphabetic() || next == '"')
+        {
+            return true;
+        }
+        prev = ch;
+    }
+    false
+}
+
+/// Add the `PARALLEL(degree)` hint, or `PARALLEL` to let Oracle choose the degree if `degree` is 0,
+/// to the query. The hint is appended to the existing hint block of the first `SELECT` if there is
+/// one. A query that does not start with `SELECT` (e.g. `WITH`) is wrapped into one.
+#[throws(ConnectorXError)]
+#[cfg(feature = "src_oracle")]
+pub fn parallel_query_oracle(
+    sql: &CXQuery<String>,
+    degree: usize,
+    aliases: &mut AliasGenerator,
+) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    let hint = match degree {
+        0 => "PARALLEL".to_string(),
+        n => format!("PARALLEL({})", n),
+    };
+    let tokens = Tokenizer::new(&OracleDialect {}, sql.as_str())
+        .tokenize()
+        .map_err(|_| ConnectorXError::SqlQueryNotSupported(sql.to_string()))?;
+    // the whitespaces and comments print back to their text, so their lengths give the position of
+    // the first keyword and of the hint block in the query
+    let mut pos = 0;
+    let mut tokens = tokens.iter().peekable();
+    while let Some(Token::Whitespace(ws)) = tokens.peek() {
+        pos += ws.to_string().len();
+        tokens.next();
+    }
+    let sql = sql.as_str();
+    let tsql = match tokens.next() {
+        Some(Token::Word(w)) if w.keyword == Keyword::SELECT => {
+            pos += w.value.len();
+            let select_end = pos;
+            let mut block = None;
+            while let Some(Token::Whitespace(ws)) = tokens.next() {
+                match ws {
+                    Whitespace::MultiLineComment(comment) if comment.starts_with('+') => {
+                        block = Some(pos + 2 + comment.len());
+                        break;
+                    }
+                    Whitespace::Space | Whitespace::Tab | Whitespace::Newline => {
+                        pos += ws.to_string().len()
+                    }
+                    _ => break,
+                }
+            }
+            match block {
+                Some(end) => format!("{} {} {}", &sql[..end], hint, &sql[end..]),
+                None => format!(
+                    "{} /*+ {} */{}",
+                    &sql[..select_end],
+                    hint,
+                    &sql[select_end..]
+                ),
+            }
+        }
+        _ => format!(
+            "SELECT /*+ {} */ * FROM ({}) {}",
+            hint,
+            sql,
+            aliases.alias("CXTMPTAB_PARALLEL")
+        ),
+    };
+
+    debug!("Transformed parallel query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+/// Where the `NULL` values of a column go: sorted before or after the others, see
+/// [`crate::sources::oracle::OracleSource::partition_nulls`], and in the first or the last
+/// partition when partitioning on the column, see [`null_partition`].
+#[cfg(feature = "src_oracle")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[cfg(feature = "src_oracle")]
+impl std::fmt::Display for NullsOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NullsOrder::First => write!(f, "NULLS FIRST"),
+            NullsOrder::Last => write!(f, "NULLS LAST"),
+        }
+    }
+}
+
+/// The index of the partition taking the `NULL` keys in, out of `num` partitions on a column in
+/// ascending order of their ranges: the first one if `nulls` sorts them first, the last one
+/// otherwise, where Oracle sorts them by default. No range matches a `NULL`, so exactly one of the
+/// partitions of a query must take them in or they are not read at all.
+#[cfg(feature = "src_oracle")]
+pub fn null_partition(nulls: Option<NullsOrder>, num: usize) -> usize {
+    match nulls {
+        Some(NullsOrder::First) => 0,
+        Some(NullsOrder::Last) | None => num.saturating_sub(1),
+    }
+}
+
+/// The rows of the query whose `col` is in `[lower, upper)`, and those whose `col` is `NULL` if
+/// `nulls`, which should be set for the partition given by [`null_partition`] only.
+#[cfg(feature = "src_oracle")]
+pub fn single_col_partition_query_oracle(
+    sql: &str,
+    col: &str,
+    lower: i64,
+    upper: i64,
+    nulls: bool,
+) -> String {
+    trace!("Incoming oracle query: {}", sql);
+
+    let range = format!(
+        "CXTMPTAB_PART.{} >= {} AND CXTMPTAB_PART.{} < {}",
+        col, lower, col, upper
+    );
+    let tsql = match nulls {
+        true => format!(
+            "SELECT * FROM ({}) CXTMPTAB_PART WHERE ({} OR CXTMPTAB_PART.{} IS NULL)",
+            sql, range, col
+        ),
+        false => format!("SELECT * FROM ({}) CXTMPTAB_PART WHERE {}", sql, range),
+    };
+
+    debug!("Transformed single column partition query: {}", tsql);
+    tsql
+}
+
+/// The range of a partition key supplied from outside, e.g. by a scheduler, see
+/// [`bounds_partition_query_oracle`]. Each end is included, excluded or unbounded.
+#[cfg(feature = "src_oracle")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionBound {
+    pub lower: Bound<i64>,
+    pub upper: Bound<i64>,
+    /// Also take the rows whose key is `NULL` in, which no range matches.
+    pub nulls: bool,
+}
+
+#[cfg(feature = "src_oracle")]
+impl PartitionBound {
+    /// The usual `[lower, upper)` range.
+    pub fn half_open(lower: i64, upper: i64) -> Self {
+        PartitionBound {
+            lower: Bound::Included(lower),
+            upper: Bound::Excluded(upper),
+            nulls: false,
+        }
+    }
+}
+
+/// The rows of the query whose `col` is in `bound`, like [`single_col_partition_query_oracle`]
+/// without assuming a half-open range. A bound unbounded at both ends without `nulls` keeps all
+/// the rows but the `NULL` keys.
+#[cfg(feature = "src_oracle")]
+pub fn bounds_partition_query_oracle(
+    sql: &str,
+    col: &str,
+    bound: &PartitionBound,
+    aliases: &mut AliasGenerator,
+) -> String {
+    trace!("Incoming oracle query: {}", sql);
+
+    let alias = aliases.alias("CXTMPTAB_PART");
+    let col = format!("{}.{}", alias, col);
+    let mut range = vec![];
+    match bound.lower {
+        Bound::Included(v) => range.push(format!("{} >= {}", col, v)),
+        Bound::Excluded(v) => range.push(format!("{} > {}", col, v)),
+        Bound::Unbounded => {}
+    }
+    match bound.upper {
+        Bound::Included(v) => range.push(format!("{} <= {}", col, v)),
+        Bound::Excluded(v) => range.push(format!("{} < {}", col, v)),
+        Bound::Unbounded => {}
+    }
+    let range = match range.is_empty() {
+        true => format!("{} IS NOT NULL", col),
+        false => range.join(" AND "),
+    };
+    let tsql = match bound.nulls {
+        true => format!(
+            "SELECT * FROM ({}) {} WHERE ({} OR {} IS NULL)",
+            sql, alias, range, col
+        ),
+        false => format!("SELECT * FROM ({}) {} WHERE {}", sql, alias, range),
+    };
+
+    debug!("Transformed bounds partition query: {}", tsql);
+    tsql
+}
+
+/// Sort the result of the query by `columns`, e.g. so that a query run with Oracle parallel
+/// query (see [`parallel_query_oracle`]) still yields its rows in order: the parallel servers sort
+/// ranges of the rows and the query coordinator returns the ranges in order.
+#[cfg(feature = "src_oracle")]
+pub fn order_query_oracle(
+    sql: &CXQuery<String>,
+    columns: &[String],
+    aliases: &mut AliasGenerator,
+) -> CXQuery<String> {
+    trace!("Incoming oracle query: {}", sql);
+
+    let tsql = format!(
+        "SELECT * FROM ({}) {} ORDER BY {}",
+        sql.as_str(),
+        aliases.alias("CXTMPTAB_ORDER"),
+        columns.join(", ")
+    );
+
+    debug!("Transformed order query: {}", tsql);
+    CXQuery::Wrapped(tsql)
+}
+
+/// The table read by `sql` if it is a plain `SELECT ... FROM table` without joins or set
+/// operations, e.g. to look up the catalog metadata of the table.
+pub fn single_table<T: Dialect>(sql: &str, dialect: &T) -> Option<ObjectName> {
+    let ast = Parser::parse_sql(dialect, sql).ok()?;
+    if ast.len() != 1 {
+        return None;
+    }
+    let mut query = ast[0].as_query()?.clone();
+    match query.as_select_mut()?.from.as_slice() {
+        [TableWithJoins {
+            relation: TableFactor::Table { name, .. },
+            joins,
+        }] if joins.is_empty() => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// The names of the result columns of `sql` which are the relevance score `SCORE(label)` of an
+/// Oracle Text `CONTAINS(column, text, label)` operator: their alias, or `SCORE(label)` for the
+/// unaliased ones. Only the select list of the outer query is looked at; the count, limit and
+/// partition queries wrap `sql` in a subquery, which keeps `SCORE` in the query block of its
+/// `CONTAINS` as Oracle requires.
+#[cfg(feature = "src_oracle")]
+pub fn score_columns_oracle(sql: &str) -> Vec<String> {
+    let ast = match Parser::parse_sql(&OracleDialect {}, sql) {
+        Ok(ast) if ast.len() == 1 => ast,
+        _ => return vec![],
+    };
+    let mut query = match ast[0].as_query() {
+        Some(query) => query.clone(),
+        None => return vec![],
+    };
+    let select = match query.as_select_mut() {
+        Some(select) => select,
+        None => return vec![],
+    };
+    let label = |expr: &Expr| match expr {
+        Expr::Function(Function { name, args, .. })
+            if name.to_string().eq_ignore_ascii_case("SCORE") =>
+        {
+            match args.as_slice() {
+                [FunctionArg::Unnamed(Expr::Value(Value::Number(label, _)))] => Some(label.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    select
+        .projection
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::UnnamedExpr(expr) => label(expr).map(|l| format!("SCORE({})", l)),
+            SelectItem::ExprWithAlias { expr, alias } => {
+                label(expr).map(|_| match alias.quote_style {
+                    Some(_) => alias.value.clone(),
+                    None => alias.value.to_uppercase(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// `ident` as a quoted identifier of `dialect`: in backticks on MySQL, in double quotes elsewhere,
+/// with the quote character doubled inside. Quoted names are case sensitive, e.g. the upper case
+/// names Oracle gives the unquoted ones must be written in upper case.
+pub fn quote_ident<T: Dialect>(ident: &str, dialect: &T) -> String {
+    match dialect.type_id() == (MySqlDialect {}.type_id()) {
+        true => format!("`{}`", ident.replace('`', "``")),
+        false => format!("\"{}\"", ident.replace('"', "\"\"")),
+    }
+}
+
+/// The dot separated parts of `name`, e.g. `schema.table`, each quoted with [`quote_ident`].
+pub fn quote_name<T: Dialect>(name: &str, dialect: &T) -> String {
+    name.split('.')
+        .map(|part| quote_ident(part, dialect))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// `s` as a string literal of `dialect`: quoted, with the quotes doubled and, on MySQL where
+/// backslashes are escape characters by default, the backslashes doubled as well.
+pub fn string_literal<T: Dialect>(s: &str, dialect: &T) -> String {
+    let mut escaped = s.replace('\'', "''");
+    if dialect.type_id() == (MySqlDialect {}.type_id()) {
+        escaped = escaped.replace('\\', "\\\\");
+    }
+    format!("'{}'", escaped)
+}
+
+/// Whether `dialect` has the typed `DATE '...'` and `TIMESTAMP '...'` literals.
+#[cfg(feature = "dst_arrow")]
+fn typed_literals<T: Dialect>(dialect: &T) -> bool {
+    dialect.type_id() != (MsSqlDialect {}.type_id())
+        && dialect.type_id() != (SQLiteDialect {}.type_id())
+}
+
+/// `d` as a date literal of `dialect`, typed where the database supports it.
+#[cfg(feature = "dst_arrow")]
+pub fn date_literal<T: Dialect>(d: NaiveDate, dialect: &T) -> String {
+    let s = format!("'{}'", d.format("%Y-%m-%d"));
+    match typed_literals(dialect) {
+        true => format!("DATE {}", s),
+        false => s,
+    }
+}
+
+/// `dt` as a timestamp literal of `dialect`, typed where the database supports it. The date and
+/// the time are separated by a space, as all the databases take it.
+#[cfg(feature = "dst_arrow")]
+pub fn datetime_literal<T: Dialect>(dt: NaiveDateTime, dialect: &T) -> String {
+    let s = format!("'{}'", dt.format("%Y-%m-%d %H:%M:%S%.f"));
+    match typed_literals(dialect) {
+        true => format!("TIMESTAMP {}", s),
+        false => s,
+    }
+}
+
+/// Only select the rows whose `col` is greater than `watermark`, ordered by `col`.
+/// `watermark` is injected as is so it must be a valid SQL literal, see
+/// [`crate::sources::incremental::Watermark::to_sql`].
+pub fn incremental_query(
+    sql: &CXQuery<String>,
+    col: &str,
+    watermark: Option<&str>,
+) -> CXQuery<String> {
+    trace!("Incoming query: {}", sql);
+    const INC_TMP_TAB_NAME: &str = "CXTMPTAB_INC";
+
+    let tsql = sql.map(|q| match watermark {
+        Some(w) => format!(
+            "SELECT * FROM ({}) {} WHERE {}.{} > {} ORDER BY {}.{}",
+            q, INC_TMP_TAB_NAME, INC_TMP_TAB_NAME, col, w, INC_TMP_TAB_NAME, col
+        ),
+        None => format!(
+            "SELECT * FROM ({}) {} ORDER BY {}.{}",
+            q, INC_TMP_TAB_NAME, INC_TMP_TAB_NAME, col
+        ),
+    });
+
+    debug!("Transformed incremental query: {}", tsql);
+    tsql
+}
+
 #[throws(ConnectorXError)]
 pub fn single_col_partition_query<T: Dialect>(
     sql: &str,
@@ -340,7 +1171,7 @@ pub fn single_col_partition_query<T: Dialect>(
     // HACK: Some dialect (e.g. Oracle) does not support "AS" for alias
     #[cfg(feature = "src_oracle")]
     if dialect.type_id() == (OracleDialect {}.type_id()) {
-        return format!("SELECT * FROM ({}) CXTMPTAB_PART WHERE CXTMPTAB_PART.{} >= {} AND CXTMPTAB_PART.{} < {}", sql, col, lower, col, upper);
+        return single_col_partition_query_oracle(sql, col, lower, upper, false);
         // table_alias = "";
         // cid = Box::new(Expr::Identifier(Ident {
         //     value: col.to_string(),
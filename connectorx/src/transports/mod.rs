@@ -34,7 +34,7 @@ pub use bigquery_arrow::{BigQueryArrowTransport, BigQueryArrowTransportError};
 #[cfg(all(feature = "src_csv", feature = "dst_arrow"))]
 pub use csv_arrow::CSVArrowTransport;
 #[cfg(all(feature = "src_dummy", feature = "dst_arrow"))]
-pub use dummy_arrow::DummyArrowTransport;
+pub use dummy_arrow::{DummyArrowTransport, DummyArrowTransportError};
 #[cfg(all(feature = "src_dummy", feature = "dst_arrow2"))]
 pub use dummy_arrow2::DummyArrow2Transport;
 #[cfg(all(feature = "src_mssql", feature = "dst_arrow"))]
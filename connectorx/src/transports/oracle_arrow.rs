@@ -41,5 +41,6 @@ impl_transport!(
         { Date[NaiveDate]            => Date32[NaiveDate]          | conversion auto }
         { Timestamp[NaiveDateTime]   => Date64[NaiveDateTime]      | conversion auto }
         { TimestampTz[DateTime<Utc>] => DateTimeTz[DateTime<Utc>]  | conversion auto }
+        { Decimal[(i128, i8)]        => Decimal[(i128, i8)]        | conversion auto }
     }
 );
@@ -43,5 +43,15 @@ impl_transport!(
         { Date[NaiveDate]               => Date32[NaiveDate]            | conversion auto }
         { Timestamp[NaiveDateTime]      => Date64[NaiveDateTime]        | conversion auto }
         { TimestampTz[DateTime<Utc>]    => DateTimeTz[DateTime<Utc>]    | conversion auto }
+        { Decimal[(i128, i8)]           => Float64[f64]                 | conversion option }
     }
 );
+
+impl TypeConversion<(i128, i8), f64> for OracleArrow2Transport {
+    fn convert((mantissa, scale): (i128, i8)) -> f64 {
+        // parsed rather than divided, to round the value once
+        let text = format!("{}E{}", mantissa, -(scale as i32));
+        text.parse()
+            .unwrap_or_else(|_| panic!("cannot convert decimal {} to float64", text))
+    }
+}
@@ -7,18 +7,73 @@
 use crate::destinations::{Consume, Destination, DestinationPartition};
 use crate::errors::{ConnectorXError, Result as CXResult};
 use crate::sources::{PartitionParser, Produce, Source, SourcePartition};
+use std::fmt;
 
 #[doc(hidden)]
 /// `TypeSystem` describes all the types a source or destination support
 /// using enum variants.
 /// The variant can be used to type check with a static type `T` through the `check` method.
-pub trait TypeSystem: Copy + Clone + Send + Sync {
+/// Its `Display` is the name of the type in the error messages, see [`fmt_type`].
+pub trait TypeSystem: Copy + Clone + Send + Sync + fmt::Display {
     /// Check whether T is the same type as defined by self.
     fn check<T: TypeAssoc<Self>>(self) -> CXResult<()> {
         T::check(self)
     }
 }
 
+/// Display a type of a type system as its name in the source or destination, e.g. `VARCHAR`, with
+/// a `(nullable)` suffix for the nullable variant.
+pub fn fmt_type(f: &mut fmt::Formatter, name: &str, nullable: bool) -> fmt::Result {
+    match nullable {
+        true => write!(f, "{}(nullable)", name),
+        false => write!(f, "{}", name),
+    }
+}
+
+/// A type name split by [`parse_type`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeName {
+    /// The name without its parameters, in upper case with single spaces, e.g.
+    /// `TIMESTAMP WITH TIME ZONE` for `timestamp(6) with  time zone`.
+    pub name: String,
+    /// The parameters between parentheses, e.g. `["18", "2"]` for `NUMBER(18, 2)`.
+    pub params: Vec<String>,
+    pub nullable: bool,
+}
+
+/// Split the name of a type as displayed by a type system, e.g. `NUMBER(18,2)(nullable)`, or as
+/// written in a DDL, to parse it back into a variant of the type system with `TryFrom<&str>`. The
+/// type is nullable if it has the `(nullable)` suffix of [`fmt_type`].
+pub fn parse_type(ty: &str) -> TypeName {
+    let mut ty = ty.trim();
+    let suffix = ty.len().saturating_sub("(nullable)".len());
+    let nullable = ty
+        .get(suffix..)
+        .map_or(false, |s| s.eq_ignore_ascii_case("(nullable)"));
+    if nullable {
+        ty = &ty[..suffix];
+    }
+    let (name, params) = match (ty.find('('), ty.rfind(')')) {
+        (Some(open), Some(close)) if open < close => (
+            format!("{}{}", &ty[..open], &ty[close + 1..]),
+            ty[open + 1..close]
+                .split(',')
+                .map(|p| p.trim().to_string())
+                .collect(),
+        ),
+        _ => (ty.to_string(), vec![]),
+    };
+    TypeName {
+        name: name
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_uppercase(),
+        params,
+        nullable,
+    }
+}
+
 #[doc(hidden)]
 /// Associate a static type to a TypeSystem
 pub trait TypeAssoc<TS: TypeSystem> {
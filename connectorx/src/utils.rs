@@ -1,4 +1,5 @@
 use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
 
 pub struct DummyBox<T>(pub T);
 
@@ -15,3 +16,60 @@ impl<T> DerefMut for DummyBox<T> {
         &mut self.0
     }
 }
+
+/// The values collected by the partitions of a source, e.g. the rows they skipped, shared with
+/// them so that they can be read after the source was consumed by the load. The clones share the
+/// values.
+#[derive(Debug)]
+pub struct Collector<T> {
+    values: Arc<Mutex<Vec<T>>>,
+}
+
+impl<T> Clone for Collector<T> {
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<T> Default for Collector<T> {
+    fn default() -> Self {
+        Self {
+            values: Arc::new(Mutex::new(vec![])),
+        }
+    }
+}
+
+impl<T> Collector<T> {
+    pub fn push(&self, value: T) {
+        self.values.lock().unwrap().push(value);
+    }
+
+    /// Update the values under the lock, e.g. to replace one.
+    pub fn update<R>(&self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        f(&mut self.values.lock().unwrap())
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.lock().unwrap().len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The values collected so far, leaving none behind.
+    pub fn take(&self) -> Vec<T> {
+        std::mem::take(&mut *self.values.lock().unwrap())
+    }
+}
+
+impl<T: Clone> Collector<T> {
+    /// The values collected so far.
+    pub fn get(&self) -> Vec<T> {
+        self.values.lock().unwrap().clone()
+    }
+}
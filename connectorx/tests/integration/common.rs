@@ -0,0 +1,42 @@
+use arrow::array::Int64Array;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use arrow::util::pretty::print_batches;
+use connectorx::sql::CXQuery;
+
+/// The queries of the two partitions of `table`, split on its `id` column.
+pub fn partition_queries(table: &str) -> Vec<CXQuery<String>> {
+    vec![
+        CXQuery::naked(format!("SELECT * FROM {} WHERE id <= 2", table)),
+        CXQuery::naked(format!("SELECT * FROM {} WHERE id > 2", table)),
+    ]
+}
+
+/// Print `batches` and check their rows, ordered by `id`, their first column, against `expected`,
+/// where a NULL is an empty string.
+pub fn assert_rows(batches: &[RecordBatch], expected: &[&[&str]]) {
+    print_batches(batches).unwrap();
+    let mut rows = vec![];
+    for batch in batches {
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        for row in 0..batch.num_rows() {
+            let values: Vec<String> = batch
+                .columns()
+                .iter()
+                .map(|col| array_value_to_string(col, row).unwrap())
+                .collect();
+            rows.push((ids.value(row), values));
+        }
+    }
+    rows.sort_by_key(|(id, _)| *id);
+    let rows: Vec<Vec<String>> = rows.into_iter().map(|(_, values)| values).collect();
+    let expected: Vec<Vec<String>> = expected
+        .iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect();
+    assert_eq!(expected, rows);
+}
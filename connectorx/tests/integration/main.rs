@@ -0,0 +1,13 @@
+//! Integration tests of the sources against databases started in Docker containers with
+//! testcontainers, so that they need no database set up beforehand. Each test creates a table
+//! whose last row is all NULLs, loads it into Arrow with two partitions, and compares the values
+//! with the expected ones.
+//!
+//! They need Docker, so they are ignored by default:
+//! `cargo test --features all --test integration -- --ignored`
+
+mod common;
+mod mysql;
+mod oracle;
+mod postgres;
+mod sqlite;
@@ -0,0 +1,82 @@
+use crate::common::{assert_rows, partition_queries};
+use connectorx::{
+    prelude::*,
+    sources::postgres::{rewrite_tls_args, BinaryProtocol, PostgresSource},
+    transports::PostgresArrowTransport,
+};
+use postgres::{Client, NoTls};
+use testcontainers::{clients, images::postgres::Postgres};
+use url::Url;
+
+#[test]
+#[ignore]
+fn test_postgres_container() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let docker = clients::Cli::default();
+    let node = docker.run(Postgres::default());
+    let dburl = format!(
+        "postgresql://postgres@127.0.0.1:{}/postgres",
+        node.get_host_port_ipv4(5432)
+    );
+
+    let mut client = Client::connect(&dburl, NoTls).unwrap();
+    client
+        .batch_execute(
+            "CREATE TABLE cx_types (
+                id INTEGER,
+                i16 SMALLINT,
+                i64 BIGINT,
+                f FLOAT8,
+                n NUMERIC(10, 2),
+                t TEXT,
+                v VARCHAR(10),
+                b BOOLEAN,
+                d DATE
+            );
+            INSERT INTO cx_types VALUES
+                (1, 1, 10000000000, 1.5, 12.25, 'a', 'x', true, '2021-01-01'),
+                (2, -2, -20000000000, -2.5, -0.5, 'bc', 'yz', false, '2021-12-31'),
+                (3, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL);",
+        )
+        .unwrap();
+
+    let (config, _tls) = rewrite_tls_args(&Url::parse(&dburl).unwrap()).unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let mut destination = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
+        source,
+        &mut destination,
+        &partition_queries("cx_types"),
+        Some("SELECT * FROM cx_types".to_string()),
+    );
+    dispatcher.run().unwrap();
+
+    assert_rows(
+        &destination.arrow().unwrap(),
+        &[
+            &[
+                "1",
+                "1",
+                "10000000000",
+                "1.5",
+                "12.25",
+                "a",
+                "x",
+                "true",
+                "2021-01-01",
+            ],
+            &[
+                "2",
+                "-2",
+                "-20000000000",
+                "-2.5",
+                "-0.5",
+                "bc",
+                "yz",
+                "false",
+                "2021-12-31",
+            ],
+            &["3", "", "", "", "", "", "", "", ""],
+        ],
+    );
+}
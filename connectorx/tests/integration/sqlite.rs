@@ -0,0 +1,51 @@
+use crate::common::{assert_rows, partition_queries};
+use connectorx::{prelude::*, transports::SQLiteArrowTransport};
+use rusqlite::Connection;
+use std::env;
+use std::fs;
+
+/// SQLite needs no container, the database is a file.
+#[test]
+fn test_sqlite_file() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let path = env::temp_dir().join(format!("connectorx_integration_{}.db", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE cx_types (
+            id INTEGER,
+            i INTEGER,
+            f REAL,
+            t TEXT,
+            b BOOLEAN,
+            d DATE
+        );
+        INSERT INTO cx_types VALUES
+            (1, 10000000000, 1.5, 'a', 1, '2021-01-01'),
+            (2, -20000000000, -2.5, 'bc', 0, '2021-12-31'),
+            (3, NULL, NULL, NULL, NULL, NULL);",
+    )
+    .unwrap();
+
+    let source = SQLiteSource::new(path.to_str().unwrap(), 2).unwrap();
+    let mut destination = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, SQLiteArrowTransport>::new(
+        source,
+        &mut destination,
+        &partition_queries("cx_types"),
+        Some("SELECT * FROM cx_types".to_string()),
+    );
+    dispatcher.run().unwrap();
+    let batches = destination.arrow().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_rows(
+        &batches,
+        &[
+            &["1", "10000000000", "1.5", "a", "true", "2021-01-01"],
+            &["2", "-20000000000", "-2.5", "bc", "false", "2021-12-31"],
+            &["3", "", "", "", "", ""],
+        ],
+    );
+}
@@ -1,18 +1,25 @@
 use arrow::{
-    array::{BooleanArray, Float64Array, Int64Array, StringArray},
-    record_batch::RecordBatch,
+    array::{BooleanArray, DecimalArray, Float64Array, Int64Array, StringArray},
+    datatypes::DataType,
+    ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream},
+    ipc::reader::StreamReader,
+    record_batch::{RecordBatch, RecordBatchReader},
 };
 use connectorx::{
-    constants::RECORD_BATCH_SIZE,
-    destinations::arrow::{ArrowDestination, ArrowTypeSystem},
+    constants::{DECIMAL_PRECISION, DECIMAL_SCALE, RECORD_BATCH_SIZE},
+    destinations::arrow::{
+        Agg, ArrowDestination, ArrowTypeSystem, MmapDestination, TempFileDestination,
+    },
     prelude::*,
     sources::{
         dummy::{DummySource, DummyTypeSystem},
         postgres::{rewrite_tls_args, BinaryProtocol, PostgresSource},
+        watermark::{WatermarkTracker, WATERMARK_METADATA_KEY},
     },
     sql::CXQuery,
-    transports::{DummyArrowTransport, PostgresArrowTransport},
+    transports::{DummyArrowTransport, DummyArrowTransportError, PostgresArrowTransport},
 };
+use connectorx::{destinations::tee::TeeDestination, impl_transport};
 use postgres::NoTls;
 use std::env;
 use url::Url;
@@ -35,6 +42,56 @@ fn arrow_destination_col_major() {
         .unwrap();
 }
 
+#[test]
+fn test_arrow_decimal() {
+    let mut destination = ArrowDestination::new();
+    destination
+        .allocate(
+            4,
+            &["d"],
+            &[ArrowTypeSystem::Decimal(true)],
+            DataOrder::RowMajor,
+        )
+        .unwrap();
+    {
+        let mut partitions = destination.partition(1).unwrap();
+        let writer = &mut partitions[0];
+        let values: [Option<(i128, i8)>; 4] =
+            [Some((425, 1)), None, Some((-123456789, 4)), Some((12, -2))];
+        for value in values {
+            writer.consume(value).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    let records = destination.arrow().unwrap();
+    assert_eq!(
+        &DataType::Decimal(DECIMAL_PRECISION, DECIMAL_SCALE),
+        records[0].schema().field(0).data_type()
+    );
+    let col = records[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<DecimalArray>()
+        .unwrap();
+    assert_eq!(425 * 10i128.pow(9), col.value(0));
+    assert!(records[0].column(0).is_null(1));
+    assert_eq!(-123456789 * 10i128.pow(6), col.value(2));
+    assert_eq!(12 * 10i128.pow(12), col.value(3));
+
+    // more decimal places than the column keeps
+    let mut destination = ArrowDestination::new();
+    destination
+        .allocate(
+            1,
+            &["d"],
+            &[ArrowTypeSystem::Decimal(false)],
+            DataOrder::RowMajor,
+        )
+        .unwrap();
+    let mut partitions = destination.partition(1).unwrap();
+    assert!(partitions[0].consume((1i128, 11i8)).is_err());
+}
+
 #[test]
 fn test_arrow() {
     let schema = [
@@ -141,6 +198,332 @@ fn test_arrow() {
     }
 }
 
+#[test]
+fn test_arrow_row_count() {
+    let schema = [DummyTypeSystem::I64(true), DummyTypeSystem::String(true)];
+    let nrows = vec![4, 7, RECORD_BATCH_SIZE + 1];
+    let queries: Vec<CXQuery> = nrows
+        .iter()
+        .map(|v| CXQuery::naked(format!("{},{}", v, schema.len())))
+        .collect();
+    let mut destination = ArrowDestination::new();
+    let counter = destination.row_counter();
+    assert_eq!(0, destination.row_count());
+
+    let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+        DummySource::new(&["a", "b"], &schema),
+        &mut destination,
+        &queries,
+        None,
+    );
+    dispatcher.run().expect("run dispatcher");
+
+    let total: usize = nrows.iter().sum();
+    assert_eq!(total, destination.row_count());
+    assert_eq!(total, counter.load(std::sync::atomic::Ordering::Relaxed));
+    let records = destination.arrow().unwrap();
+    assert_eq!(total, records.iter().map(|r| r.num_rows()).sum::<usize>());
+}
+
+#[test]
+fn test_arrow_aggregate() {
+    let schema = [
+        DummyTypeSystem::I64(true),
+        DummyTypeSystem::F64(true),
+        DummyTypeSystem::Bool(false),
+    ];
+    let nrows = vec![4, 7];
+    let ncols = schema.len();
+    let queries: Vec<CXQuery> = nrows
+        .iter()
+        .map(|v| CXQuery::naked(format!("{},{}", v, ncols)))
+        .collect();
+    let run = || {
+        let mut destination = ArrowDestination::new();
+        let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+            DummySource::new(&["a", "b", "c"], &schema),
+            &mut destination,
+            &queries,
+            None,
+        );
+        dispatcher.run().expect("run dispatcher");
+        destination
+    };
+
+    let rb = run()
+        .aggregate(&[
+            Agg::Sum { col: "a" },
+            Agg::Mean { col: "b" },
+            Agg::Min { col: "a" },
+            Agg::Max { col: "b" },
+            Agg::Count { col: "a" },
+            Agg::CountDistinct { col: "a" },
+        ])
+        .unwrap();
+    assert_eq!(1, rb.num_rows());
+    let int = |i: usize| {
+        rb.column(i)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .value(0)
+    };
+    let float = |i: usize| {
+        rb.column(i)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap()
+            .value(0)
+    };
+    assert_eq!(27, int(0));
+    assert!((float(1) - 27.0 / 11.0).abs() < 1e-9);
+    assert_eq!(0, int(2));
+    assert_eq!(6.0, float(3));
+    let count = |i: usize| {
+        rb.column(i)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .value(0)
+    };
+    assert_eq!(11, count(4));
+    assert_eq!(7, count(5));
+
+    let rb = run()
+        .aggregate(&[Agg::GroupBy {
+            keys: vec!["c"],
+            aggs: vec![Agg::Sum { col: "a" }, Agg::Count { col: "a" }],
+        }])
+        .unwrap();
+    assert_eq!(2, rb.num_rows());
+    let keys = rb
+        .column(0)
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .unwrap();
+    let sums = rb.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let counts = rb.column(2).as_any().downcast_ref::<Int64Array>().unwrap();
+    for i in 0..rb.num_rows() {
+        match keys.value(i) {
+            true => {
+                assert_eq!(14, sums.value(i));
+                assert_eq!(6, counts.value(i));
+            }
+            false => {
+                assert_eq!(13, sums.value(i));
+                assert_eq!(5, counts.value(i));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_aggregate_types() {
+    use arrow::array::Date32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use connectorx::destinations::arrow::aggregate;
+    use std::sync::Arc;
+
+    let big = (1 << 53) + 1;
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Utf8, false),
+            Field::new("i", DataType::Int64, true),
+            Field::new("s", DataType::Utf8, true),
+            Field::new("d", DataType::Date32, true),
+        ])),
+        vec![
+            Arc::new(StringArray::from(vec!["x", "y", "x"])),
+            Arc::new(Int64Array::from(vec![Some(big), Some(1), None])),
+            Arc::new(StringArray::from(vec![Some("pear"), None, Some("apple")])),
+            Arc::new(Date32Array::from(vec![Some(19000), Some(18000), None])),
+        ],
+    )
+    .unwrap();
+
+    let rb = aggregate(
+        &batch,
+        &[
+            Agg::Sum { col: "i" },
+            Agg::Max { col: "i" },
+            Agg::Min { col: "s" },
+            Agg::Max { col: "s" },
+            Agg::Min { col: "d" },
+        ],
+    )
+    .unwrap();
+    let ints = |i: usize| rb.column(i).as_any().downcast_ref::<Int64Array>().unwrap();
+    let strings = |i: usize| rb.column(i).as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(big + 1, ints(0).value(0));
+    assert_eq!(big, ints(1).value(0));
+    assert_eq!("apple", strings(2).value(0));
+    assert_eq!("pear", strings(3).value(0));
+    let dates = rb.column(4).as_any().downcast_ref::<Date32Array>().unwrap();
+    assert_eq!(18000, dates.value(0));
+
+    // the aggregations next to a GroupBy are computed per group
+    let rb = aggregate(
+        &batch,
+        &[
+            Agg::Max { col: "s" },
+            Agg::GroupBy {
+                keys: vec!["k"],
+                aggs: vec![Agg::Count { col: "i" }],
+            },
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        vec!["k", "max(s)", "count(i)"],
+        rb.schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>()
+    );
+    let keys = rb.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+    let maxs = rb.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+    let counts = rb.column(2).as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(
+        ("x", "pear", 1),
+        (keys.value(0), maxs.value(0), counts.value(0))
+    );
+    assert_eq!("y", keys.value(1));
+    assert!(maxs.is_null(1));
+    assert_eq!(1, counts.value(1));
+
+    let group_by = Agg::GroupBy {
+        keys: vec!["k"],
+        aggs: vec![],
+    };
+    assert!(aggregate(&batch, &[group_by.clone(), group_by]).is_err());
+}
+
+#[test]
+fn test_arrow_stream() {
+    let schema = [DummyTypeSystem::I64(true), DummyTypeSystem::String(true)];
+    let nrows = vec![4, 7];
+    let ncols = schema.len();
+    let queries: Vec<CXQuery> = nrows
+        .iter()
+        .map(|v| CXQuery::naked(format!("{},{}", v, ncols)))
+        .collect();
+    let mut destination = ArrowDestination::new();
+
+    let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+        DummySource::new(&["a", "b"], &schema),
+        &mut destination,
+        &queries,
+        None,
+    );
+    dispatcher.run().expect("run dispatcher");
+
+    let stream = destination.arrow_stream().unwrap();
+    let mut ffi_stream = Box::new(FFI_ArrowArrayStream::empty());
+    unsafe { stream.export(&mut *ffi_stream) };
+
+    let reader = ArrowArrayStreamReader::try_new(*ffi_stream).unwrap();
+    assert_eq!(ncols, reader.schema().fields().len());
+    let mut rows: Vec<usize> = reader.map(|rb| rb.unwrap().num_rows()).collect();
+    rows.sort_unstable();
+    assert_eq!(nrows, rows);
+}
+
+#[test]
+fn test_arrow_mmap() {
+    let schema = [
+        DummyTypeSystem::I64(true),
+        DummyTypeSystem::F64(true),
+        DummyTypeSystem::String(true),
+    ];
+    let nrows = vec![4, 7];
+    let ncols = schema.len();
+    let queries: Vec<CXQuery> = nrows
+        .iter()
+        .map(|v| CXQuery::naked(format!("{},{}", v, ncols)))
+        .collect();
+    let path = env::temp_dir().join("connectorx_test_arrow_mmap.arrow");
+    let mut destination = MmapDestination::new(&path).unwrap();
+
+    let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+        DummySource::new(&["a", "b", "c"], &schema),
+        destination.destination(),
+        &queries,
+        None,
+    );
+    dispatcher.run().expect("run dispatcher");
+
+    let result = destination.finish().unwrap();
+    assert_eq!(path, result.path);
+    assert_eq!(ncols, result.schema.fields().len());
+
+    let reader = StreamReader::try_new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut rows: Vec<usize> = reader.map(|rb| rb.unwrap().num_rows()).collect();
+    rows.sort_unstable();
+    assert_eq!(nrows, rows);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_arrow_temp_file() {
+    let schema = [DummyTypeSystem::I64(true), DummyTypeSystem::String(true)];
+    let queries = [CXQuery::naked("4,2"), CXQuery::naked("7,2")];
+    let run = |destination: &mut TempFileDestination| {
+        let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+            DummySource::new(&["a", "b"], &schema),
+            destination.destination().unwrap(),
+            &queries,
+            None,
+        );
+        dispatcher.run().expect("run dispatcher");
+    };
+
+    // the temp file is removed on drop
+    let mut destination = TempFileDestination::new().unwrap();
+    run(&mut destination);
+    let result = destination.finish().unwrap();
+    assert!(result.path.exists());
+    drop(destination);
+    assert!(!result.path.exists());
+
+    // unless it is kept
+    let path = env::temp_dir().join("connectorx_test_arrow_temp_file.arrow");
+    let mut destination = TempFileDestination::new().unwrap();
+    run(&mut destination);
+    let result = destination.keep(&path).unwrap();
+    assert_eq!(path, result.path);
+    let reader = StreamReader::try_new(std::fs::File::open(&path).unwrap()).unwrap();
+    let nrows: usize = reader.map(|rb| rb.unwrap().num_rows()).sum();
+    assert_eq!(11, nrows);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_arrow_watermark() {
+    let mut tracker = WatermarkTracker::with_lateness(2, 5);
+    assert_eq!(None, tracker.update(0, 100));
+    assert_eq!(i64::MIN, tracker.watermark());
+    assert_eq!(Some(45), tracker.update(1, 50));
+    // out of order event, the partition has progressed further already
+    assert_eq!(None, tracker.update(1, 40));
+    assert_eq!(None, tracker.update(0, 120));
+    assert_eq!(Some(75), tracker.update(1, 80));
+
+    let schema = [DummyTypeSystem::I64(true)];
+    let mut destination = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+        DummySource::new(&["a"], &schema),
+        &mut destination,
+        &[CXQuery::naked("3,1")],
+        None,
+    );
+    dispatcher.run().expect("run dispatcher");
+    let rb = destination.arrow().unwrap().pop().unwrap();
+    let tagged = tracker.tag(&rb).unwrap();
+    assert_eq!(rb.num_rows(), tagged.num_rows());
+    assert_eq!("75", tagged.schema().metadata()[WATERMARK_METADATA_KEY]);
+}
+
 #[test]
 fn test_arrow_large() {
     let schema = [
@@ -291,3 +674,148 @@ fn test_postgres_arrow() {
         }
     }
 }
+
+#[test]
+fn test_arrow_streaming() {
+    let schema = [DummyTypeSystem::I64(true), DummyTypeSystem::String(true)];
+    // the first partition is much larger than the second one
+    let queries = [CXQuery::naked("1000000,2"), CXQuery::naked("10,2")];
+    let (mut destination, rx) = ArrowDestination::new_streaming();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let handle = std::thread::spawn(move || {
+        pool.install(|| {
+            let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+                DummySource::new(&["a", "b"], &schema),
+                &mut destination,
+                &queries,
+                None,
+            );
+            dispatcher.run().expect("run dispatcher");
+        });
+    });
+
+    let received: Vec<(usize, usize)> = rx
+        .iter()
+        .map(|p| (p.partition, p.batches.iter().map(|rb| rb.num_rows()).sum()))
+        .collect();
+    handle.join().unwrap();
+    assert_eq!(vec![(1, 10), (0, 1000000)], received);
+}
+
+/// The transport of `DummyArrowTransport` routed to a tee of two arrow destinations.
+struct DummyArrowTeeTransport;
+
+impl_transport!(
+    name = DummyArrowTeeTransport,
+    error = DummyArrowTransportError,
+    systems = DummyTypeSystem => ArrowTypeSystem,
+    route = DummySource => TeeDestination<ArrowDestination, ArrowDestination>,
+    mappings = {
+        { F64[f64]                => Float64[f64]               | conversion auto}
+        { I64[i64]                => Int64[i64]                 | conversion auto}
+        { Bool[bool]              => Boolean[bool]              | conversion auto}
+        { String[String]          => LargeUtf8[String]          | conversion auto}
+    }
+);
+
+#[test]
+fn test_tee() {
+    let schema = [
+        DummyTypeSystem::I64(true),
+        DummyTypeSystem::F64(false),
+        DummyTypeSystem::Bool(false),
+        DummyTypeSystem::String(true),
+    ];
+    let queries = [CXQuery::naked(format!("{},{}", 7, schema.len()))];
+    let mut destination = TeeDestination::new(ArrowDestination::new(), ArrowDestination::new());
+    let dispatcher = Dispatcher::<_, _, DummyArrowTeeTransport>::new(
+        DummySource::new(&["a", "b", "c", "d"], &schema),
+        &mut destination,
+        &queries,
+        None,
+    );
+    dispatcher.run().expect("run dispatcher");
+
+    let (first, second) = destination.into_inner();
+    let first: Vec<RecordBatch> = first.arrow().unwrap();
+    let second: Vec<RecordBatch> = second.arrow().unwrap();
+    assert_eq!(1, first.len());
+    assert_eq!(7, first[0].num_rows());
+    assert_eq!(first[0].schema(), second[0].schema());
+    assert_eq!(first[0].columns(), second[0].columns());
+}
+
+/// Writes the worker loading the partition in place of the `I64` values, see
+/// `test_partition_assignment`.
+struct DummyWorkerTransport;
+
+impl_transport!(
+    name = DummyWorkerTransport,
+    error = DummyArrowTransportError,
+    systems = DummyTypeSystem => ArrowTypeSystem,
+    route = DummySource => ArrowDestination,
+    mappings = {
+        { I64[i64]                => Int64[i64]                 | conversion option}
+    }
+);
+
+impl TypeConversion<i64, i64> for DummyWorkerTransport {
+    fn convert(_: i64) -> i64 {
+        current_worker().expect("partition loaded by a worker") as i64
+    }
+}
+
+#[test]
+fn test_partition_assignment() {
+    let schema = [DummyTypeSystem::I64(false)];
+    // the number of rows of a partition identifies it in the result
+    let queries: Vec<CXQuery> = (1..=6)
+        .map(|v| CXQuery::naked(format!("{},{}", v, schema.len())))
+        .collect();
+    let run = |assignment: PartitionAssignment| {
+        let mut destination = ArrowDestination::new();
+        let mut dispatcher = Dispatcher::<_, _, DummyWorkerTransport>::new(
+            DummySource::new(&["a"], &schema),
+            &mut destination,
+            &queries,
+            None,
+        );
+        dispatcher.set_partition_assignment(assignment);
+        dispatcher.run()?;
+        let mut workers: Vec<(usize, i64)> = destination
+            .arrow()
+            .unwrap()
+            .iter()
+            .map(|rb| {
+                let col = rb.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+                assert!(col.values().iter().all(|&w| w == col.value(0)));
+                (rb.num_rows(), col.value(0))
+            })
+            .collect();
+        workers.sort_unstable();
+        Ok::<_, DummyArrowTransportError>(workers)
+    };
+
+    let pinned = PartitionAssignment::Pinned(vec![2, 0, 1, 2, 0, 1]);
+    for _ in 0..5 {
+        assert_eq!(
+            vec![(1, 2), (2, 0), (3, 1), (4, 2), (5, 0), (6, 1)],
+            run(pinned.clone()).unwrap()
+        );
+    }
+    assert_eq!(
+        vec![(1, 0), (2, 1), (3, 0), (4, 1), (5, 0), (6, 1)],
+        run(PartitionAssignment::RoundRobin(2)).unwrap()
+    );
+    assert!(run(PartitionAssignment::Pinned(vec![0, 1])).is_err());
+    assert!(run(PartitionAssignment::RoundRobin(0)).is_err());
+    assert_eq!(
+        Some(vec![vec![0, 2, 4], vec![1, 3]]),
+        PartitionAssignment::RoundRobin(2).workers(5).unwrap()
+    );
+    assert_eq!(None, PartitionAssignment::Dynamic.workers(5).unwrap());
+}
@@ -0,0 +1,106 @@
+use arrow::{
+    array::{Int64Array, StringArray},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatch,
+};
+use connectorx::{
+    prelude::*,
+    sources::dummy::{DummySource, DummyTypeSystem},
+    sql::CXQuery,
+};
+use std::sync::Arc;
+
+#[derive(SerializeToArrow, Debug, PartialEq)]
+struct User {
+    id: i64,
+    name: String,
+    age: Option<i64>,
+}
+
+#[test]
+fn test_derive_produce_row() {
+    let mut source = DummySource::new(
+        &["id", "name", "age"],
+        &[
+            DummyTypeSystem::I64(false),
+            DummyTypeSystem::String(false),
+            DummyTypeSystem::I64(true),
+        ],
+    );
+    source.set_queries(&[CXQuery::naked("2,3")]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+
+    let (n, _) = parser.fetch_next().unwrap();
+    let users = (0..n)
+        .map(|_| User::produce_row(&mut parser))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        vec![
+            User {
+                id: 0,
+                name: "0".to_string(),
+                age: Some(0)
+            },
+            User {
+                id: 1,
+                name: "1".to_string(),
+                age: Some(1)
+            },
+        ],
+        users
+    );
+}
+
+#[derive(FromArrow, Debug, PartialEq)]
+struct Account {
+    id: i64,
+    #[arrow(rename = "account_name")]
+    name: Option<String>,
+    #[arrow(default)]
+    balance: Option<i64>,
+}
+
+#[test]
+fn test_derive_from_arrow() {
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("account_name", DataType::Utf8, true),
+    ]);
+    let batch = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec![Some("a"), None])),
+        ],
+    )
+    .unwrap();
+
+    let accounts = Account::from_record_batch(&batch).unwrap();
+    assert_eq!(
+        vec![
+            Account {
+                id: 1,
+                name: Some("a".to_string()),
+                balance: None
+            },
+            Account {
+                id: 2,
+                name: None,
+                balance: None
+            },
+        ],
+        accounts
+    );
+}
+
+#[test]
+fn test_derive_from_arrow_missing_column() {
+    let schema = Schema::new(vec![Field::new("id", DataType::Int64, false)]);
+    let batch =
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(Int64Array::from(vec![1]))]).unwrap();
+    assert!(Account::from_record_batch(&batch).is_err());
+}
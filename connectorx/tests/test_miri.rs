@@ -0,0 +1,78 @@
+//! The data path from a source partition to the Arrow builders, without any FFI call, so that it
+//! can run under Miri (`just miri`), which reports the undefined behavior and the leaked memory.
+
+use arrow::array::{Float64Array, Int64Array, StringArray};
+use connectorx::{
+    destinations::arrow::ArrowDestination,
+    prelude::*,
+    sources::dummy::{DummySource, DummyTypeSystem},
+    sql::CXQuery,
+    transports::DummyArrowTransport,
+};
+
+const SCHEMA: [DummyTypeSystem; 3] = [
+    DummyTypeSystem::I64(false),
+    DummyTypeSystem::String(true),
+    DummyTypeSystem::F64(true),
+];
+
+fn load(nrows: &[usize]) -> ArrowDestination {
+    let queries: Vec<CXQuery> = nrows
+        .iter()
+        .map(|n| CXQuery::naked(format!("{},{}", n, SCHEMA.len())))
+        .collect();
+    let mut destination = ArrowDestination::new();
+    let dispatcher = Dispatcher::<_, _, DummyArrowTransport>::new(
+        DummySource::new(&["a", "b", "c"], &SCHEMA),
+        &mut destination,
+        &queries,
+        None,
+    );
+    dispatcher.run().expect("run dispatcher");
+    destination
+}
+
+#[test]
+fn test_miri_arrow() {
+    let nrows = [3, 5, 2];
+    let batches = load(&nrows).arrow().unwrap();
+    assert_eq!(nrows.len(), batches.len());
+
+    let mut sizes: Vec<usize> = batches.iter().map(|b| b.num_rows()).collect();
+    sizes.sort_unstable();
+    assert_eq!(vec![2, 3, 5], sizes);
+    for batch in &batches {
+        let n = batch.num_rows();
+        let ints = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(Int64Array::from((0..n as i64).collect::<Vec<_>>()), *ints);
+        let strings = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(
+            (0..n).map(|i| i.to_string()).collect::<Vec<_>>(),
+            strings
+                .iter()
+                .map(|s| s.unwrap().to_string())
+                .collect::<Vec<_>>()
+        );
+        let floats = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(n, floats.len());
+    }
+}
+
+#[test]
+fn test_miri_arrow_dropped() {
+    // the batches are freed with the destination, without being taken out of it
+    let destination = load(&[4, 4]);
+    drop(destination);
+}
@@ -5,10 +5,11 @@ use arrow::{
 use connectorx::{
     destinations::arrow::ArrowDestination,
     prelude::*,
-    sources::mysql::{BinaryProtocol, MySQLSource, TextProtocol},
+    sources::mysql::{BinaryProtocol, MySQLSource, MySQLTypeSystem, TextProtocol},
     sql::CXQuery,
     transports::MySQLArrowTransport,
 };
+use std::convert::TryFrom;
 use std::env;
 
 #[test]
@@ -125,3 +126,37 @@ pub fn verify_arrow_results(result: Vec<RecordBatch>) {
         }
     }
 }
+
+#[test]
+fn test_type_display() {
+    assert_eq!(
+        "BIGINT UNSIGNED",
+        MySQLTypeSystem::ULongLong(false).to_string()
+    );
+    assert_eq!(
+        "VARCHAR(nullable)",
+        MySQLTypeSystem::VarChar(true).to_string()
+    );
+    assert!(matches!(
+        MySQLTypeSystem::try_from("int(11) unsigned(nullable)"),
+        Ok(MySQLTypeSystem::ULong(true))
+    ));
+    assert!(matches!(
+        MySQLTypeSystem::try_from("DECIMAL(10,2)"),
+        Ok(MySQLTypeSystem::Decimal(false))
+    ));
+}
+
+#[test]
+fn test_mysql_lazy_pool() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("MYSQL_URL").unwrap();
+    let mut url = url::Url::parse(&dburl).unwrap();
+    url.set_password(Some("not-the-password")).unwrap();
+
+    // nothing connects before a connection is checked out, e.g. for a token to be set first
+    let mut source = MySQLSource::<BinaryProtocol>::new(url.as_str(), 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select * from test_table")]);
+    assert!(source.fetch_metadata().is_err());
+}
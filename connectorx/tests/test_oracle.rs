@@ -1,7 +1,30 @@
+use chrono::{DateTime, TimeZone, Utc};
+use connectorx::constants::{DECIMAL_PRECISION, DECIMAL_SCALE};
 use connectorx::prelude::*;
-use connectorx::sources::oracle::OracleSource;
-use connectorx::sql::CXQuery;
+use connectorx::sources::oracle::{
+    connect_oracle, decimal128, default_type_widths, fetch_error, identifier_limit,
+    retry_on_resource_busy, CachedValue, IntOrText, Iso8601Format, LobStorage, OracleSource,
+    OracleSourceError, OracleTypeSystem, QueryCache, SqlPlan, TimeZoneSuffix, DEFAULT_ACTION_NAME,
+    DEFAULT_MEMORY_WARNING_THRESHOLD, DEFAULT_MODULE_NAME,
+};
+use connectorx::sources::row_errors::{RowError, RowErrorPolicy, RowErrors};
+use connectorx::sources::table_scan::TableScan;
+use connectorx::sql::{
+    null_partition, single_col_partition_query_oracle, CXQuery, NullsOrder, PartitionBound,
+};
+use connectorx::typesystem::{parse_type, TypeName};
+use r2d2_oracle::oracle::sql_type::{Object, OracleType};
+use r2d2_oracle::oracle::SqlValue;
+use r2d2_oracle::oracle::{DbError, Error as OciError, Version};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::env;
+use std::ops::Bound;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use url::Url;
 
 #[test]
 #[ignore]
@@ -79,3 +102,2129 @@ fn test_types() {
         rows
     );
 }
+
+#[test]
+#[ignore]
+fn test_produce_raw() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+
+    source.set_queries(&[CXQuery::naked("select * from admin.test_table")]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+
+    let (n, _) = parser.fetch_next().unwrap();
+    assert!(n > 0);
+    let value = parser.produce_raw().unwrap();
+    assert!(matches!(
+        value.oracle_type().unwrap(),
+        OracleType::Number(_, _)
+    ));
+    assert_eq!(1, value.get::<i64>().unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_tail_first() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.tail_first("test_int", Some(3));
+
+    source.set_queries(&[CXQuery::naked("select test_int from test_table")]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    partition.result_rows().expect("run query");
+    assert_eq!(3, partition.nrows());
+
+    let mut parser = partition.parser().unwrap();
+    let mut rows: Vec<i64> = Vec::new();
+    loop {
+        let (n, is_last) = parser.fetch_next().unwrap();
+        for _i in 0..n {
+            rows.push(parser.produce().unwrap());
+        }
+        if is_last {
+            break;
+        }
+    }
+    assert_eq!(vec![2333, 5, 4], rows);
+}
+
+#[test]
+#[ignore]
+fn test_tail_first_partitioned() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 2).unwrap();
+    source.tail_first("test_int", Some(3));
+    source.set_queries(&[
+        CXQuery::naked("select test_int from test_table where test_int < 3"),
+        CXQuery::naked("select test_int from test_table where test_int >= 3"),
+    ]);
+    source.fetch_metadata().unwrap();
+    assert!(source.partition().is_err());
+
+    let mut source = OracleSource::new(&dburl, 2).unwrap();
+    source.tail_first("test_int", Some(3));
+    source.set_queries(&[
+        CXQuery::naked("select test_int from test_table where test_int < 3"),
+        CXQuery::naked("select test_int from test_table where test_int >= 3"),
+    ]);
+    source.set_origin_query(Some("select test_int from test_table".to_string()));
+    source.fetch_metadata().unwrap();
+    assert_eq!(Some(3), source.result_rows().unwrap());
+    let mut partitions = source.partition().unwrap();
+    assert_eq!(1, partitions.len());
+    let mut partition = partitions.remove(0);
+    partition.result_rows().expect("run query");
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let rows: Vec<i64> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(vec![2333, 5, 4], rows);
+}
+
+#[test]
+#[ignore]
+fn test_no_metadata_probe() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    // a FOR UPDATE query cannot be wrapped by the rownum probe
+    let queries = [CXQuery::naked(
+        "select test_int, test_char from test_table for update",
+    )];
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&queries);
+    assert!(source.fetch_metadata().is_err());
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.metadata_probe(false);
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    assert_eq!(vec!["TEST_INT", "TEST_CHAR"], source.names());
+}
+
+#[test]
+#[ignore]
+fn test_out_params() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+
+    let query = source
+        .out_params_query(
+            "BEGIN SELECT COUNT(*), MAX(TRIM(test_char)) INTO :cnt, :name FROM test_table; END;",
+            &[
+                ("cnt", OracleType::Number(10, 0)),
+                ("name", OracleType::Varchar2(5)),
+            ],
+        )
+        .unwrap();
+    source.set_queries(&[query]);
+    source.fetch_metadata().unwrap();
+    assert_eq!(vec!["CNT", "NAME"], source.names());
+
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    partition.result_rows().expect("run query");
+    assert_eq!(1, partition.nrows());
+
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(1, n);
+    let cnt: i64 = parser.produce().unwrap();
+    let name: String = parser.produce().unwrap();
+    assert_eq!(5, cnt);
+    assert_eq!("str2", name);
+}
+
+#[test]
+#[ignore]
+fn test_decimal_rounding() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    let produce = |strategy: RoundingStrategy| -> Decimal {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.decimal_scale(Some(2));
+        source.decimal_rounding(strategy);
+        source.set_queries(&[CXQuery::naked("select 2.345 as v from dual")]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        assert_eq!(1, n);
+        parser.produce().unwrap()
+    };
+
+    assert_eq!(
+        Decimal::new(235, 2),
+        produce(RoundingStrategy::MidpointAwayFromZero)
+    );
+    assert_eq!(
+        Decimal::new(234, 2),
+        produce(RoundingStrategy::MidpointNearestEven)
+    );
+}
+
+#[test]
+fn test_type_display() {
+    assert_eq!(
+        "VARCHAR(nullable)",
+        OracleTypeSystem::VarChar(true).to_string()
+    );
+    assert_eq!("FLOAT", OracleTypeSystem::Float(false).to_string());
+    assert_eq!(
+        "TIMESTAMP WITH TIME ZONE(nullable)",
+        OracleTypeSystem::TimestampTz(true).to_string()
+    );
+    let err = OracleTypeSystem::VarChar(true).check::<i64>().unwrap_err();
+    assert_eq!(
+        "Data type unexpected: \"VARCHAR(nullable)\" expected, i64 found.",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_type_try_from() {
+    use OracleTypeSystem::*;
+    assert!(matches!(
+        OracleTypeSystem::try_from("NUMBER(18,2)"),
+        Ok(NumFloat(false))
+    ));
+    assert!(matches!(
+        OracleTypeSystem::try_from("number(10)(nullable)"),
+        Ok(NumInt(true))
+    ));
+    assert!(matches!(
+        OracleTypeSystem::try_from("NUMBER(*, 0)"),
+        Ok(NumInt(false))
+    ));
+    assert!(matches!(
+        OracleTypeSystem::try_from("varchar2(100 char)"),
+        Ok(VarChar(false))
+    ));
+    assert!(matches!(
+        OracleTypeSystem::try_from("TIMESTAMP(6) WITH LOCAL TIME ZONE"),
+        Ok(TimestampTz(false))
+    ));
+    assert!(matches!(
+        OracleTypeSystem::try_from("XMLTYPE"),
+        Err(ConnectorXError::UnknownType(_))
+    ));
+
+    let types = [
+        NumInt(true),
+        Float(false),
+        NumFloat(true),
+        BinaryFloat(false),
+        BinaryDouble(true),
+        Blob(false),
+        Clob(true),
+        VarChar(false),
+        Char(true),
+        NVarChar(false),
+        NChar(true),
+        Date(false),
+        Timestamp(true),
+        TimestampTz(false),
+    ];
+    for ty in types {
+        let name = ty.to_string();
+        assert_eq!(
+            name,
+            OracleTypeSystem::try_from(name.as_str())
+                .unwrap()
+                .to_string()
+        );
+    }
+}
+
+#[test]
+fn test_parse_type() {
+    assert_eq!(
+        TypeName {
+            name: "TIMESTAMP WITH TIME ZONE".to_string(),
+            params: vec!["9".to_string()],
+            nullable: true,
+        },
+        parse_type(" timestamp(9)  with time zone(nullable)")
+    );
+    assert_eq!(
+        TypeName {
+            name: "NUMBER".to_string(),
+            params: vec!["18".to_string(), "2".to_string()],
+            nullable: false,
+        },
+        parse_type("NUMBER(18, 2)")
+    );
+    assert_eq!(
+        TypeName {
+            name: "DATE".to_string(),
+            params: vec![],
+            nullable: false,
+        },
+        parse_type("DATE")
+    );
+}
+
+#[test]
+fn test_identifier_limit() {
+    assert_eq!(30, identifier_limit(&Version::new(11, 2, 0, 4, 0)));
+    assert_eq!(30, identifier_limit(&Version::new(12, 1, 0, 2, 0)));
+    assert_eq!(128, identifier_limit(&Version::new(12, 2, 0, 1, 0)));
+    assert_eq!(128, identifier_limit(&Version::new(19, 0, 0, 0, 0)));
+}
+
+#[test]
+fn test_decimal128() {
+    assert_eq!((123456789, 4), decimal128("12345.6789", Some(4)).unwrap());
+    assert_eq!((-123450, 4), decimal128("-12.345", Some(4)).unwrap());
+    assert_eq!((5000, 4), decimal128(".5", Some(4)).unwrap());
+    assert_eq!((0, 2), decimal128("0", Some(2)).unwrap());
+    assert_eq!((12, -2), decimal128("1200", Some(-2)).unwrap());
+    assert_eq!((12345, 3), decimal128("12.345", None).unwrap());
+    assert_eq!((125, 0), decimal128("1.25E+2", None).unwrap());
+    assert_eq!((15, 4), decimal128("1.5E-3", None).unwrap());
+    assert_eq!(
+        (i128::pow(10, 37), 0),
+        decimal128(&format!("1{}", "0".repeat(37)), None).unwrap()
+    );
+
+    assert!(matches!(
+        decimal128(&format!("1{}", "0".repeat(38)), None),
+        Err(OracleSourceError::Decimal128Overflow(_))
+    ));
+    assert!(matches!(
+        decimal128("1E-39", None),
+        Err(OracleSourceError::Decimal128Overflow(_))
+    ));
+    // rejected before the digits are padded with zeros
+    for s in &["1E+2000000000", "1E-2147483648", "-9.9E2147483647"] {
+        assert!(
+            matches!(
+                decimal128(s, None),
+                Err(OracleSourceError::Decimal128Overflow(_))
+            ),
+            "{}",
+            s
+        );
+    }
+    assert!(matches!(
+        decimal128("1E+2000000000", Some(2)),
+        Err(OracleSourceError::Decimal128Overflow(_))
+    ));
+    assert!(decimal128("12.345", Some(2)).is_err());
+    assert!(decimal128("1.2.3", None).is_err());
+    assert!(decimal128("abc", None).is_err());
+}
+
+#[test]
+#[ignore]
+fn test_decimal128_number_column() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select cast(1234567890123456.7891 as number(20,4)) as v, \
+         cast(null as number(20,4)) as n, cast(42.5 as number(20,4)) as w from dual",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(1, n);
+    let v: (i128, i8) = parser.produce().unwrap();
+    assert_eq!((12345678901234567891, 4), v);
+    let n: Option<(i128, i8)> = parser.produce().unwrap();
+    assert_eq!(None, n);
+    let w: Option<(i128, i8)> = parser.produce().unwrap();
+    assert_eq!(Some((425000, 4)), w);
+}
+
+#[test]
+#[ignore]
+fn test_decimal128_arrow() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let queries = [CXQuery::naked(
+        "select cast(1234567890123456.7891 as number(20,4)) as v, \
+         cast(null as number(20,4)) as n, cast(1.5 as number) as f from dual",
+    )];
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.numbers_as_decimal128(true);
+    let mut destination = ArrowDestination::new();
+    let dispatcher =
+        Dispatcher::<_, _, OracleArrowTransport>::new(source, &mut destination, &queries, None);
+    dispatcher.run().unwrap();
+    let records = destination.arrow().unwrap();
+    let schema = records[0].schema();
+    assert_eq!(
+        &arrow::datatypes::DataType::Decimal(DECIMAL_PRECISION, DECIMAL_SCALE),
+        schema.field(0).data_type()
+    );
+    // an unconstrained NUMBER has no scale to keep
+    assert_eq!(
+        &arrow::datatypes::DataType::Float64,
+        schema.field(2).data_type()
+    );
+    let col = records[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<arrow::array::DecimalArray>()
+        .unwrap();
+    assert_eq!(12345678901234567891 * 10i128.pow(6), col.value(0));
+    assert!(records[0].column(1).is_null(0));
+}
+
+#[test]
+#[ignore]
+fn test_money_columns() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    let produce = |strict: bool| -> Result<Vec<Decimal>, OracleSourceError> {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.money_columns(&["amount"], 2);
+        source.strict(strict);
+        source.set_queries(&[CXQuery::naked(
+            "select 2.345 as amount, 1.5 as price from dual \
+             union all select 7 as amount, 1.5 as price from dual",
+        )]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        assert_eq!(2, n);
+        let mut values = vec![];
+        for _ in 0..n * 2 {
+            values.push(parser.produce()?);
+        }
+        Ok(values)
+    };
+
+    // the amounts are rounded half-even and padded to 2 decimals, the prices are left as they are
+    let values = produce(false).unwrap();
+    assert_eq!(
+        vec!["2.34", "1.5", "7.00", "1.5"],
+        values.iter().map(|v| v.to_string()).collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        produce(true),
+        Err(OracleSourceError::DecimalTruncation(_, 2))
+    ));
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.money_columns(&["missing"], 2);
+    source.set_queries(&[CXQuery::naked("select 1 as amount from dual")]);
+    source.fetch_metadata().unwrap();
+    assert!(source.partition().is_err());
+}
+
+#[test]
+#[ignore]
+fn test_gv_view() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+
+    let queries = source.gv_view_queries("GV$SESSION", true).unwrap();
+    assert!(!queries.is_empty());
+    assert!(source.gv_view_queries("DUAL", true).is_err());
+
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    assert!(source.names().iter().any(|n| n == "INST_ID"));
+    let partitions = source.partition().unwrap();
+    assert_eq!(queries.len(), partitions.len());
+}
+
+#[test]
+#[ignore]
+fn test_partition_reset() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+
+    source.set_queries(&[CXQuery::naked(
+        "select test_int from test_table order by test_int",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+
+    let mut passes: Vec<Vec<i64>> = vec![];
+    for _ in 0..2 {
+        let mut parser = partition.reset().unwrap();
+        let mut rows = vec![];
+        loop {
+            let (n, is_last) = parser.fetch_next().unwrap();
+            for _i in 0..n {
+                rows.push(parser.produce().unwrap());
+            }
+            if is_last {
+                break;
+            }
+        }
+        passes.push(rows);
+    }
+    assert_eq!(vec![1, 2, 4, 5, 2333], passes[0]);
+    assert_eq!(passes[0], passes[1]);
+}
+
+#[test]
+#[ignore]
+fn test_lob_prefetch() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let conn = connect_oracle(&Url::parse(&dburl).unwrap())
+        .unwrap()
+        .connect()
+        .unwrap();
+    let _ = conn.execute("DROP TABLE test_lob_prefetch", &[]);
+    conn.execute("CREATE TABLE test_lob_prefetch(id NUMBER(5), c CLOB)", &[])
+        .unwrap();
+    conn.execute(
+        "INSERT INTO test_lob_prefetch SELECT level, TO_CLOB('clob' || level) FROM dual CONNECT BY level <= 100",
+        &[],
+    )
+    .unwrap();
+    conn.commit().unwrap();
+
+    // read all the rows and return them with the round trips of the reading session
+    let read = |prefetch: Option<u32>| -> (Vec<String>, u64) {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.lob_prefetch(prefetch);
+        source.set_queries(&[CXQuery::naked(
+            "select c, sys_context('USERENV', 'SID') sid from test_lob_prefetch order by id",
+        )]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let mut parser = partition.parser().unwrap();
+        let mut rows = vec![];
+        let mut sid = String::new();
+        loop {
+            let (n, is_last) = parser.fetch_next().unwrap();
+            for _i in 0..n {
+                rows.push(parser.produce().unwrap());
+                sid = parser.produce().unwrap();
+            }
+            if is_last {
+                break;
+            }
+        }
+        let roundtrips = conn
+            .query_row_as::<u64>(
+                "SELECT s.value FROM v$sesstat s JOIN v$statname n ON s.statistic# = n.statistic# \
+                 WHERE n.name = 'SQL*Net roundtrips to/from client' AND s.sid = :1",
+                &[&sid],
+            )
+            .unwrap();
+        (rows, roundtrips)
+    };
+
+    let (rows, without) = read(None);
+    let (prefetched, with) = read(Some(100));
+    assert_eq!(100, rows.len());
+    assert_eq!("clob1", rows[0]);
+    assert_eq!(rows, prefetched);
+    assert!(with < without);
+
+    // multi-byte values at the size limit and empty CLOBs, which are not null
+    conn.execute("TRUNCATE TABLE test_lob_prefetch", &[])
+        .unwrap();
+    conn.execute(
+        "INSERT INTO test_lob_prefetch VALUES (1, TO_CLOB(RPAD('\u{e9}', 1000, '\u{e9}')))",
+        &[],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO test_lob_prefetch VALUES (2, EMPTY_CLOB())",
+        &[],
+    )
+    .unwrap();
+    conn.commit().unwrap();
+    let (prefetched, _) = read(Some(4000));
+    assert_eq!(vec!["\u{e9}".repeat(1000), String::new()], prefetched);
+
+    conn.execute("DROP TABLE test_lob_prefetch", &[]).unwrap();
+}
+
+#[test]
+#[ignore]
+fn test_column_descriptors() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+
+    source.set_queries(&[CXQuery::naked(
+        "select cast(test_float as number(10,2)) amount, test_char from test_table",
+    )]);
+    source.fetch_metadata().unwrap();
+    let descriptors = source.column_descriptors();
+    assert_eq!(2, descriptors.len());
+    assert_eq!("AMOUNT", descriptors[0].name);
+    assert_eq!(Some(10), descriptors[0].precision);
+    assert_eq!(Some(2), descriptors[0].scale);
+    assert_eq!(None, descriptors[0].length);
+    assert_eq!(Some(5), descriptors[1].length);
+}
+
+#[test]
+#[ignore]
+fn test_edition() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    assert!(source.edition("ORA$BASE; DROP".to_string()).is_err());
+    source.edition("ORA$BASE".to_string()).unwrap();
+
+    source.set_queries(&[CXQuery::naked(
+        "select sys_context('USERENV', 'CURRENT_EDITION_NAME') edition from dual",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(1, n);
+    let edition: String = parser.produce().unwrap();
+    assert_eq!("ORA$BASE", edition);
+}
+
+#[test]
+#[ignore]
+fn test_query_scalar() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let source = OracleSource::new(&dburl, 1).unwrap();
+
+    let count: i64 = source
+        .query_scalar("select count(*) from test_table")
+        .unwrap();
+    assert_eq!(5, count);
+    let max: f64 = source
+        .query_scalar("select max(test_float) from test_table")
+        .unwrap();
+    assert_eq!(2.2, max);
+    assert!(source
+        .query_scalar::<i64>("select test_int from test_table")
+        .is_err());
+}
+
+#[test]
+#[ignore]
+fn test_prewarm() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 3).unwrap();
+    source.client_result_cache(true).unwrap();
+    source.prewarm().unwrap();
+    assert_eq!((3, 3), source.pool_state());
+
+    source.set_queries(&[
+        CXQuery::naked("select test_int from test_table where test_int < 2"),
+        CXQuery::naked("select test_int from test_table where test_int >= 2"),
+    ]);
+    source.fetch_metadata().unwrap();
+    let partitions = source.partition().unwrap();
+    assert_eq!(2, partitions.len());
+}
+
+#[test]
+#[ignore]
+fn test_skip_count_monitoring_query() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let query =
+        "select cast(snap_id as number(10)) snap_id from dba_hist_snapshot where rownum <= 3";
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.set_origin_query(Some(query.to_string()));
+    source.fetch_metadata().unwrap();
+    assert_eq!(None, source.result_rows().unwrap());
+
+    let mut partitions = source.partition().unwrap();
+    let partition = &mut partitions[0];
+    assert!(partition.skips_count());
+    partition.result_rows().unwrap();
+    let nrows = partition.nrows();
+    assert!(partition.is_cached());
+    let mut parser = partition.parser().unwrap();
+    let mut read = 0;
+    loop {
+        let (n, is_last) = parser.fetch_next().unwrap();
+        for _ in 0..n {
+            let _: i64 = parser.produce().unwrap();
+        }
+        read += n;
+        if is_last {
+            break;
+        }
+    }
+    assert_eq!(nrows, read);
+
+    // the other queries are still counted
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select test_int from test_table")]);
+    source.fetch_metadata().unwrap();
+    let partitions = source.partition().unwrap();
+    assert!(!partitions[0].skips_count());
+}
+
+#[test]
+#[ignore]
+fn test_skip_count_dblink_query() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    // a loopback link to the test database stands in for a remote one
+    let dblink = env::var("ORACLE_DBLINK").unwrap();
+    let query = format!("select test_int from test_table@{}", dblink);
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(query.as_str())]);
+    source.set_origin_query(Some(query.clone()));
+    source.fetch_metadata().unwrap();
+    assert_eq!(vec!["TEST_INT".to_string()], source.names());
+    assert_eq!(None, source.result_rows().unwrap());
+
+    let mut partitions = source.partition().unwrap();
+    let partition = &mut partitions[0];
+    assert!(partition.skips_count());
+    partition.result_rows().unwrap();
+    assert_eq!(3, partition.nrows());
+    assert!(partition.is_cached());
+}
+
+#[test]
+#[ignore]
+fn test_lock_retry() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let queries = [CXQuery::naked(
+        "select test_int from test_table where test_int = 1 for update nowait",
+    )];
+
+    // hold the row lock in another session
+    let locker = connect_oracle(&Url::parse(&dburl).unwrap())
+        .unwrap()
+        .connect()
+        .unwrap();
+    locker
+        .execute(
+            "select test_int from test_table where test_int = 1 for update",
+            &[],
+        )
+        .unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.metadata_probe(false);
+    source.set_queries(&queries);
+    assert!(source.fetch_metadata().is_err());
+
+    // release the lock while the source is backing off
+    let release = thread::spawn(move || {
+        thread::sleep(Duration::from_secs(1));
+        locker.rollback().unwrap();
+    });
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.metadata_probe(false);
+    source.lock_retry(6, Duration::from_millis(250));
+    source.set_queries(&queries);
+    source.fetch_metadata().unwrap();
+    release.join().unwrap();
+
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(1, n);
+    let v: i64 = parser.produce().unwrap();
+    assert_eq!(1, v);
+}
+
+#[test]
+fn test_retry_on_resource_busy() {
+    let error = |code: i32| {
+        OciError::OciError(DbError::new(
+            code,
+            0,
+            format!("ORA-{:05}", code),
+            "dpiStmt_execute".to_string(),
+            "execute".to_string(),
+        ))
+    };
+    let backoff = Duration::from_millis(1);
+
+    // busy twice, then the lock is released
+    let mut attempts = 0;
+    let res = retry_on_resource_busy(Some((3, backoff)), || {
+        attempts += 1;
+        match attempts {
+            1 | 2 => Err(error(54)),
+            _ => Ok(attempts),
+        }
+    });
+    assert_eq!(3, res.unwrap());
+
+    // out of attempts
+    let mut attempts = 0;
+    let res: Result<(), _> = retry_on_resource_busy(Some((2, backoff)), || {
+        attempts += 1;
+        Err(error(54))
+    });
+    assert!(matches!(res, Err(OciError::OciError(e)) if e.code() == 54));
+    assert_eq!(2, attempts);
+
+    // other errors, and no retry, fail at once
+    for (lock_retry, code) in [(Some((3, backoff)), 942), (None, 54)] {
+        let mut attempts = 0;
+        let res: Result<(), _> = retry_on_resource_busy(lock_retry, || {
+            attempts += 1;
+            Err(error(code))
+        });
+        assert!(res.is_err());
+        assert_eq!(1, attempts);
+    }
+}
+
+#[test]
+#[ignore]
+fn test_count_timeout() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.metadata_probe(false);
+    source.count_timeout(Some(Duration::from_secs(1)));
+    source.fetch_timeout(Some(Duration::from_secs(10)));
+    // both the count and the fetch take 3 seconds
+    let query = "select 1 as v from dual where cx_sleep(3) = 1";
+    source.set_origin_query(Some(query.to_string()));
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+
+    let err = source.result_rows().unwrap_err();
+    assert!(err.to_string().contains("DPI-1067"), "{}", err);
+
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let err = partition.result_rows().unwrap_err();
+    assert!(err.to_string().contains("DPI-1067"), "{}", err);
+
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(1, n);
+    let v: i64 = parser.produce().unwrap();
+    assert_eq!(1, v);
+}
+
+#[test]
+#[ignore]
+fn test_explain_plan() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.explain_plan(Some(1_000_000));
+    source.set_queries(&[CXQuery::naked("select test_int from test_table")]);
+    source.fetch_metadata().unwrap();
+    let estimates = source.plan_estimates();
+    assert_eq!(1, estimates.len());
+    assert!(estimates[0].cost.unwrap() <= 1_000_000);
+
+    // the cartesian product of the dictionary views has an absurd estimated cost
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.explain_plan(Some(1_000_000));
+    source.set_queries(&[CXQuery::naked(
+        "select a.object_id from all_objects a, all_objects b, all_objects c",
+    )]);
+    assert!(source.fetch_metadata().is_err());
+}
+
+#[test]
+#[ignore]
+fn test_capture_sql_plans() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let run = |baseline: Option<u64>| -> (Result<usize, OracleSourceError>, Vec<SqlPlan>) {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.capture_sql_plans(baseline);
+        let plans = source.sql_plans();
+        source.set_queries(&[CXQuery::naked(
+            "select /* cx_sql_plan */ test_int from test_table",
+        )]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        let parsed = partition
+            .parser()
+            .and_then(|mut parser| parser.fetch_next())
+            .map(|(n, _)| n);
+        (parsed, plans.get())
+    };
+
+    let (parsed, plans) = run(None);
+    assert_eq!(5, parsed.unwrap());
+    assert_eq!(1, plans.len());
+    assert_eq!(0, plans[0].partition);
+    let source = OracleSource::new(&dburl, 1).unwrap();
+    let text: String = source
+        .query_scalar(&format!(
+            "select sql_text from v$sql where sql_id = '{}' and rownum = 1",
+            plans[0].sql_id
+        ))
+        .unwrap();
+    assert!(text.contains("cx_sql_plan"));
+
+    let (parsed, same) = run(Some(plans[0].plan_hash));
+    assert!(parsed.is_ok());
+    assert_eq!(plans[0].sql_id, same[0].sql_id);
+
+    let (parsed, _) = run(Some(plans[0].plan_hash + 1));
+    assert!(matches!(
+        parsed,
+        Err(OracleSourceError::PlanChanged(sql_id, _, _)) if sql_id == plans[0].sql_id
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_adaptive_buf_size() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    let buf_size = |query: &str| -> u32 {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.adaptive_buf_size(1);
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        source.buf_size()
+    };
+    let narrow = buf_size("select test_int from test_table");
+    let wide = buf_size("select test_int, cast(test_char as varchar2(4000)) c from test_table");
+    assert_eq!((1 << 20) / 22, narrow);
+    assert_eq!((1 << 20) / (22 + 4000), wide);
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.adaptive_buf_size(1);
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, cast(test_char as varchar2(4000)) c from test_table order by test_int",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(5, n);
+}
+
+#[test]
+fn test_default_type_widths() {
+    let widths = default_type_widths();
+    assert_eq!(30, widths.len());
+    assert_eq!(8, widths[&OracleTypeSystem::NumInt(true)]);
+    assert_eq!(16, widths[&OracleTypeSystem::Decimal(true)]);
+    assert_eq!(8, widths[&OracleTypeSystem::NumInt(false)]);
+    assert_eq!(50, widths[&OracleTypeSystem::VarChar(true)]);
+    assert_eq!(4, widths[&OracleTypeSystem::Date(false)]);
+    assert_eq!(1 << 30, DEFAULT_MEMORY_WARNING_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn test_estimated_memory_usage() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[
+        CXQuery::naked("select test_int, test_char from test_table where test_int < 3"),
+        CXQuery::naked("select test_int, test_char from test_table where test_int >= 3"),
+    ]);
+    assert!(source.estimated_memory_usage(&HashMap::new()).is_err());
+
+    source.fetch_metadata().unwrap();
+    assert_eq!(
+        5 * (8 + 50),
+        source.estimated_memory_usage(&HashMap::new()).unwrap()
+    );
+
+    let mut widths = HashMap::new();
+    for nullable in [false, true] {
+        widths.insert(OracleTypeSystem::Char(nullable), 5);
+    }
+    source.memory_warning_threshold(10);
+    assert_eq!(5 * (8 + 5), source.estimated_memory_usage(&widths).unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_sys_refcursor() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::from_sys_refcursor(
+        &dburl,
+        "begin open :1 for select test_int, test_char from test_table order by test_int; end;",
+    )
+    .unwrap();
+    source.fetch_metadata().unwrap();
+    assert_eq!(vec!["TEST_INT", "TEST_CHAR"], source.names());
+    let mut partitions = source.partition().unwrap();
+    assert_eq!(1, partitions.len());
+    let mut partition = partitions.remove(0);
+    partition.result_rows().unwrap();
+    assert_eq!(5, partition.nrows());
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(5, n);
+    let first: (i64, Option<String>) = (parser.produce().unwrap(), parser.produce().unwrap());
+    assert_eq!((1, Some("str1 ".to_string())), first);
+}
+
+#[test]
+#[ignore]
+fn test_query_cache() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let cache = Arc::new(QueryCache::new(8, Duration::from_secs(60)));
+
+    let load = |time_zone: Option<&str>| -> (bool, Vec<(i64, Option<String>)>) {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.set_session_time_zone(time_zone);
+        source.with_query_cache(cache.clone());
+        source.set_queries(&[CXQuery::naked(
+            "select test_int, test_char from test_table order by test_int",
+        )]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let cached = partition.is_cached();
+        let mut parser = partition.parser().unwrap();
+        let (n, is_last) = parser.fetch_next().unwrap();
+        assert!(is_last);
+        let rows = (0..n)
+            .map(|_| (parser.produce().unwrap(), parser.produce().unwrap()))
+            .collect();
+        (cached, rows)
+    };
+
+    let (cached, first) = load(None);
+    assert!(!cached);
+    assert_eq!(1, cache.len());
+    let (cached, second) = load(None);
+    assert!(cached);
+    assert_eq!(1, cache.hits());
+    assert_eq!(first, second);
+    assert_eq!((1, Some("str1 ".to_string())), second[0]);
+
+    // another session time zone is another context
+    let (cached, _) = load(Some("+05:00"));
+    assert!(!cached);
+    assert_eq!(2, cache.len());
+    assert_eq!(1, cache.hits());
+}
+
+#[test]
+fn test_query_cache_context() {
+    let cache = QueryCache::new(8, Duration::from_secs(60));
+    let rows = vec![vec![CachedValue::Text("1".to_string())]];
+    cache.insert("db1", "select 1 from dual", rows.clone());
+    assert!(cache.get("db2", "select 1 from dual").is_none());
+    assert_eq!(rows, *cache.get("db1", "select 1 from dual").unwrap());
+
+    cache.insert("db2", "select 1 from dual", vec![]);
+    assert_eq!(2, cache.len());
+    assert_eq!(rows, *cache.get("db1", "select 1 from dual").unwrap());
+}
+
+#[test]
+#[ignore]
+fn test_partition_buf_size() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 3).unwrap();
+    source.adaptive_buf_size(1);
+    source.set_queries(&[
+        CXQuery::naked("select cast(test_char as varchar2(10)) c from test_table"),
+        CXQuery::naked("select test_clob c from test_types"),
+        CXQuery::naked("select cast(test_int as varchar2(10)) c from test_table"),
+    ]);
+    source.fetch_metadata().unwrap();
+    let partitions = source.partition().unwrap();
+    let sizes: Vec<u32> = partitions.iter().map(|p| p.buf_size()).collect();
+    assert_eq!(
+        vec![(1 << 20) / 10, (1 << 20) / 4000, (1 << 20) / 10],
+        sizes
+    );
+}
+
+#[test]
+#[ignore]
+fn test_int_or_text() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select 1 v from dual union all select 99999999999999999999 from dual union all select null from dual",
+    )]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(3, n);
+    let values: Vec<Option<IntOrText>> = (0..n).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(
+        vec![
+            Some(IntOrText::Int(1)),
+            Some(IntOrText::Text("99999999999999999999".to_string())),
+            None,
+        ],
+        values
+    );
+}
+
+#[test]
+#[ignore]
+fn test_column_stats() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let conn = connect_oracle(&Url::parse(&dburl).unwrap())
+        .unwrap()
+        .connect()
+        .unwrap();
+    conn.execute(
+        "BEGIN DBMS_STATS.GATHER_TABLE_STATS(USER, 'TEST_TABLE'); END;",
+        &[],
+    )
+    .unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.adaptive_buf_size(1);
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, test_int + 1 as next_int from test_table",
+    )]);
+    source.fetch_metadata().unwrap();
+    let stats = source.column_stats().unwrap();
+    assert_eq!(5, stats.rows);
+    assert_eq!(Some("1".to_string()), stats.columns[0].min);
+    assert_eq!(Some("2333".to_string()), stats.columns[0].max);
+    assert_eq!(Some(1.), stats.distinct_ratio("TEST_INT"));
+    // an expression has no statistics
+    assert_eq!(None, stats.columns[1].avg_bytes);
+    assert_eq!(None, stats.distinct_ratio("NEXT_INT"));
+    // the buffer is now sized from the actual width of the rows, the expression from its type
+    let row_bytes = stats.avg_row_bytes().ceil() as u32 + 22;
+    assert_eq!((1 << 20) / row_bytes, source.buf_size());
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select a.test_int from test_table a join test_table b on a.test_int = b.test_int",
+    )]);
+    source.fetch_metadata().unwrap();
+    assert!(source.column_stats().is_err());
+}
+
+#[test]
+#[ignore]
+fn test_empty_schema() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    // without `fetch_metadata` the partitions have no columns
+    source.set_queries(&[CXQuery::naked("select 1 from dual where 1=0")]);
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    assert!(matches!(
+        partition.parser(),
+        Err(OracleSourceError::EmptySchema)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_client_result_cache() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    // a single connection so that all the queries share the session and its cache
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.client_result_cache(true).unwrap();
+    // set by scripts/oracle.sql, the results are only cached on the server otherwise
+    let size: String = source
+        .query_scalar("select value from v$parameter where name = 'client_result_cache_size'")
+        .unwrap();
+    assert_ne!(
+        "0", size,
+        "the client result cache is disabled on the server"
+    );
+
+    let roundtrips = || -> i64 {
+        source
+            .query_scalar(
+                "select m.value from v$mystat m join v$statname n on m.statistic# = n.statistic# \
+                 where n.name = 'SQL*Net roundtrips to/from client'",
+            )
+            .unwrap()
+    };
+    let lookup = || -> i64 {
+        source
+            .query_scalar("select count(*) from test_table")
+            .unwrap()
+    };
+    let mode: String = source
+        .query_scalar("select value from v$parameter where name = 'result_cache_mode'")
+        .unwrap();
+    assert_eq!("FORCE", mode);
+    assert_eq!(5, lookup());
+
+    // the round trips of reading the statistic itself, a checkout costs none by itself
+    let start = roundtrips();
+    let baseline = roundtrips() - start;
+    // the repeated lookup is answered from the client cache
+    let start = roundtrips();
+    assert_eq!(5, lookup());
+    assert_eq!(baseline, roundtrips() - start);
+
+    source.client_result_cache(false).unwrap();
+    let mode: String = source
+        .query_scalar("select value from v$parameter where name = 'result_cache_mode'")
+        .unwrap();
+    assert_eq!("MANUAL", mode);
+}
+
+#[test]
+#[ignore]
+fn test_module_name() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 2).unwrap();
+    let module = |source: &OracleSource| -> (String, Option<String>) {
+        (
+            source
+                .query_scalar("select sys_context('USERENV', 'MODULE') from dual")
+                .unwrap(),
+            source
+                .query_scalar("select sys_context('USERENV', 'ACTION') from dual")
+                .unwrap(),
+        )
+    };
+    assert_eq!(
+        (
+            DEFAULT_MODULE_NAME.to_string(),
+            Some(DEFAULT_ACTION_NAME.to_string())
+        ),
+        module(&source)
+    );
+
+    // the setting is applied to one connection, the other picks it up as it is checked out
+    source.prewarm().unwrap();
+    source.set_module_name("cx_test", None).unwrap();
+    for _ in 0..4 {
+        assert_eq!(("cx_test".to_string(), None), module(&source));
+    }
+    assert_eq!((2, 2), source.pool_state());
+}
+
+#[test]
+#[ignore]
+fn test_comments() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.fetch_comments(true);
+
+    source.set_queries(&[CXQuery::naked("select test_int, test_char from test_table")]);
+    source.fetch_metadata().unwrap();
+    assert_eq!(
+        Some("table for the connectorx tests"),
+        source.table_comment()
+    );
+    assert_eq!(vec![Some("an integer"), None], source.column_comments());
+
+    // not a plain select from a table
+    source.set_queries(&[CXQuery::naked(
+        "select test_int from (select * from test_table)",
+    )]);
+    source.fetch_metadata().unwrap();
+    assert_eq!(None, source.table_comment());
+    assert_eq!(vec![None], source.column_comments());
+}
+
+#[test]
+#[ignore]
+fn test_lob_storage() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.fetch_lob_storage(true);
+
+    source.set_queries(&[CXQuery::naked("select id, doc, note from test_securefile")]);
+    source.fetch_metadata().unwrap();
+    let storage: Vec<_> = source
+        .column_descriptors()
+        .into_iter()
+        .map(|col| col.lob_storage)
+        .collect();
+    assert_eq!(
+        vec![
+            None,
+            Some(LobStorage {
+                securefile: true,
+                compression: Some("HIGH".to_string()),
+                deduplication: true,
+                encrypted: false,
+                in_row: true,
+            }),
+            Some(LobStorage {
+                securefile: false,
+                compression: None,
+                deduplication: false,
+                encrypted: false,
+                in_row: true,
+            }),
+        ],
+        storage
+    );
+
+    // not looked up by default
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select doc from test_securefile")]);
+    source.fetch_metadata().unwrap();
+    assert_eq!(None, source.column_descriptors()[0].lob_storage);
+}
+
+#[test]
+#[ignore]
+fn test_consumer_group() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 2).unwrap();
+    assert!(matches!(
+        source.with_consumer_group("CX_NO_SUCH_GROUP"),
+        Err(OracleSourceError::InvalidConsumerGroup(group)) if group == "CX_NO_SUCH_GROUP"
+    ));
+
+    // switching to the default group is granted to public
+    source
+        .with_consumer_group("DEFAULT_CONSUMER_GROUP")
+        .unwrap();
+    let count: i64 = source
+        .query_scalar("select count(*) from test_table")
+        .unwrap();
+    assert_eq!(5, count);
+}
+
+#[test]
+#[ignore]
+fn test_timestamp_nanos() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let source = OracleSource::new(&dburl, 1).unwrap();
+
+    // beyond the range of i64 nanoseconds, which ends in 2262
+    let nanos: i128 = source
+        .query_scalar("select timestamp '2300-01-01 00:00:00.123456789' from dual")
+        .unwrap();
+    assert_eq!(10_413_792_000_123_456_789, nanos);
+
+    let nanos: i128 = source
+        .query_scalar(
+            "select to_timestamp_tz('1970-01-01 01:00:00.5 +01:00', \
+             'YYYY-MM-DD HH24:MI:SS.FF TZH:TZM') from dual",
+        )
+        .unwrap();
+    assert_eq!(500_000_000, nanos);
+
+    let nanos: Option<i128> = source
+        .query_scalar("select cast(null as timestamp) from dual")
+        .unwrap();
+    assert_eq!(None, nanos);
+}
+
+#[test]
+#[ignore]
+fn test_flashback() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let source = OracleSource::new(&dburl, 1).unwrap();
+    let scn: i64 = source
+        .query_scalar("select timestamp_to_scn(systimestamp) from dual")
+        .unwrap();
+
+    let count = |mut source: OracleSource, query: &str| -> i64 {
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let mut parser = partition.parser().unwrap();
+        parser.fetch_next().unwrap();
+        parser.produce().unwrap()
+    };
+    let query = "with t as (select * from test_table) \
+                 select count(*) from t join (select test_int from test_table) s \
+                 on t.test_int = s.test_int";
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.as_of_scn(scn as u64);
+    assert_eq!(5, count(source, query));
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.as_of_timestamp(chrono::Utc::now() - chrono::Duration::seconds(5));
+    assert_eq!(5, count(source, query));
+}
+
+#[test]
+#[ignore]
+fn test_restore_point() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    match source.as_of_restore_point("cx_no_such_restore_point") {
+        Err(OracleSourceError::UnknownRestorePoint(name)) => {
+            assert_eq!("cx_no_such_restore_point", name)
+        }
+        r => panic!("expected an unknown restore point error, got {:?}", r),
+    }
+
+    let conn = connect_oracle(&Url::parse(&dburl).unwrap())
+        .unwrap()
+        .connect()
+        .unwrap();
+    let _ = conn.execute("DROP TABLE test_restore_point", &[]);
+    let _ = conn.execute("DROP RESTORE POINT cx_test_restore_point", &[]);
+    conn.execute("CREATE TABLE test_restore_point(id NUMBER(5))", &[])
+        .unwrap();
+    conn.execute("INSERT INTO test_restore_point VALUES (1)", &[])
+        .unwrap();
+    conn.commit().unwrap();
+    conn.execute("CREATE RESTORE POINT cx_test_restore_point", &[])
+        .unwrap();
+    conn.execute("INSERT INTO test_restore_point VALUES (2)", &[])
+        .unwrap();
+    conn.commit().unwrap();
+
+    // the rows inserted after the restore point are not read
+    source.as_of_restore_point("cx_test_restore_point").unwrap();
+    source.set_queries(&[CXQuery::naked("select count(*) from test_restore_point")]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut parser = partitions[0].parser().unwrap();
+    parser.fetch_next().unwrap();
+    let count: i64 = parser.produce().unwrap();
+    assert_eq!(1, count);
+
+    // the count of the result reads the same snapshot
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.as_of_restore_point("cx_test_restore_point").unwrap();
+    source.set_queries(&[CXQuery::naked("select id from test_restore_point")]);
+    source.set_origin_query(Some("select id from test_restore_point".to_string()));
+    source.fetch_metadata().unwrap();
+    assert_eq!(Some(1), source.result_rows().unwrap());
+
+    conn.execute("DROP RESTORE POINT cx_test_restore_point", &[])
+        .unwrap();
+    conn.execute("DROP TABLE test_restore_point", &[]).unwrap();
+}
+
+#[test]
+#[ignore]
+fn test_dates_as_iso8601() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let query = "select timestamp '2020-01-02 03:04:05.123456' ts, \
+                 to_timestamp_tz('2020-01-02 04:04:05.123456 +01:00', \
+                                 'YYYY-MM-DD HH24:MI:SS.FF TZH:TZM') tstz, \
+                 date '2020-01-02' d \
+                 from dual";
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.dates_as_iso8601(Some(Iso8601Format::default()));
+    source.set_queries(&[CXQuery::naked(query)]);
+    source.fetch_metadata().unwrap();
+    assert!(source
+        .schema()
+        .iter()
+        .all(|ty| matches!(ty, OracleTypeSystem::VarChar(_))));
+    let mut partitions = source.partition().unwrap();
+    let mut parser = partitions[0].parser().unwrap();
+    parser.fetch_next().unwrap();
+    let values: Vec<String> = (0..3).map(|_| parser.produce().unwrap()).collect();
+    assert_eq!(
+        vec![
+            "2020-01-02T03:04:05.123456Z",
+            "2020-01-02T03:04:05.123456Z",
+            "2020-01-02T00:00:00.000000Z",
+        ],
+        values
+    );
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.dates_as_iso8601(Some(Iso8601Format {
+        precision: 3,
+        suffix: TimeZoneSuffix::Offset,
+    }));
+    let ts: String = source
+        .query_scalar("select timestamp '2020-01-02 03:04:05.123456' from dual")
+        .unwrap();
+    assert_eq!("2020-01-02T03:04:05.123+00:00", ts);
+}
+
+#[test]
+#[ignore]
+fn test_init_sql_file() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let path = env::temp_dir().join("connectorx_test_init_sql_file.sql");
+    std::fs::write(
+        &path,
+        "-- session setup\n\
+         ALTER SESSION SET NLS_DATE_FORMAT = 'YYYY/MM/DD';\n\
+         ALTER SESSION SET TIME_ZONE = '+03:00';\n",
+    )
+    .unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.init_sql_file(&path).unwrap();
+    let date_format: String = source
+        .query_scalar("select sys_context('USERENV', 'NLS_DATE_FORMAT') from dual")
+        .unwrap();
+    assert_eq!("YYYY/MM/DD", date_format);
+    let time_zone: String = source
+        .query_scalar("select sessiontimezone from dual")
+        .unwrap();
+    assert_eq!("+03:00", time_zone);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[ignore]
+fn test_parallel_degree() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    for query in [
+        "select test_int from test_table",
+        "select /*+ FULL(test_table) */ test_int from test_table",
+        "with t as (select test_int from test_table) select test_int from t",
+    ] {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.with_parallel_degree(2);
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        assert_eq!(5, n);
+    }
+}
+
+#[test]
+#[ignore]
+fn test_parallel_order_by() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.with_parallel_degree(4);
+    source.parallel_order_by(&["test_int desc"]);
+    source.set_queries(&[CXQuery::naked("select test_int, test_char from test_table")]);
+    source.fetch_metadata().unwrap();
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    partition.result_rows().unwrap();
+    assert_eq!(5, partition.nrows());
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    let ints: Vec<i64> = (0..n)
+        .map(|_| {
+            let v = parser.produce().unwrap();
+            let _: Option<String> = parser.produce().unwrap();
+            v
+        })
+        .collect();
+    assert_eq!(vec![2333, 5, 4, 2, 1], ints);
+}
+
+#[test]
+#[ignore]
+fn test_strict() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    let produce = |strict: bool| -> Result<Decimal, OracleSourceError> {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.decimal_scale(Some(2));
+        source.strict(strict);
+        source.set_queries(&[CXQuery::naked("select 2.345 as v from dual")]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        let mut parser = partition.parser().unwrap();
+        parser.fetch_next().unwrap();
+        parser.produce()
+    };
+
+    assert_eq!(Decimal::new(234, 2), produce(false).unwrap());
+    assert!(matches!(
+        produce(true),
+        Err(OracleSourceError::DecimalTruncation(value, 2)) if value == Decimal::new(2345, 3)
+    ));
+
+    // values that fit the scale pass in strict mode
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.decimal_scale(Some(2));
+    source.strict(true);
+    let value: Decimal = source.query_scalar("select 2.3 from dual").unwrap();
+    assert_eq!(Decimal::new(23, 1), value);
+}
+
+#[test]
+#[ignore]
+fn test_include_rowscn() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.include_rowscn(true);
+    source.set_queries(&[CXQuery::naked(
+        "select * from (select * from test_table t where t.test_int < 3) part",
+    )]);
+    source.fetch_metadata().unwrap();
+    assert_eq!(
+        vec!["TEST_INT", "TEST_CHAR", "TEST_FLOAT", "ORA_ROWSCN"],
+        source.names()
+    );
+    assert!(matches!(
+        source.schema()[3],
+        OracleTypeSystem::NumInt(false)
+    ));
+
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(2, n);
+    let _: i64 = parser.produce().unwrap();
+    let _: Option<String> = parser.produce().unwrap();
+    let _: Option<f64> = parser.produce().unwrap();
+    let scn: u64 = parser.produce().unwrap();
+    assert!(scn > 0);
+}
+
+#[test]
+#[ignore]
+fn test_set_operation_metadata() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+
+    for query in [
+        "select test_int, test_char from test_table union all select 1, 'x' from dual",
+        "select test_int from test_table minus select 1 from dual order by 1",
+    ] {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.set_queries(&[CXQuery::naked(query)]);
+        source.fetch_metadata().unwrap();
+        assert_eq!("TEST_INT", source.names()[0]);
+        assert!(matches!(source.schema()[0], OracleTypeSystem::NumInt(_)));
+    }
+}
+
+#[test]
+fn test_value_truncated() {
+    let names = vec!["ID".to_string(), "NAME".to_string(), "NOTE".to_string()];
+    let schema = vec![
+        OracleTypeSystem::NumInt(true),
+        OracleTypeSystem::VarChar(true),
+        OracleTypeSystem::NVarChar(true),
+    ];
+    let truncated = OciError::OciError(DbError::new(
+        1406,
+        0,
+        "ORA-01406: fetched column value was truncated".to_string(),
+        "dpiStmt_fetch".to_string(),
+        "fetch".to_string(),
+    ));
+    match fetch_error(truncated, &names, &schema) {
+        OracleSourceError::ValueTruncated(columns) => assert_eq!("NAME, NOTE", columns),
+        e => panic!("unexpected error {}", e),
+    }
+
+    let other = OciError::OciError(DbError::new(
+        942,
+        0,
+        "ORA-00942: table or view does not exist".to_string(),
+        "dpiStmt_execute".to_string(),
+        "execute".to_string(),
+    ));
+    assert!(matches!(
+        fetch_error(other, &names, &schema),
+        OracleSourceError::OracleError(_)
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_optimizer_stats() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let conn = connect_oracle(&Url::parse(&dburl).unwrap())
+        .unwrap()
+        .connect()
+        .unwrap();
+    conn.execute(
+        "BEGIN DBMS_STATS.GATHER_TABLE_STATS(USER, 'TEST_TABLE'); END;",
+        &[],
+    )
+    .unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select * from test_table")]);
+    let stats = source.optimizer_stats("test_int").unwrap();
+    assert!(stats.low.is_some());
+    assert!(stats.high.is_some());
+    assert_eq!(Some(5), stats.num_distinct);
+    assert_eq!(Some((1., 2333.)), stats.numeric_range());
+
+    assert!(source.optimizer_stats("missing").is_err());
+    source.set_queries(&[CXQuery::naked("select 1 from dual, test_table")]);
+    assert!(source.optimizer_stats("test_int").is_err());
+}
+
+#[test]
+#[ignore]
+fn test_test_connection() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    OracleSource::test_connection(&dburl).unwrap();
+
+    let mut url = Url::parse(&dburl).unwrap();
+    url.set_password(Some("wrong password")).unwrap();
+    assert!(matches!(
+        OracleSource::test_connection(url.as_str()),
+        Err(OracleSourceError::AuthenticationFailed(_))
+    ));
+
+    let mut url = Url::parse(&dburl).unwrap();
+    url.set_port(Some(1)).unwrap();
+    assert!(matches!(
+        OracleSource::test_connection(url.as_str()),
+        Err(OracleSourceError::NoListener(_))
+    ));
+}
+
+#[test]
+#[ignore]
+fn test_contains_score() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let conn = connect_oracle(&Url::parse(&dburl).unwrap())
+        .unwrap()
+        .connect()
+        .unwrap();
+    let _ = conn.execute("DROP TABLE test_contains", &[]);
+    conn.execute(
+        "CREATE TABLE test_contains(id NUMBER(5), doc VARCHAR2(100))",
+        &[],
+    )
+    .unwrap();
+    for (id, doc) in [(1, "oracle text"), (2, "postgres"), (3, "oracle oracle")] {
+        conn.execute("INSERT INTO test_contains VALUES (:1, :2)", &[&id, &doc])
+            .unwrap();
+    }
+    conn.commit().unwrap();
+    conn.execute(
+        "CREATE INDEX test_contains_idx ON test_contains(doc) INDEXTYPE IS CTXSYS.CONTEXT",
+        &[],
+    )
+    .unwrap();
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select id, score(1), score(1) as relevance from test_contains \
+         where contains(doc, 'oracle', 1) > 0 order by id",
+    )]);
+    source.fetch_metadata().unwrap();
+    assert!(matches!(
+        source.schema()[1],
+        OracleTypeSystem::NumFloat(true)
+    ));
+    assert!(matches!(
+        source.schema()[2],
+        OracleTypeSystem::NumFloat(true)
+    ));
+    // the count query keeps CONTAINS and SCORE in the same query block
+    assert_eq!(Some(2), source.result_rows().unwrap());
+
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    partition.result_rows().unwrap();
+    assert_eq!(2, partition.nrows());
+    let mut parser = partition.parser().unwrap();
+    let (n, _) = parser.fetch_next().unwrap();
+    assert_eq!(2, n);
+    for id in [1, 3] {
+        let got: i64 = parser.produce().unwrap();
+        assert_eq!(id, got);
+        let score: f64 = parser.produce().unwrap();
+        let relevance: f64 = parser.produce().unwrap();
+        assert!(score > 0.);
+        assert_eq!(score, relevance);
+    }
+    conn.execute("DROP TABLE test_contains", &[]).unwrap();
+}
+
+#[test]
+#[ignore]
+fn test_partition_order_key() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let run = || -> Vec<i64> {
+        let mut source = OracleSource::new(&dburl, 1).unwrap();
+        source.with_parallel_degree(4);
+        source.partition_order_key(&["test_int"]);
+        source.set_queries(&[CXQuery::naked("select test_int from test_table")]);
+        source.fetch_metadata().unwrap();
+        let mut partitions = source.partition().unwrap();
+        let mut partition = partitions.remove(0);
+        partition.result_rows().unwrap();
+        assert_eq!(5, partition.nrows());
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        (0..n).map(|_| parser.produce().unwrap()).collect()
+    };
+    let first = run();
+    assert_eq!(vec![1, 2, 4, 5, 2333], first);
+    assert_eq!(first, run());
+}
+
+#[test]
+#[ignore]
+fn test_partition_bounds() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.set_queries(&[CXQuery::naked("select test_int from test_table")]);
+    source.set_partition_bounds(
+        "test_int",
+        vec![
+            PartitionBound::half_open(1, 4),
+            PartitionBound {
+                lower: Bound::Included(4),
+                upper: Bound::Included(5),
+                nulls: false,
+            },
+            PartitionBound {
+                lower: Bound::Excluded(5),
+                upper: Bound::Unbounded,
+                nulls: true,
+            },
+        ],
+    );
+    source.fetch_metadata().unwrap();
+    let partitions = source.partition().unwrap();
+    let rows: Vec<Vec<i64>> = partitions
+        .into_iter()
+        .map(|mut partition| {
+            partition.result_rows().unwrap();
+            let mut parser = partition.parser().unwrap();
+            let (n, _) = parser.fetch_next().unwrap();
+            let mut values: Vec<i64> = (0..n).map(|_| parser.produce().unwrap()).collect();
+            values.sort_unstable();
+            values
+        })
+        .collect();
+    assert_eq!(vec![vec![1, 2], vec![4, 5], vec![2333]], rows);
+}
+
+#[test]
+#[ignore]
+fn test_partition_bounds_dispatch() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let queries = [CXQuery::naked("select test_int from test_table")];
+    let mut source = OracleSource::new(&dburl, 2).unwrap();
+    source.set_partition_bounds(
+        "test_int",
+        vec![
+            PartitionBound::half_open(1, 4),
+            PartitionBound::half_open(4, 6),
+            PartitionBound {
+                lower: Bound::Included(6),
+                upper: Bound::Unbounded,
+                nulls: true,
+            },
+        ],
+    );
+    let mut destination = ArrowDestination::new();
+    let dispatcher =
+        Dispatcher::<_, _, OracleArrowTransport>::new(source, &mut destination, &queries, None);
+    dispatcher.run().unwrap();
+    let mut ids: Vec<i64> = destination
+        .arrow()
+        .unwrap()
+        .iter()
+        .flat_map(|rb| {
+            rb.column(0)
+                .as_any()
+                .downcast_ref::<arrow::array::Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        })
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(vec![1, 2, 4, 5, 2333], ids);
+}
+
+#[test]
+#[ignore]
+fn test_partition_null_keys() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    // the keys 1 to 11 without 4 and 8, and 3 NULL keys
+    let query = "select case when mod(level, 4) = 0 then null else level end as k \
+                 from dual connect by level <= 12";
+    let ranges = [(1, 5), (5, 9), (9, 12)];
+    let run = || -> Vec<Vec<Option<i64>>> {
+        ranges
+            .iter()
+            .enumerate()
+            .map(|(i, &(lower, upper))| {
+                // sorted first, the NULL keys are read by the first partition
+                let nulls = i == null_partition(Some(NullsOrder::First), ranges.len());
+                let part = single_col_partition_query_oracle(query, "k", lower, upper, nulls);
+                let mut source = OracleSource::new(&dburl, 1).unwrap();
+                source.partition_order_key(&["k"]);
+                source.partition_nulls(Some(NullsOrder::First));
+                source.set_queries(&[CXQuery::Wrapped(part)]);
+                source.fetch_metadata().unwrap();
+                let mut partitions = source.partition().unwrap();
+                let mut partition = partitions.remove(0);
+                partition.result_rows().unwrap();
+                let mut parser = partition.parser().unwrap();
+                let (n, _) = parser.fetch_next().unwrap();
+                (0..n).map(|_| parser.produce().unwrap()).collect()
+            })
+            .collect()
+    };
+    let first = run();
+    assert_eq!(
+        vec![
+            vec![None, None, None, Some(1), Some(2), Some(3)],
+            vec![Some(5), Some(6), Some(7)],
+            vec![Some(9), Some(10), Some(11)],
+        ],
+        first
+    );
+    assert_eq!(first, run());
+}
+
+#[test]
+#[ignore]
+fn test_table_scan() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let source = OracleSource::new(&dburl, 1).unwrap();
+    let batch = source
+        .table_scan::<OracleArrowTransport>("test_table", &["test_int", "TEST_CHAR"], Some(2))
+        .unwrap();
+    assert_eq!(2, batch.num_rows());
+    assert_eq!(2, batch.num_columns());
+
+    let source = OracleSource::new(&dburl, 1).unwrap();
+    let batch = source
+        .table_scan::<OracleArrowTransport>("test_table", &[], None)
+        .unwrap();
+    assert_eq!(5, batch.num_rows());
+    assert_eq!(3, batch.num_columns());
+
+    let source = OracleSource::new(&dburl, 1).unwrap();
+    assert!(source
+        .table_scan::<OracleArrowTransport>("test_table", &["test_nope"], None)
+        .is_err());
+}
+
+#[test]
+#[ignore]
+fn test_session_time_zone() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    let read = |source: &mut OracleSource| -> (String, Vec<Option<DateTime<Utc>>>) {
+        let zone = source
+            .query_scalar("select sessiontimezone from dual")
+            .unwrap();
+        source.set_queries(&[CXQuery::naked(
+            "select ts from test_timestamp_ltz order by id",
+        )]);
+        source.fetch_metadata().unwrap();
+        let mut partition = source.partition().unwrap().remove(0);
+        partition.result_rows().unwrap();
+        let mut parser = partition.parser().unwrap();
+        let (n, _) = parser.fetch_next().unwrap();
+        (zone, (0..n).map(|_| parser.produce().unwrap()).collect())
+    };
+    let expected = vec![Some(Utc.ymd(2021, 6, 1).and_hms(10, 0, 0)), None];
+
+    // the zone of the client environment by default
+    let (client_zone, values) = read(&mut source);
+    assert_eq!(expected, values);
+    // the values are fetched as 15:30 in the session zone, the same instant
+    source.set_session_time_zone(Some("+05:30")).unwrap();
+    assert_eq!(("+05:30".to_string(), expected.clone()), read(&mut source));
+    // and the idle sessions are back to the client zone when it is cleared
+    source.set_session_time_zone(None).unwrap();
+    assert_eq!((client_zone, expected), read(&mut source));
+}
+
+#[test]
+#[ignore]
+fn test_count() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.skip_count(Some(true));
+    assert_eq!(5, source.count("select * from test_table").unwrap());
+    assert_eq!(
+        2,
+        source
+            .count("select * from test_table order by test_int fetch first 2 rows only")
+            .unwrap()
+    );
+    assert_eq!(
+        1,
+        source
+            .count("select * from test_table where test_int > 1000 fetch first 10 rows only")
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_row_errors_take() {
+    let errors = RowErrors::new();
+    let shared = errors.clone();
+    for (partition, row) in [(1, 0), (0, 7), (0, 2)] {
+        shared.push(RowError {
+            partition,
+            row,
+            column: "C".to_string(),
+            message: String::new(),
+        });
+    }
+    assert_eq!(3, errors.len());
+    let taken: Vec<_> = errors.take().iter().map(|e| (e.partition, e.row)).collect();
+    assert_eq!(vec![(0, 2), (0, 7), (1, 0)], taken);
+    assert!(shared.is_empty());
+}
+
+#[test]
+#[ignore]
+fn test_on_row_error() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let queries = [CXQuery::naked(
+        "select id, amount from test_dirty order by id",
+    )];
+
+    let source = OracleSource::new(&dburl, 1).unwrap();
+    let mut destination = ArrowDestination::new();
+    let dispatcher =
+        Dispatcher::<_, _, OracleArrowTransport>::new(source, &mut destination, &queries, None);
+    assert!(dispatcher.run().is_err());
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.on_row_error(RowErrorPolicy::SkipAndCollect);
+    let errors = source.row_errors();
+    let mut destination = ArrowDestination::new();
+    let dispatcher =
+        Dispatcher::<_, _, OracleArrowTransport>::new(source, &mut destination, &queries, None);
+    dispatcher.run().unwrap();
+    let batches = destination.arrow().unwrap();
+    let ids: Vec<i64> = batches
+        .iter()
+        .flat_map(|rb| {
+            rb.column(0)
+                .as_any()
+                .downcast_ref::<arrow::array::Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        })
+        .collect();
+    assert_eq!(vec![1, 3], ids);
+
+    let errors = errors.take();
+    assert_eq!(1, errors.len());
+    assert_eq!((0, 1), (errors[0].partition, errors[0].row));
+    assert_eq!("AMOUNT", errors[0].column);
+
+    // the rows are checked with the types the columns are produced as, here the dates as text
+    let queries = [CXQuery::naked(
+        "select id, sysdate as loaded, amount from test_dirty order by id",
+    )];
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.on_row_error(RowErrorPolicy::SkipAndCollect);
+    source.dates_as_iso8601(Some(Iso8601Format::default()));
+    let errors = source.row_errors();
+    let mut destination = ArrowDestination::new();
+    let dispatcher =
+        Dispatcher::<_, _, OracleArrowTransport>::new(source, &mut destination, &queries, None);
+    dispatcher.run().unwrap();
+    let nrows: usize = destination
+        .arrow()
+        .unwrap()
+        .iter()
+        .map(|rb| rb.num_rows())
+        .sum();
+    assert_eq!(2, nrows);
+    let errors = errors.take();
+    assert_eq!(1, errors.len());
+    assert_eq!(("AMOUNT", 1), (errors[0].column.as_str(), errors[0].row));
+}
+
+#[test]
+#[ignore]
+fn test_type_handler() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dburl = env::var("ORACLE_URL").unwrap();
+    let read = |source: &mut OracleSource| -> Result<Vec<Option<String>>, OracleSourceError> {
+        source.set_queries(&[CXQuery::naked(
+            "select address from test_address order by id",
+        )]);
+        source.fetch_metadata()?;
+        assert!(matches!(source.schema()[0], OracleTypeSystem::VarChar(_)));
+        let mut partition = source.partition()?.remove(0);
+        partition.result_rows()?;
+        let mut parser = partition.parser()?;
+        let (n, _) = parser.fetch_next()?;
+        (0..n).map(|_| parser.produce()).collect()
+    };
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    assert!(read(&mut source).is_err());
+
+    let mut source = OracleSource::new(&dburl, 1).unwrap();
+    source.register_type_handler(
+        "cx_address_type",
+        Box::new(|value: &SqlValue| -> Result<String, OracleSourceError> {
+            let address: Object = value.get()?;
+            Ok(format!(
+                "{}, {}",
+                address.get::<String>("STREET")?,
+                address.get::<String>("CITY")?
+            ))
+        }),
+    );
+    assert_eq!(
+        vec![Some("1 Main St, Springfield".to_string()), None],
+        read(&mut source).unwrap()
+    );
+}
@@ -1,17 +1,29 @@
 use arrow::{
     array::{BooleanArray, Float64Array, Int64Array, StringArray},
+    datatypes::{DataType, Field, Schema},
     record_batch::RecordBatch,
 };
+use chrono::NaiveDate;
 use connectorx::{
     destinations::arrow::ArrowDestination,
     prelude::*,
-    sources::postgres::{rewrite_tls_args, BinaryProtocol, CSVProtocol, PostgresSource},
-    sources::PartitionParser,
+    sources::incremental::{IncrementalSource, Watermark},
+    sources::postgres::{
+        get_replication_stream, rewrite_tls_args, BinaryProtocol, CSVProtocol,
+        PostgresReplicationSource, PostgresSource, PostgresTypeSystem,
+    },
+    sources::writable::{MergeStats, WritableSource},
+    sources::{HealthStatus, PartitionParser},
     sql::CXQuery,
     transports::PostgresArrowTransport,
 };
+use futures::{executor::block_on, StreamExt};
 use postgres::NoTls;
+use sqlparser::dialect::{MsSqlDialect, MySqlDialect, PostgreSqlDialect};
+use std::convert::TryFrom;
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 
 #[test]
@@ -149,6 +161,35 @@ fn test_postgres() {
     verify_arrow_results(result);
 }
 
+#[test]
+fn test_postgres_fault_tolerant() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+
+    let queries = [
+        CXQuery::naked("select * from test_table where test_int < 2"),
+        CXQuery::naked("select * from test_table where no_such_column >= 2"),
+    ];
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 2).unwrap();
+    let mut destination = ArrowDestination::new();
+    let dispatcher =
+        FaultTolerantDispatcher::<_, _, PostgresArrowTransport<BinaryProtocol, NoTls>>::new(
+            builder,
+            &mut destination,
+            &queries,
+            None,
+        );
+
+    let result = dispatcher.fetch().expect("fetch partial result");
+    assert_eq!(1, result.failed_partitions.len());
+    assert_eq!(1, result.failed_partitions[0].0);
+    let nrows: usize = result.batches.iter().map(|rb| rb.num_rows()).sum();
+    assert_eq!(2, nrows);
+}
+
 #[test]
 fn test_postgres_csv() {
     let _ = env_logger::builder().is_test(true).try_init();
@@ -221,6 +262,227 @@ fn test_postgres_agg() {
         ])));
 }
 
+#[test]
+fn test_postgres_incremental() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let state_file = env::temp_dir().join("connectorx_test_postgres_incremental");
+    let _ = std::fs::remove_file(&state_file);
+    let queries = [CXQuery::naked("select test_int from test_table")];
+
+    let fetch = || {
+        let (config, _tls) = rewrite_tls_args(&url).unwrap();
+        let builder = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+        let source = IncrementalSource::new(builder, "test_int", &state_file).unwrap();
+        let mut destination = ArrowDestination::new();
+        let (mut result, commit) = source
+            .fetch::<PostgresArrowTransport<BinaryProtocol, NoTls>, _, _, _>(
+                &mut destination,
+                &queries,
+                &PostgreSqlDialect {},
+            )
+            .expect("run dispatcher");
+        let values: Vec<Option<i64>> = match result.pop() {
+            Some(rb) => rb
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .iter()
+                .collect(),
+            None => vec![],
+        };
+        assert!(result.is_empty());
+        (commit, values)
+    };
+
+    let (commit, values) = fetch();
+    assert_eq!(
+        vec![Some(0), Some(1), Some(2), Some(3), Some(4), Some(1314)],
+        values
+    );
+    assert_eq!(Some(&Watermark::Int(1314)), commit.watermark());
+    commit.commit().unwrap();
+
+    let (commit, values) = fetch();
+    assert!(values.is_empty());
+    assert_eq!(Some(&Watermark::Int(1314)), commit.watermark());
+    std::fs::remove_file(&state_file).unwrap();
+}
+
+#[test]
+fn test_watermark_literal() {
+    let ts = NaiveDate::from_ymd(2020, 1, 2).and_hms_micro(3, 4, 5, 6);
+    assert_eq!("42", Watermark::Int(42).to_sql(&PostgreSqlDialect {}));
+    assert_eq!(
+        "'it''s'",
+        Watermark::Str("it's".to_string()).to_sql(&PostgreSqlDialect {})
+    );
+    assert_eq!(
+        "'a\\\\b'",
+        Watermark::Str("a\\b".to_string()).to_sql(&MySqlDialect {})
+    );
+    assert_eq!(
+        "TIMESTAMP '2020-01-02 03:04:05.000006'",
+        Watermark::DateTime(ts).to_sql(&PostgreSqlDialect {})
+    );
+    assert_eq!(
+        "'2020-01-02 03:04:05.000006'",
+        Watermark::DateTime(ts).to_sql(&MsSqlDialect {})
+    );
+    assert_eq!(
+        "DATE '2020-01-02'",
+        Watermark::Date(ts.date()).to_sql(&PostgreSqlDialect {})
+    );
+}
+
+#[test]
+fn test_postgres_merge() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut client = config.connect(NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS test_merge;
+             CREATE TABLE test_merge(id BIGINT PRIMARY KEY, name TEXT);
+             INSERT INTO test_merge VALUES (1, 'a'), (2, 'b');",
+        )
+        .unwrap();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int64Array::from(vec![2, 3])),
+            Arc::new(StringArray::from(vec![Some("b'2"), None])),
+        ],
+    )
+    .unwrap();
+
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    let stats = source.merge("test_merge", &["id"], &batch).unwrap();
+    assert_eq!(
+        MergeStats {
+            inserted: 1,
+            updated: 1
+        },
+        stats
+    );
+
+    let rows: Vec<(i64, Option<String>)> = client
+        .query("SELECT id, name FROM test_merge ORDER BY id", &[])
+        .unwrap()
+        .iter()
+        .map(|r| (r.get(0), r.get(1)))
+        .collect();
+    assert_eq!(
+        vec![
+            (1, Some("a".to_string())),
+            (2, Some("b'2".to_string())),
+            (3, None)
+        ],
+        rows
+    );
+    client.batch_execute("DROP TABLE test_merge").unwrap();
+}
+
+#[test]
+#[ignore]
+fn test_postgres_replication() {
+    // requires a server with `wal_level = logical`
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut client = config.connect(NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS test_replication;
+             CREATE TABLE test_replication(id BIGINT PRIMARY KEY, name TEXT);
+             DROP PUBLICATION IF EXISTS test_pub;
+             CREATE PUBLICATION test_pub FOR TABLE test_replication;",
+        )
+        .unwrap();
+    // a slot cannot be created in a transaction that has written
+    client
+        .execute(
+            "SELECT pg_create_logical_replication_slot('test_slot', 'pgoutput')",
+            &[],
+        )
+        .unwrap();
+    client
+        .batch_execute(
+            "INSERT INTO test_replication VALUES (1, 'a'), (2, 'b');
+             UPDATE test_replication SET name = 'c' WHERE id = 2;
+             DELETE FROM test_replication WHERE id = 1;",
+        )
+        .unwrap();
+
+    let batches: Vec<RecordBatch> = get_replication_stream(&config, NoTls, "test_slot", "test_pub")
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let mut stream = PostgresReplicationSource::new(&config, NoTls, "test_slot", "test_pub")
+        .unwrap()
+        .into_stream(Duration::from_millis(100));
+    client
+        .execute("INSERT INTO test_replication VALUES (3, 'd')", &[])
+        .unwrap();
+    let streamed = block_on(stream.next()).unwrap().unwrap();
+    assert_eq!(1, streamed.num_rows());
+    drop(stream);
+    // let the background thread notice the dropped stream before the slot goes away
+    std::thread::sleep(Duration::from_millis(500));
+
+    client
+        .batch_execute(
+            "SELECT pg_drop_replication_slot('test_slot');
+             DROP PUBLICATION test_pub;
+             DROP TABLE test_replication;",
+        )
+        .unwrap();
+
+    let rb = RecordBatch::concat(&batches[0].schema(), &batches).unwrap();
+    assert_eq!(
+        "public.test_replication",
+        rb.schema().metadata()["table"].as_str()
+    );
+    let ops: Vec<Option<&str>> = rb
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap()
+        .iter()
+        .collect();
+    assert_eq!(
+        vec![
+            Some("INSERT"),
+            Some("INSERT"),
+            Some("UPDATE"),
+            Some("DELETE")
+        ],
+        ops
+    );
+    let ids: Vec<Option<i64>> = rb
+        .column(3)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap()
+        .iter()
+        .collect();
+    assert_eq!(vec![Some(1), Some(2), Some(2), Some(1)], ids);
+}
+
 pub fn verify_arrow_results(result: Vec<RecordBatch>) {
     assert!(result.len() == 2);
 
@@ -304,3 +566,134 @@ pub fn verify_arrow_results(result: Vec<RecordBatch>) {
         }
     }
 }
+
+#[test]
+fn test_postgres_health_check() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+
+    match source.health_check(Duration::from_secs(10)).unwrap() {
+        HealthStatus::Ok { .. } => {}
+        status => panic!("unexpected health status {:?}", status),
+    }
+    assert_eq!(
+        HealthStatus::Timeout,
+        source.health_check(Duration::from_nanos(0)).unwrap()
+    );
+    // the timed out probe left nothing running on the only connection of the pool
+    assert!(matches!(
+        source.health_check(Duration::from_secs(10)).unwrap(),
+        HealthStatus::Ok { .. }
+    ));
+}
+
+#[test]
+fn test_postgres_column_stats() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut client = config.connect(NoTls).unwrap();
+    client.batch_execute("ANALYZE test_table").unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    source.set_queries(&[CXQuery::naked(
+        "select test_int, test_str, test_int + 1 as next_int from test_table",
+    )]);
+    source.fetch_metadata().unwrap();
+
+    let stats = source.column_stats().unwrap();
+    assert_eq!(6, stats.rows);
+    assert_eq!(Some("0".to_string()), stats.columns[0].min);
+    assert_eq!(Some("1314".to_string()), stats.columns[0].max);
+    assert_eq!(Some(1.), stats.distinct_ratio("test_int"));
+    assert!((stats.distinct_ratio("test_str").unwrap() - 5. / 6.).abs() < 1e-6);
+    assert_eq!(Some(4.), stats.columns[0].avg_bytes);
+    // an expression has no statistics
+    assert_eq!(None, stats.distinct_ratio("next_int"));
+    assert_eq!(None, stats.columns[2].avg_bytes);
+}
+
+#[test]
+fn test_postgres_query_label() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    // the comment delimiters are removed from the label
+    source.with_query_label("nightly */ drop table test_table; /*");
+    source.set_queries(&[CXQuery::naked("select current_query() as q")]);
+    source.fetch_metadata().unwrap();
+
+    let mut partitions = source.partition().unwrap();
+    let mut partition = partitions.remove(0);
+    let mut parser = partition.parser().unwrap();
+    parser.fetch_next().unwrap();
+    let query = Produce::<&str>::produce(&mut parser).unwrap();
+    assert!(query.starts_with("/* connectorx: nightly  drop table test_table;  */ "));
+}
+
+#[test]
+fn test_type_display() {
+    assert_eq!("INT8", PostgresTypeSystem::Int8(false).to_string());
+    assert_eq!("TEXT(nullable)", PostgresTypeSystem::Text(true).to_string());
+    assert_eq!(
+        "INT4[](nullable)",
+        PostgresTypeSystem::Int4Array(true).to_string()
+    );
+    for name in [
+        "INT8",
+        "TEXT(nullable)",
+        "INT4[](nullable)",
+        "\"CHAR\"",
+        "TIMESTAMPTZ",
+    ] {
+        assert_eq!(
+            name,
+            PostgresTypeSystem::try_from(name).unwrap().to_string()
+        );
+    }
+    assert!(matches!(
+        PostgresTypeSystem::try_from("numeric(10,2)[]"),
+        Ok(PostgresTypeSystem::NumericArray(false))
+    ));
+    assert!(matches!(
+        PostgresTypeSystem::try_from("timestamp(3) with time zone"),
+        Ok(PostgresTypeSystem::TimestampTz(false))
+    ));
+}
+
+#[test]
+fn test_postgres_count() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dburl = env::var("POSTGRES_URL").unwrap();
+    let url = Url::parse(dburl.as_str()).unwrap();
+    let (config, _tls) = rewrite_tls_args(&url).unwrap();
+    let mut source = PostgresSource::<BinaryProtocol, NoTls>::new(config, NoTls, 1).unwrap();
+    assert_eq!(6, source.count("select * from test_table").unwrap());
+    assert_eq!(
+        2,
+        source
+            .count("select * from test_table where test_int > 1 order by test_int limit 2")
+            .unwrap()
+    );
+    // the limit bounds the count, it is not the count
+    assert_eq!(
+        1,
+        source
+            .count("select * from test_table where test_int > 1000 limit 10")
+            .unwrap()
+    );
+    // counting leaves the origin query of the load as it is
+    let origin = "select * from test_table where test_int < 3".to_string();
+    source.set_origin_query(Some(origin.clone()));
+    assert_eq!(6, source.count("select * from test_table").unwrap());
+    assert_eq!(Some(origin), source.origin_query());
+}
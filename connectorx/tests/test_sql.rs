@@ -0,0 +1,511 @@
+use arrow::array::{Float64Array, Int64Array, StringArray, TimestampMicrosecondArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use connectorx::sources::oracle::OracleDialect;
+use connectorx::sources::writable::{
+    chunks, matched_count_query, merge_query_oracle, upsert_query_mysql, upsert_query_postgres,
+    MERGE_CHUNK_ROWS,
+};
+use connectorx::sql::{
+    bounds_partition_query_oracle, count_query, count_query_oracle, dblink_query_oracle,
+    limit1_query, limit1_query_oracle, null_partition, single_col_partition_query,
+    single_col_partition_query_oracle, AliasGenerator, CXQuery, NullsOrder, PartitionBound,
+};
+use proptest::prelude::*;
+use proptest::sample::select;
+use sqlparser::ast::{Expr, Query, SetExpr, Statement, TableFactor, Value};
+use sqlparser::dialect::{Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect};
+use sqlparser::parser::Parser;
+use std::collections::HashSet;
+use std::ops::Bound;
+use std::sync::Arc;
+
+fn arb_column() -> impl Strategy<Value = String> {
+    select(vec!["a", "b", "c"]).prop_map(String::from)
+}
+
+fn arb_expr() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![
+        arb_column(),
+        (0..1000u32).prop_map(|n| n.to_string()),
+        "[a-z]{0,5}".prop_map(|s| format!("'{}'", s)),
+    ];
+    leaf.prop_recursive(3, 16, 2, |inner| {
+        prop_oneof![
+            (inner.clone(), select(vec!["+", "-", "*"]), inner.clone())
+                .prop_map(|(l, op, r)| format!("{} {} {}", l, op, r)),
+            inner.clone().prop_map(|e| format!("({})", e)),
+            (inner.clone(), inner).prop_map(|(l, r)| format!("COALESCE({}, {})", l, r)),
+        ]
+    })
+}
+
+fn arb_window() -> impl Strategy<Value = String> {
+    (
+        select(vec!["ROW_NUMBER()", "RANK()", "SUM(a)", "COUNT(*)"]),
+        arb_column(),
+        arb_column(),
+    )
+        .prop_map(|(f, p, o)| format!("{} OVER (PARTITION BY {} ORDER BY {})", f, p, o))
+}
+
+fn arb_items() -> impl Strategy<Value = String> {
+    let item = prop_oneof![
+        3 => arb_expr(),
+        1 => (arb_expr(), "x[0-9]").prop_map(|(e, alias)| format!("{} AS {}", e, alias)),
+        1 => arb_window(),
+        1 => Just("*".to_string()),
+    ];
+    prop::collection::vec(item, 1..4).prop_map(|items| items.join(", "))
+}
+
+fn arb_where() -> impl Strategy<Value = String> {
+    prop::option::of((arb_expr(), select(vec!["=", "<", ">", "<>"]), arb_expr())).prop_map(|p| {
+        p.map_or(String::new(), |(l, op, r)| {
+            format!(" WHERE {} {} {}", l, op, r)
+        })
+    })
+}
+
+/// A `SELECT` without `WITH`, `ORDER BY` or `LIMIT`, which can be an operand of a set operation:
+/// a plain select, a select from a subquery, a select filtered by a subquery or a set operation.
+fn arb_body() -> impl Strategy<Value = String> {
+    let leaf = (
+        arb_items(),
+        select(vec!["t1", "t2", "s.t3"]),
+        arb_where(),
+        prop::option::of(arb_column()),
+    )
+        .prop_map(|(items, table, filter, group)| {
+            let group = group.map_or(String::new(), |c| format!(" GROUP BY {}", c));
+            format!("SELECT {} FROM {}{}{}", items, table, filter, group)
+        });
+    leaf.prop_recursive(3, 24, 2, |inner| {
+        prop_oneof![
+            (arb_items(), inner.clone(), arb_where()).prop_map(|(items, sub, filter)| format!(
+                "SELECT {} FROM ({}) AS sub{}",
+                items, sub, filter
+            )),
+            (arb_items(), arb_column(), inner.clone()).prop_map(|(items, col, sub)| format!(
+                "SELECT {} FROM t1 WHERE {} IN ({})",
+                items, col, sub
+            )),
+            (
+                inner.clone(),
+                select(vec!["UNION", "UNION ALL", "EXCEPT", "INTERSECT"]),
+                inner
+            )
+                .prop_map(|(l, op, r)| format!("{} {} {}", l, op, r)),
+        ]
+    })
+}
+
+/// A syntactically valid `SELECT` statement, optionally with CTEs, `ORDER BY` and `LIMIT`.
+fn arb_select_query() -> impl Strategy<Value = String> {
+    (
+        prop::option::of(arb_body()),
+        arb_body(),
+        prop::option::of(prop::collection::vec(
+            (arb_column(), select(vec!["", " ASC", " DESC"])),
+            1..3,
+        )),
+        prop::option::of(1..100u32),
+    )
+        .prop_map(|(cte, body, order, limit)| {
+            let mut sql = String::new();
+            if let Some(cte) = cte {
+                sql.push_str(&format!("WITH cte AS ({}) ", cte));
+            }
+            sql.push_str(&body);
+            if let Some(order) = order {
+                let order: Vec<String> = order.into_iter().map(|(c, o)| c + o).collect();
+                sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+            }
+            if let Some(limit) = limit {
+                sql.push_str(&format!(" LIMIT {}", limit));
+            }
+            sql
+        })
+}
+
+fn parse_query<D: Dialect>(sql: &str, dialect: &D) -> Query {
+    let ast = Parser::parse_sql(dialect, sql).unwrap();
+    assert_eq!(1, ast.len(), "{}", sql);
+    match &ast[0] {
+        Statement::Query(q) => (**q).clone(),
+        _ => panic!("not a query: {}", sql),
+    }
+}
+
+/// The outer query and the derived table of `SELECT ... FROM (subquery) alias ...`.
+fn split_derived(query: &Query) -> (String, Query) {
+    match &query.body {
+        SetExpr::Select(select) => match &select.from[0].relation {
+            TableFactor::Derived { subquery, .. } => (
+                select
+                    .projection
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                (**subquery).clone(),
+            ),
+            _ => panic!("no derived table in {}", query),
+        },
+        _ => panic!("not a select: {}", query),
+    }
+}
+
+/// `count_query` counts the rows of `sql`: it is `SELECT count(*) FROM (q)`, where `q` is `sql`
+/// without its `ORDER BY`, which does not change the number of rows, and with its CTEs moved to the
+/// outer query.
+fn check_count_query<D: Dialect>(sql: &str, dialect: &D) -> Result<(), TestCaseError> {
+    let counted = count_query(&CXQuery::naked(sql), dialect).unwrap();
+    let counted = parse_query(counted.as_str(), dialect);
+    let (projection, inner) = split_derived(&counted);
+    prop_assert_eq!("count(*)", projection.to_lowercase());
+
+    let mut expected = parse_query(sql, dialect);
+    prop_assert_eq!(&expected.with, &counted.with);
+    expected.with = None;
+    if expected.offset.is_none() {
+        expected.order_by = vec![];
+    }
+    if let SetExpr::Select(select) = &mut expected.body {
+        select.sort_by = vec![];
+    }
+    prop_assert_eq!(expected, inner);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+
+    #[test]
+    fn test_count_query_generic(sql in arb_select_query()) {
+        check_count_query(&sql, &GenericDialect {})?;
+    }
+
+    #[test]
+    fn test_count_query_postgres(sql in arb_select_query()) {
+        check_count_query(&sql, &PostgreSqlDialect {})?;
+    }
+
+    #[test]
+    fn test_count_query_oracle(sql in arb_select_query()) {
+        let counted = count_query(&CXQuery::naked(sql.as_str()), &OracleDialect {}).unwrap();
+        let counted = parse_query(counted.as_str(), &OracleDialect {});
+        let (projection, inner) = split_derived(&counted);
+        prop_assert_eq!("count(*)", projection.to_lowercase());
+        prop_assert_eq!(parse_query(&sql, &OracleDialect {}), inner);
+    }
+
+    #[test]
+    fn test_limit1_query(sql in arb_select_query()) {
+        let limited = limit1_query(&CXQuery::naked(sql.as_str()), &GenericDialect {}).unwrap();
+        let mut limited = parse_query(limited.as_str(), &GenericDialect {});
+        prop_assert_eq!(
+            Some(Expr::Value(Value::Number("1".to_string(), false))),
+            limited.limit.take()
+        );
+        let mut expected = parse_query(&sql, &GenericDialect {});
+        expected.limit = None;
+        prop_assert_eq!(expected, limited);
+    }
+
+    #[test]
+    fn test_limit1_query_oracle(sql in arb_select_query()) {
+        let limited = limit1_query_oracle(&CXQuery::naked(sql.as_str())).unwrap();
+        let limited = parse_query(limited.as_str(), &OracleDialect {});
+        let (projection, inner) = split_derived(&limited);
+        prop_assert_eq!("*", projection);
+        prop_assert_eq!(parse_query(&sql, &OracleDialect {}), inner);
+    }
+}
+
+#[test]
+fn test_dblink_query_oracle() {
+    assert!(dblink_query_oracle("select * from emp@remote"));
+    assert!(dblink_query_oracle(
+        "SELECT * FROM hr.emp@remote.example.com e"
+    ));
+    assert!(dblink_query_oracle(r#"select * from "Emp"@"Remote""#));
+    assert!(dblink_query_oracle(
+        "select e.id from emp e join dept@remote d on e.dept = d.id"
+    ));
+    assert!(!dblink_query_oracle("select * from emp"));
+    assert!(!dblink_query_oracle(
+        "select * from users where email = 'a@example.com'"
+    ));
+    assert!(!dblink_query_oracle("select * from emp where id = @id"));
+}
+
+#[test]
+fn test_alias_generator() {
+    assert!(AliasGenerator::new(9).is_err());
+    let mut aliases = AliasGenerator::new(30).unwrap();
+    assert_eq!("CXTMPTAB_COUNT", aliases.alias("CXTMPTAB_COUNT"));
+
+    let mut seen = HashSet::new();
+    seen.insert("CXTMPTAB_COUNT".to_string());
+    let long = "CXTMPTAB_A_VERY_LONG_DERIVED_TABLE_NAME";
+    let names = (0..1000)
+        .map(|i| format!("{}_{}", long, i))
+        .chain(vec![long.to_string(); 100])
+        .chain(vec![
+            "cxtmptab_count".to_string(),
+            "CXTMPTAB_COUNT".to_string(),
+        ])
+        .chain(vec!["ÉTÉ_".repeat(10)]);
+    for name in names {
+        let alias = aliases.alias(&name);
+        assert!(alias.len() <= 30, "{} is longer than 30 bytes", alias);
+        assert!(seen.insert(alias.to_uppercase()), "{} is not unique", alias);
+    }
+}
+
+#[test]
+fn test_single_col_partition_query_oracle() {
+    let sql = "select * from t";
+    assert_eq!(
+        "SELECT * FROM (select * from t) CXTMPTAB_PART \
+         WHERE CXTMPTAB_PART.k >= 0 AND CXTMPTAB_PART.k < 10",
+        single_col_partition_query_oracle(sql, "k", 0, 10, false)
+    );
+    assert_eq!(
+        single_col_partition_query_oracle(sql, "k", 0, 10, false),
+        single_col_partition_query(sql, "k", 0, 10, &OracleDialect {}).unwrap()
+    );
+    assert_eq!(
+        "SELECT * FROM (select * from t) CXTMPTAB_PART \
+         WHERE (CXTMPTAB_PART.k >= 10 AND CXTMPTAB_PART.k < 20 OR CXTMPTAB_PART.k IS NULL)",
+        single_col_partition_query_oracle(sql, "k", 10, 20, true)
+    );
+}
+
+#[test]
+fn test_null_partition() {
+    assert_eq!(0, null_partition(Some(NullsOrder::First), 4));
+    assert_eq!(3, null_partition(Some(NullsOrder::Last), 4));
+    // Oracle sorts them last in ascending order
+    assert_eq!(3, null_partition(None, 4));
+    assert_eq!(0, null_partition(None, 1));
+    assert_eq!(0, null_partition(None, 0));
+}
+
+#[test]
+fn test_cxquery_hash() {
+    let queries = vec![
+        CXQuery::naked("select * from t where k < 10"),
+        CXQuery::naked("select * from t where k >= 10"),
+        CXQuery::naked("select * from t where k < 10"),
+        CXQuery::Wrapped("select * from t where k < 10".to_string()),
+    ];
+    let unique: HashSet<CXQuery> = queries.iter().cloned().collect();
+    assert_eq!(3, unique.len());
+    assert!(unique.contains(&CXQuery::naked("select * from t where k >= 10")));
+    assert_ne!(queries[0], queries[3]);
+    assert_eq!(CXQuery::Naked("q"), CXQuery::Naked("q"));
+}
+
+#[test]
+fn test_is_count_query() {
+    let dialect = GenericDialect {};
+    for sql in [
+        "SELECT COUNT(*) FROM t",
+        "select count(1) from t where k > 0",
+        "SELECT count(*) AS n FROM (SELECT * FROM t) AS CXTMPTAB_COUNT",
+    ] {
+        assert!(CXQuery::naked(sql).is_count_query(&dialect), "{}", sql);
+    }
+    for sql in [
+        "SELECT * FROM t",
+        "SELECT k, COUNT(*) FROM t GROUP BY k",
+        "SELECT COUNT(DISTINCT k) FROM t",
+        "SELECT COUNT(k) FROM t",
+        "SELECT COUNT(*), MAX(k) FROM t",
+        "SELECT COUNT(*) FROM t UNION ALL SELECT COUNT(*) FROM u",
+        "not a query",
+    ] {
+        assert!(!CXQuery::naked(sql).is_count_query(&dialect), "{}", sql);
+    }
+}
+
+#[test]
+fn test_count_query_idempotent() {
+    let sql = CXQuery::naked("select * from t where k > 0");
+    let counted = count_query(&sql, &GenericDialect {}).unwrap();
+    assert_eq!(counted, count_query(&counted, &GenericDialect {}).unwrap());
+
+    let counted = count_query(&sql, &OracleDialect {}).unwrap();
+    assert_eq!(
+        "SELECT COUNT(*) FROM (select * from t where k > 0) CXTMPTAB_COUNT",
+        count_query(&counted, &OracleDialect {}).unwrap().as_str()
+    );
+
+    // the result of a count query of the user is a single row
+    let sql = CXQuery::naked("SELECT COUNT(*) FROM t");
+    assert_ne!(
+        sql.as_str(),
+        count_query(&sql, &GenericDialect {}).unwrap().as_str()
+    );
+}
+
+#[test]
+fn test_bounds_partition_query_oracle() {
+    let sql = "select * from t";
+    let query = |lower, upper, nulls| {
+        bounds_partition_query_oracle(
+            sql,
+            "k",
+            &PartitionBound {
+                lower,
+                upper,
+                nulls,
+            },
+            &mut AliasGenerator::new(30).unwrap(),
+        )
+    };
+    assert_eq!(
+        single_col_partition_query_oracle(sql, "k", 0, 10, false),
+        bounds_partition_query_oracle(
+            sql,
+            "k",
+            &PartitionBound::half_open(0, 10),
+            &mut AliasGenerator::new(30).unwrap()
+        )
+    );
+    assert_eq!(
+        "SELECT * FROM (select * from t) CXTMPTAB_PART \
+         WHERE CXTMPTAB_PART.k > 0 AND CXTMPTAB_PART.k <= 10",
+        query(Bound::Excluded(0), Bound::Included(10), false)
+    );
+    assert_eq!(
+        "SELECT * FROM (select * from t) CXTMPTAB_PART WHERE CXTMPTAB_PART.k < -5",
+        query(Bound::Unbounded, Bound::Excluded(-5), false)
+    );
+    assert_eq!(
+        "SELECT * FROM (select * from t) CXTMPTAB_PART \
+         WHERE (CXTMPTAB_PART.k >= 100 OR CXTMPTAB_PART.k IS NULL)",
+        query(Bound::Included(100), Bound::Unbounded, true)
+    );
+    assert_eq!(
+        "SELECT * FROM (select * from t) CXTMPTAB_PART WHERE CXTMPTAB_PART.k IS NOT NULL",
+        query(Bound::Unbounded, Bound::Unbounded, false)
+    );
+}
+
+#[test]
+fn test_rewrite_aliases_oracle() {
+    // the rewrites of a query share a generator, so a nested rewrite does not reuse an alias
+    let mut aliases = AliasGenerator::new(30).unwrap();
+    let bound = PartitionBound::half_open(0, 10);
+    let inner = bounds_partition_query_oracle("select * from t", "k", &bound, &mut aliases);
+    let outer = bounds_partition_query_oracle(&inner, "k", &bound, &mut aliases);
+    let count = count_query_oracle(&CXQuery::Wrapped(outer), &mut aliases);
+
+    // each alias follows the parenthesis closing its derived table, after the one of `COUNT(*)`
+    let used: Vec<_> = count
+        .as_str()
+        .split(") ")
+        .skip(2)
+        .filter_map(|rest| rest.split(' ').next())
+        .collect();
+    assert_eq!(3, used.len(), "{}", count);
+    assert_eq!("CXTMPTAB_PART", used[0]);
+    assert_eq!("CXTMPTAB_COUNT", used[2]);
+    assert_ne!(used[0], used[1]);
+    assert!(used[1].starts_with("CXTMPTAB_PART_"), "{}", used[1]);
+    assert!(used.iter().all(|alias| alias.len() <= 30));
+}
+
+fn merge_batch() -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("Id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("score", DataType::Float64, true),
+        Field::new("ts", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+    ]));
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec![Some("it's \\' OR 1=1"), None])),
+            Arc::new(Float64Array::from(vec![f64::NAN, f64::INFINITY])),
+            Arc::new(TimestampMicrosecondArray::from(vec![
+                Some(1_600_000_000_123_456),
+                None,
+            ])),
+        ],
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_merge_literals() {
+    let batch = merge_batch();
+
+    let sql = upsert_query_postgres("public.Merge", &["Id"], &batch).unwrap();
+    assert_eq!(
+        "INSERT INTO \"public\".\"Merge\" (\"Id\", \"name\", \"score\", \"ts\") VALUES \
+         (1, 'it''s \\'' OR 1=1', 'NaN'::float8, TIMESTAMP '2020-09-13 12:26:40.123456'), \
+         (2, NULL, 'Infinity'::float8, NULL) \
+         ON CONFLICT (\"Id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\", \
+         \"score\" = EXCLUDED.\"score\", \"ts\" = EXCLUDED.\"ts\"",
+        sql
+    );
+    Parser::parse_sql(&PostgreSqlDialect {}, &sql).unwrap();
+
+    let sql = merge_query_oracle("Merge", &["Id"], &batch).unwrap();
+    assert!(sql.contains("BINARY_DOUBLE_NAN \"score\""), "{}", sql);
+    assert!(sql.contains("BINARY_DOUBLE_INFINITY \"score\""), "{}", sql);
+    assert!(
+        sql.contains("TIMESTAMP '2020-09-13 12:26:40.123456' \"ts\""),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.contains("CXTMPTAB_TGT.\"Id\" = CXTMPTAB_SRC.\"Id\""),
+        "{}",
+        sql
+    );
+
+    // MySQL takes backslash escapes in its strings, and has no NaN
+    assert!(upsert_query_mysql("Merge", &["Id"], &batch).is_err());
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(batch.schema().fields()[..2].to_vec())),
+        batch.columns()[..2].to_vec(),
+    )
+    .unwrap();
+    let sql = upsert_query_mysql("Merge", &["Id"], &batch).unwrap();
+    assert_eq!(
+        "INSERT INTO `Merge` (`Id`, `name`) VALUES (1, 'it''s \\\\'' OR 1=1'), (2, NULL) \
+         ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)",
+        sql
+    );
+}
+
+#[test]
+fn test_merge_chunks() {
+    let n = 2 * MERGE_CHUNK_ROWS + 1;
+    let schema = Arc::new(Schema::new(vec![Field::new("k", DataType::Int64, false)]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![Arc::new(Int64Array::from(
+            (0..n as i64).collect::<Vec<_>>(),
+        ))],
+    )
+    .unwrap();
+    let lens: Vec<_> = chunks(&batch).map(|c| c.num_rows()).collect();
+    assert_eq!(vec![MERGE_CHUNK_ROWS, MERGE_CHUNK_ROWS, 1], lens);
+
+    let last = chunks(&batch).last().unwrap();
+    assert_eq!(
+        "SELECT COUNT(*) FROM `t` WHERE (`k`) IN ((2000))",
+        matched_count_query("t", &["k"], &last, &MySqlDialect {}).unwrap()
+    );
+    assert_eq!(
+        "SELECT COUNT(*) FROM \"t\" WHERE (\"k\") IN ((2000))",
+        matched_count_query("t", &["k"], &last, &OracleDialect {}).unwrap()
+    );
+}
@@ -0,0 +1,73 @@
+use connectorx::{destinations::text_buffer::TextBuffer, prelude::*};
+
+/// A parser of a single text column, handing out its values as borrowed `&str`.
+struct TextColumn {
+    rows: Vec<Option<String>>,
+    current: usize,
+}
+
+impl<'r> Produce<'r, Option<&'r str>> for TextColumn {
+    type Error = ConnectorXError;
+
+    fn produce(&'r mut self) -> Result<Option<&'r str>, ConnectorXError> {
+        let value = self.rows[self.current].as_deref();
+        self.current += 1;
+        Ok(value)
+    }
+}
+
+#[test]
+fn test_text_buffer() {
+    let rows = vec![
+        Some("hello".to_string()),
+        None,
+        Some(String::new()),
+        Some("wörld".to_string()),
+        Some("x".repeat(1000)),
+        None,
+        None,
+        Some("🦀".to_string()),
+        Some("ninth".to_string()),
+    ];
+    let mut parser = TextColumn {
+        rows: rows.clone(),
+        current: 0,
+    };
+    let mut buffer = TextBuffer::with_capacity(rows.len(), 0);
+    for _ in 0..rows.len() {
+        buffer.produce(&mut parser).unwrap();
+    }
+
+    assert_eq!(rows.len(), buffer.len());
+    assert_eq!(3, buffer.null_count());
+    assert_eq!(rows.len() + 1, buffer.offsets().len());
+    assert_eq!(0, buffer.offsets()[0]);
+    assert_eq!(buffer.values().len() as i64, buffer.offsets()[rows.len()]);
+    assert_eq!(vec![0b0001_1101, 0b0000_0001], buffer.validity());
+
+    // the values as a consumer of the buffers sees them, from the offsets and the validity bitmap
+    let rebuilt: Vec<Option<String>> = buffer
+        .offsets()
+        .windows(2)
+        .enumerate()
+        .map(|(i, w)| {
+            (buffer.validity()[i / 8] & (1 << (i % 8)) != 0).then(|| {
+                String::from_utf8(buffer.values()[w[0] as usize..w[1] as usize].to_vec()).unwrap()
+            })
+        })
+        .collect();
+    assert_eq!(rows, rebuilt);
+    assert_eq!(
+        rows,
+        buffer
+            .iter()
+            .map(|s| s.map(String::from))
+            .collect::<Vec<_>>()
+    );
+
+    buffer.clear();
+    assert!(buffer.is_empty());
+    assert_eq!(&[0], buffer.offsets());
+    buffer.push(Some("again"));
+    assert_eq!(Some("again"), buffer.get(0));
+}
@@ -1,6 +1,7 @@
 DROP TABLE test_table;
 DROP TABLE test_types;
 DROP TABLE test_issue;
+DROP TABLE test_securefile;
 
 CREATE TABLE test_table(
     test_int NUMBER(7),
@@ -14,6 +15,9 @@ INSERT INTO test_table VALUES (2333, NULL, NULL);
 INSERT INTO test_table VALUES (4, NULL, -4.44);
 INSERT INTO test_table VALUES (5, 'str05', NULL);
 
+COMMENT ON TABLE test_table IS 'table for the connectorx tests';
+COMMENT ON COLUMN test_table.test_int IS 'an integer';
+
 CREATE TABLE test_issue(
     v BINARY_FLOAT
 );
@@ -49,3 +53,57 @@ INSERT INTO test_types VALUES (5, 22, -0.1, 123.455, 3.1415926535, -111111.2345,
 INSERT INTO test_types VALUES (5, 22, -0.1, 123.455, 3.1415926535, -111111.2345, 'char2', 'varchar222', 'aab123', ')>KDS)(F*&%J', TO_DATE('2020-05-21', 'YYYY-MM-DD'), TO_TIMESTAMP('2020-05-21 01:02:33', 'YYYY-MM-DD HH24:MI:SS'), TO_TIMESTAMP_TZ('1899-12-01 11:00:00 +1:00',
    'YYYY-MM-DD HH:MI:SS TZH:TZM'), '13ab', '39af');
 INSERT INTO test_types VALUES (NULL, 100, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL);
+
+
+CREATE TABLE test_securefile(
+    id NUMBER(7),
+    doc CLOB,
+    note CLOB
+)
+LOB (doc) STORE AS SECUREFILE (COMPRESS HIGH DEDUPLICATE)
+LOB (note) STORE AS BASICFILE;
+
+INSERT INTO test_securefile VALUES (1, 'a document', 'a note');
+
+-- returns 1 after sleeping `secs` seconds, to make a query slow
+CREATE OR REPLACE FUNCTION cx_sleep(secs NUMBER) RETURN NUMBER IS
+BEGIN
+    DBMS_SESSION.SLEEP(secs);
+    RETURN 1;
+END;
+/
+
+CREATE TABLE test_timestamp_ltz(
+    id NUMBER(7),
+    ts TIMESTAMP WITH LOCAL TIME ZONE
+);
+
+INSERT INTO test_timestamp_ltz VALUES (1, TO_TIMESTAMP_TZ('2021-06-01 12:00:00 +02:00', 'YYYY-MM-DD HH24:MI:SS TZH:TZM'));
+INSERT INTO test_timestamp_ltz VALUES (2, NULL);
+
+-- the amount of id 2 is too large for an i64
+CREATE TABLE test_dirty(
+    id NUMBER(7),
+    amount NUMBER(38)
+);
+
+INSERT INTO test_dirty VALUES (1, 10);
+INSERT INTO test_dirty VALUES (2, 100000000000000000000);
+INSERT INTO test_dirty VALUES (3, 30);
+
+CREATE OR REPLACE TYPE cx_address_type AS OBJECT (
+    street VARCHAR2(40),
+    city VARCHAR2(40)
+);
+/
+
+CREATE TABLE test_address(
+    id NUMBER(7),
+    address cx_address_type
+);
+
+INSERT INTO test_address VALUES (1, cx_address_type('1 Main St', 'Springfield'));
+INSERT INTO test_address VALUES (2, NULL);
+
+-- the client result cache of test_client_result_cache, effective once the instance is restarted
+ALTER SYSTEM SET CLIENT_RESULT_CACHE_SIZE = 1M SCOPE = SPFILE;